@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::Parser;
 
 #[derive(Parser)]
@@ -6,8 +8,75 @@ pub struct Cli {
     pub player: Option<PlayerType>,
     #[arg(long, short, default_value = "rooms5")]
     pub room_generator: Option<RoomGeneratorType>,
+    /// longest a steady stream of cluster changes can hold back a new `k8s-live` floorplan
     #[arg(long, short, default_value = "60")]
     pub generator_poll_secs: Option<u8>,
+    /// how long the watched cluster must be quiet before a new `k8s-live` floorplan is built
+    #[arg(long, default_value = "500")]
+    pub watch_debounce_millis: Option<u64>,
+    /// manifests for `k8s-file`: a file, directory or glob, repeat for more - yaml (including
+    /// multi-document and `kubectl get -o yaml` lists) or json
+    #[arg(long = "k8s-file")]
+    pub k8s_files: Vec<String>,
+    /// kubeconfig context for `k8s-live`, default is the current context - repeat for more, with
+    /// more than one a Multiverse room opens onto the lobby of each cluster
+    #[arg(long = "context")]
+    pub contexts: Vec<String>,
+    /// kubeconfig file for `k8s-live`, default is `$KUBECONFIG` or `~/.kube/config`
+    #[arg(long)]
+    pub kubeconfig: Option<PathBuf>,
+    /// only walk this namespace, repeat for more - default is every namespace
+    #[arg(long = "namespace")]
+    pub namespaces: Vec<String>,
+    /// never walk this namespace, repeat for more
+    #[arg(long = "exclude-namespace")]
+    pub excluded_namespaces: Vec<String>,
+    /// kind to give a hallway in every namespace for `k8s-live`, repeat for more - by kind, plural
+    /// or `kind.group`, or `*` for every kind the cluster serves including custom resources but
+    /// not Event or Secret, which have to be named. the default is Deployment, StatefulSet,
+    /// DaemonSet, CronJob, Job, ReplicaSet, Service, ConfigMap, Ingress, PersistentVolumeClaim and
+    /// Pod, plus PersistentVolume and StorageClass off the lobby
+    #[arg(long = "kind")]
+    pub kinds: Vec<String>,
+    /// never give this kind a hallway, repeat for more
+    #[arg(long = "exclude-kind")]
+    pub excluded_kinds: Vec<String>,
+    /// label selector every listed kind must match, ie: `app.kubernetes.io/part-of=payments`
+    #[arg(long)]
+    pub selector: Option<String>,
+    /// what opens off the cluster lobby for `k8s-file` and `k8s-live`: namespaces, with nodes
+    /// down a hallway, or nodes - handy for capacity debugging - with namespaces down a hallway
+    #[arg(long, default_value = "namespace")]
+    pub hierarchy: Option<Hierarchy>,
+    /// how a namespace's rooms are gathered for `k8s-file` and `k8s-live`: a hallway per kind, or a
+    /// suite per application holding its deployments, services, configmaps and so on together -
+    /// rooms that belong to no application keep their kind's hallway. "g" cycles through them
+    #[arg(long, default_value = "kind")]
+    pub grouping: Option<Grouping>,
+    /// most log lines a container room's log panel asks for and keeps while following
+    #[arg(long, default_value = "200")]
+    pub log_lines: Option<usize>,
+    /// offer cluster actions in rooms: delete a pod, rollout-restart or scale a deployment and
+    /// cordon a node - each is dry run and confirmed first. without it the game is read-only
+    #[arg(long, requires = "audit_log")]
+    pub allow_mutations: bool,
+    /// every cluster action and its dry run is appended here, one json object per line - needed by
+    /// `--allow-mutations`
+    #[arg(long)]
+    pub audit_log: Option<PathBuf>,
+    /// every distinct floorplan is kept here with the time it was generated, for the timeline -
+    /// nothing is recorded without it. keep one directory per cluster so timelines do not mix
+    #[arg(long)]
+    pub history_dir: Option<PathBuf>,
+    /// most floorplans the history keeps, the oldest are deleted first - 0 records nothing
+    #[arg(long, default_value_t = 200)]
+    pub history_limit: usize,
+    /// floorplan snapshot to load when `--room-generator=snapshot` (`.json` or binary)
+    #[arg(long)]
+    pub snapshot_path: Option<PathBuf>,
+    /// write every generated floorplan to this snapshot file (`.json` or binary)
+    #[arg(long)]
+    pub save_snapshot: Option<PathBuf>,
 }
 
 #[derive(clap::ValueEnum, Clone, Default)]
@@ -25,4 +94,24 @@ pub enum RoomGeneratorType {
     Rooms25,
     K8sFile,
     K8sLive,
+    Snapshot,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Hierarchy {
+    #[default]
+    Namespace,
+    Node,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Grouping {
+    #[default]
+    Kind,
+    /// a suite per `app.kubernetes.io/part-of` label
+    PartOf,
+    /// a suite per `app.kubernetes.io/instance` label
+    Instance,
+    /// a suite per helm release, from the `meta.helm.sh/release-name` annotation
+    HelmRelease,
 }
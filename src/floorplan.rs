@@ -1,37 +1,361 @@
 #![allow(dead_code)]
 
 use bevy::prelude::*;
-use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex};
 use petgraph::visit::EdgeRef;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 
 #[derive(Event)]
 pub struct FloorPlanEvent {
     pub floorplan: FloorPlan,
 }
 
-#[derive(Component, Debug, Clone, Default, Eq, PartialEq, Hash, States)]
+/// what a room represents, so rendering never has to guess from the room's name
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum RoomKind {
+    /// the room above every cluster lobby when the world spans several kubeconfig contexts
+    Multiverse,
+    Cluster,
+    Namespace,
+    Hallway,
+    /// an application's rooms gathered by a label or its helm release, see `Grouping`
+    Suite,
+    Deployment,
+    DaemonSet,
+    StatefulSet,
+    ReplicaSet,
+    CronJob,
+    Job,
+    Pod,
+    Service,
+    ConfigMap,
+    Ingress,
+    /// one `host` of an ingress rule, `*` when the rule names none
+    IngressHost,
+    /// one `path` under an ingress host, leading to its backend service
+    IngressPath,
+    Container,
+    VolumeMount,
+    /// made from the names pods mount, a secret's data is never read
+    Secret,
+    PersistentVolumeClaim,
+    PersistentVolume,
+    StorageClass,
+    /// made from the `spec.nodeName` of the pods scheduled on it
+    Node,
+    Other(String),
+    #[default]
+    Unknown,
+}
+
+impl RoomKind {
+    pub fn from_k8s_kind(kind: &str) -> Self {
+        match kind {
+            "Namespace" => Self::Namespace,
+            "Deployment" => Self::Deployment,
+            "DaemonSet" => Self::DaemonSet,
+            "StatefulSet" => Self::StatefulSet,
+            "CronJob" => Self::CronJob,
+            "Job" => Self::Job,
+            "ReplicaSet" => Self::ReplicaSet,
+            "Pod" => Self::Pod,
+            "Service" => Self::Service,
+            "ConfigMap" => Self::ConfigMap,
+            "Ingress" => Self::Ingress,
+            "Container" => Self::Container,
+            "VolumeMount" => Self::VolumeMount,
+            "Secret" => Self::Secret,
+            "PersistentVolumeClaim" => Self::PersistentVolumeClaim,
+            "PersistentVolume" => Self::PersistentVolume,
+            "StorageClass" => Self::StorageClass,
+            "Node" => Self::Node,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for RoomKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Other(kind) => write!(f, "{kind}"),
+            Self::VolumeMount => write!(f, "Volume Mount"),
+            Self::IngressHost => write!(f, "Ingress Host"),
+            Self::IngressPath => write!(f, "Ingress Path"),
+            kind => write!(f, "{kind:?}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Health {
+    #[default]
+    Healthy,
+    Pending,
+    Failing,
+}
+
+/// the reason given to a service whose selector matches no pod
+pub const NO_MATCHING_PODS: &str = "NoMatchingPods";
+
+/**
+* reasons that mean the resource is broken rather than starting up - container waiting/terminated
+* reasons, and a service with nothing behind it
+*/
+const FAILING_REASONS: [&str; 8] = [
+    "CrashLoopBackOff",
+    "ImagePullBackOff",
+    "ErrImagePull",
+    "CreateContainerConfigError",
+    "CreateContainerError",
+    "OOMKilled",
+    "Error",
+    NO_MATCHING_PODS,
+];
+
+/**
+* runtime state of a workload - `ready`/`total` count ready containers for pods and available
+* replicas for controllers
+*/
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct ResourceStatus {
+    pub phase: Option<String>,
+    pub ready: u32,
+    pub total: u32,
+    pub restarts: u32,
+    pub reason: Option<String>,
+}
+
+impl ResourceStatus {
+    pub fn health(&self) -> Health {
+        let failing_reason = self
+            .reason
+            .as_deref()
+            .is_some_and(|reason| FAILING_REASONS.contains(&reason));
+        match self.phase.as_deref() {
+            Some("Failed") => Health::Failing,
+            _ if failing_reason => Health::Failing,
+            Some("Succeeded") => Health::Healthy,
+            Some("Pending") => Health::Pending,
+            None if self.total > 0 && self.ready == 0 => Health::Failing,
+            _ if self.ready < self.total => Health::Pending,
+            _ => Health::Healthy,
+        }
+    }
+}
+
+impl std::fmt::Display for ResourceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(phase) = &self.phase {
+            write!(f, "{phase} ")?;
+        }
+        write!(f, "{}/{} ready", self.ready, self.total)?;
+        if self.restarts > 0 {
+            write!(f, ", {} restarts", self.restarts)?;
+        }
+        if let Some(reason) = &self.reason {
+            write!(f, ", {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Component, Debug, Clone, Default, Eq, PartialEq, Hash, States, Serialize, Deserialize)]
 pub struct Room {
     pub id: String,
     pub name: String,
+    #[serde(default)]
+    pub kind: RoomKind,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    /// anything else worth showing that doesn't deserve its own field, ie: a container's image
+    #[serde(default)]
+    pub attributes: BTreeMap<String, String>,
+    #[serde(default)]
+    pub status: Option<ResourceStatus>,
+    /// the object the room stands for as yaml, trimmed of managed fields - what the inspector shows
+    #[serde(default)]
+    pub source: Option<String>,
+    /// why its doors stay shut, ie: the user may not list what a hallway would hold
+    #[serde(default)]
+    pub locked: Option<String>,
+    /// the kubeconfig context of the cluster the room is in, when the world spans several
+    #[serde(default)]
+    pub context: Option<String>,
+}
+
+impl Room {
+    pub fn health(&self) -> Health {
+        self.status
+            .as_ref()
+            .map_or(Health::Healthy, ResourceStatus::health)
+    }
+
+    /**
+     * equal in everything the world shows - `source` is left out, it's only there for the inspector
+     * and moves with every status write and `resourceVersion` bump
+     */
+    pub fn same_as(&self, other: &Self) -> bool {
+        let Self {
+            id,
+            name,
+            kind,
+            namespace,
+            labels,
+            attributes,
+            status,
+            source: _,
+            locked,
+            context,
+        } = self;
+        *id == other.id
+            && *name == other.name
+            && *kind == other.kind
+            && *namespace == other.namespace
+            && *labels == other.labels
+            && *attributes == other.attributes
+            && *status == other.status
+            && *locked == other.locked
+            && *context == other.context
+    }
+
+    /// the room as a diff describes it, without the inspector's copy of the object
+    fn without_source(&self) -> Self {
+        Self {
+            source: None,
+            ..self.clone()
+        }
+    }
+}
+
+/**
+* what a door stands for - most just connect a room to what contains it, owner doors follow
+* `ownerReferences` and know whether the owner is the controlling one
+*/
+#[derive(
+    Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize,
+)]
+pub enum DoorKind {
+    #[default]
+    Passage,
+    /// from an owned object to one of its owners
+    ToOwner { controller: bool },
+    /// from an owner to one of the objects it owns
+    ToOwned { controller: bool },
 }
 
-#[derive(Component, Debug, Clone, Default, Eq, PartialEq, Hash, States)]
+impl DoorKind {
+    /// how the room on the far side of the door relates to this one
+    pub const fn describe(self) -> &'static str {
+        match self {
+            Self::Passage => "door to",
+            Self::ToOwner { controller: true } => "controlled by",
+            Self::ToOwner { controller: false } => "owned by",
+            Self::ToOwned { controller: true } => "controls",
+            Self::ToOwned { controller: false } => "owns",
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Default, Eq, PartialEq, Hash, States, Serialize, Deserialize)]
 pub struct Door {
     pub id: String,
     pub name: String,
     pub is_exit: bool,
+    #[serde(default)]
+    pub kind: DoorKind,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, States)]
 pub enum FloorPlanError {
     RoomNotFound(String),
     DoorNotFound(String),
+    PathNotFound(String),
     ServiceError(String),
+    SnapshotError(String),
+    /// the cluster could not be reached at all - dns, refused connections, timeouts
+    ConnectionError(String),
+    /// the cluster answered but rejected our credentials, ie: an expired token
+    AuthError(String),
+    /// a manifest file is missing or is not valid yaml / json
+    ManifestError(String),
+    /// the app is shutting down or never registered `FloorPlanEvent`
+    EventChannelMissing,
+}
+
+impl std::fmt::Display for FloorPlanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RoomNotFound(id) => write!(f, "room not found: {id}"),
+            Self::DoorNotFound(id) => write!(f, "door not found: {id}"),
+            Self::PathNotFound(msg) => write!(f, "no path: {msg}"),
+            Self::ServiceError(msg) => write!(f, "service error: {msg}"),
+            Self::SnapshotError(msg) => write!(f, "snapshot error: {msg}"),
+            Self::ConnectionError(msg) => write!(f, "cannot reach cluster: {msg}"),
+            Self::AuthError(msg) => write!(f, "not authorized: {msg}"),
+            Self::ManifestError(msg) => write!(f, "bad manifest: {msg}"),
+            Self::EventChannelMissing => write!(f, "no FloorPlanEvent channel"),
+        }
+    }
 }
 
+impl std::error::Error for FloorPlanError {}
+
 pub type FloorPlanResult<T> = Result<T, FloorPlanError>;
 
+/// a door identified by the ids of the rooms it connects rather than by its own id
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct DoorChange {
+    pub from: String,
+    pub to: String,
+    pub door: Door,
+}
+
+/**
+* what changed between two floorplans - rooms are matched on `Room::id` and doors on the ids of the
+* rooms they connect and their kind, so a rename keeps its identity while a replaced pod shows up as
+* removed + added. parallel doors are matched by name first, whatever is left pairs up as renames.
+* rooms that kept their name but whose status, labels or attributes moved are `updated_rooms`. a
+* room's `source` is neither compared nor carried, so a status write alone changes nothing
+*/
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct FloorPlanDiff {
+    pub added_rooms: Vec<Room>,
+    pub removed_rooms: Vec<Room>,
+    pub renamed_rooms: Vec<(Room, Room)>,
+    pub updated_rooms: Vec<(Room, Room)>,
+    pub added_doors: Vec<DoorChange>,
+    pub removed_doors: Vec<DoorChange>,
+    pub renamed_doors: Vec<(DoorChange, DoorChange)>,
+}
+
+impl FloorPlanDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_rooms.is_empty()
+            && self.removed_rooms.is_empty()
+            && self.renamed_rooms.is_empty()
+            && self.updated_rooms.is_empty()
+            && self.added_doors.is_empty()
+            && self.removed_doors.is_empty()
+            && self.renamed_doors.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "rooms +{} -{} ~{} *{} doors +{} -{} ~{}",
+            self.added_rooms.len(),
+            self.removed_rooms.len(),
+            self.renamed_rooms.len(),
+            self.updated_rooms.len(),
+            self.added_doors.len(),
+            self.removed_doors.len(),
+            self.renamed_doors.len(),
+        )
+    }
+}
+
 #[derive(Debug, Clone, Default, States)]
 pub struct FloorPlan {
     pub graph: DiGraph<Room, Door>,
@@ -87,6 +411,10 @@ impl FloorPlan {
             .ok_or_else(|| FloorPlanError::RoomNotFound(room_index.index().to_string()))
     }
 
+    pub fn get_start_room_id(&self) -> Option<&str> {
+        self.start_room_id.as_deref()
+    }
+
     pub fn get_all_room_ids(&self) -> Vec<String> {
         self.room_index_map.keys().cloned().collect()
     }
@@ -158,6 +486,13 @@ impl FloorPlan {
             .ok_or_else(|| FloorPlanError::RoomNotFound(room_id.to_string()))
     }
 
+    pub fn get_room_by_id_mut(&mut self, room_id: &str) -> FloorPlanResult<&mut Room> {
+        let room_index = self.get_room_idx_by_id(room_id)?;
+        self.graph
+            .node_weight_mut(room_index)
+            .ok_or_else(|| FloorPlanError::RoomNotFound(room_id.to_string()))
+    }
+
     pub fn get_room_by_id(&self, room_id: &str) -> FloorPlanResult<&Room> {
         self.get_room_idx_by_id(room_id).map_or_else(
             |_| Err(FloorPlanError::RoomNotFound(room_id.to_string())),
@@ -169,6 +504,162 @@ impl FloorPlan {
         )
     }
 
+    fn rooms_by_id(&self) -> BTreeMap<&str, &Room> {
+        self.graph
+            .node_weights()
+            .map(|room| (room.id.as_str(), room))
+            .collect()
+    }
+
+    /// several doors can join the same two rooms, so each endpoint + kind keeps all of them
+    fn doors_by_endpoints(&self) -> BTreeMap<(&str, &str, DoorKind), Vec<DoorChange>> {
+        let mut doors: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for edge in self.graph.edge_references() {
+            let (Some(from), Some(to)) = (
+                self.graph.node_weight(edge.source()),
+                self.graph.node_weight(edge.target()),
+            ) else {
+                continue;
+            };
+            doors
+                .entry((from.id.as_str(), to.id.as_str(), edge.weight().kind))
+                .or_default()
+                .push(DoorChange {
+                    from: from.id.clone(),
+                    to: to.id.clone(),
+                    door: edge.weight().clone(),
+                });
+        }
+        doors
+    }
+
+    /// structural changes needed to turn `self` into `other`
+    pub fn diff(&self, other: &Self) -> FloorPlanDiff {
+        let mut diff = FloorPlanDiff::default();
+
+        let before = self.rooms_by_id();
+        let after = other.rooms_by_id();
+        for (id, room) in &before {
+            match after.get(id) {
+                None => diff.removed_rooms.push(room.without_source()),
+                Some(new_room) if new_room.name != room.name => diff
+                    .renamed_rooms
+                    .push((room.without_source(), new_room.without_source())),
+                Some(new_room) if !new_room.same_as(room) => diff
+                    .updated_rooms
+                    .push((room.without_source(), new_room.without_source())),
+                Some(_) => {}
+            }
+        }
+        diff.added_rooms = after
+            .iter()
+            .filter(|(id, _)| !before.contains_key(*id))
+            .map(|(_, room)| room.without_source())
+            .collect();
+
+        let before = self.doors_by_endpoints();
+        let mut after = other.doors_by_endpoints();
+        for (endpoints, doors) in before {
+            let mut new_doors = after.remove(&endpoints).unwrap_or_default();
+            // doors that kept their name are unchanged, the rest pair up as renames
+            let mut unmatched = Vec::new();
+            for door in doors {
+                match new_doors
+                    .iter()
+                    .position(|new_door| new_door.door.name == door.door.name)
+                {
+                    Some(position) => {
+                        new_doors.remove(position);
+                    }
+                    None => unmatched.push(door),
+                }
+            }
+            let mut new_doors = new_doors.into_iter();
+            for door in unmatched {
+                match new_doors.next() {
+                    Some(new_door) => diff.renamed_doors.push((door, new_door)),
+                    None => diff.removed_doors.push(door),
+                }
+            }
+            diff.added_doors.extend(new_doors);
+        }
+        diff.added_doors.extend(after.into_values().flatten());
+
+        diff
+    }
+
+    /**
+     * the fewest doors to walk through to get from one room to another, in the order they must be
+     * opened - empty when already there
+     */
+    pub fn shortest_path(&self, from_id: &str, to_id: &str) -> FloorPlanResult<Vec<&Door>> {
+        let from = self.get_room_idx_by_id(from_id)?;
+        let to = self.get_room_idx_by_id(to_id)?;
+
+        let mut came_through: HashMap<NodeIndex, EdgeIndex> = HashMap::new();
+        let mut visited = HashSet::from([from]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(room_index) = queue.pop_front() {
+            if room_index == to {
+                let mut doors = Vec::new();
+                let mut current = to;
+                while let Some(&edge_index) = came_through.get(&current) {
+                    doors.push(&self.graph[edge_index]);
+                    current = self
+                        .graph
+                        .edge_endpoints(edge_index)
+                        .map(|(source, _)| source)
+                        .ok_or_else(|| {
+                            FloorPlanError::DoorNotFound(edge_index.index().to_string())
+                        })?;
+                }
+                doors.reverse();
+                return Ok(doors);
+            }
+            for edge in self.graph.edges(room_index) {
+                if visited.insert(edge.target()) {
+                    came_through.insert(edge.target(), edge.id());
+                    queue.push_back(edge.target());
+                }
+            }
+        }
+        Err(FloorPlanError::PathNotFound(format!(
+            "{from_id} -> {to_id}"
+        )))
+    }
+
+    /**
+     * copy every room and door of one cluster's floorplan into this one, their ids prefixed with
+     * the context so clusters never collide. returns the id its start room, the lobby, now has
+     */
+    pub fn add_cluster(&mut self, cluster: &Self, context: &str) -> FloorPlanResult<String> {
+        let in_context = |id: &str| format!("{context}/{id}");
+        let mut indexes = HashMap::new();
+        for (index, room) in cluster
+            .graph
+            .node_indices()
+            .zip(cluster.graph.node_weights())
+        {
+            let room = Room {
+                id: in_context(&room.id),
+                context: Some(context.to_string()),
+                ..room.clone()
+            };
+            indexes.insert(index, self.add_room(room));
+        }
+        for edge in cluster.graph.edge_references() {
+            let door = Door {
+                id: in_context(&edge.weight().id),
+                ..edge.weight().clone()
+            };
+            self.add_door(indexes[&edge.source()], indexes[&edge.target()], door);
+        }
+        cluster
+            .get_start_room_id()
+            .map(in_context)
+            .ok_or_else(|| FloorPlanError::RoomNotFound(format!("{context} lobby")))
+    }
+
     pub fn get_doors_and_connected_rooms(
         &self,
         room_id: &str,
@@ -200,10 +691,12 @@ mod tests {
         let room1 = Room {
             id: "1".to_string(),
             name: "Room 1".to_string(),
+            ..Default::default()
         };
         let room2 = Room {
             id: "2".to_string(),
             name: "Room 2".to_string(),
+            ..Default::default()
         };
 
         let room1_index = floor_plan.add_room(room1);
@@ -213,6 +706,7 @@ mod tests {
             id: "1".to_string(),
             name: "Door 1".to_string(),
             is_exit: false,
+            ..Default::default()
         };
         floor_plan.add_door(room1_index, room2_index, door);
 
@@ -233,14 +727,17 @@ mod tests {
         let room1 = Room {
             id: "1".to_string(),
             name: "Room 1".to_string(),
+            ..Default::default()
         };
         let room2 = Room {
             id: "2".to_string(),
             name: "Room 2".to_string(),
+            ..Default::default()
         };
         let room3 = Room {
             id: "3".to_string(),
             name: "Room 3".to_string(),
+            ..Default::default()
         };
 
         let room1_index = floor_plan.add_room(room1);
@@ -251,11 +748,13 @@ mod tests {
             id: "1".to_string(),
             name: "Door 1".to_string(),
             is_exit: false,
+            ..Default::default()
         };
         let door2 = Door {
             id: "2".to_string(),
             name: "Door 2".to_string(),
             is_exit: false,
+            ..Default::default()
         };
 
         floor_plan.add_door(room1_index, room2_index, door1);
@@ -282,14 +781,17 @@ mod tests {
         let room1 = Room {
             id: "1".to_string(),
             name: "Room 1".to_string(),
+            ..Default::default()
         };
         let room2 = Room {
             id: "2".to_string(),
             name: "Room 2".to_string(),
+            ..Default::default()
         };
         let room3 = Room {
             id: "3".to_string(),
             name: "Room 3".to_string(),
+            ..Default::default()
         };
 
         floor_plan.add_room(room1.clone());
@@ -300,11 +802,13 @@ mod tests {
             id: "1".to_string(),
             name: "Door 1".to_string(),
             is_exit: false,
+            ..Default::default()
         };
         let door2 = Door {
             id: "2".to_string(),
             name: "Door 2".to_string(),
             is_exit: false,
+            ..Default::default()
         };
 
         floor_plan.add_door(
@@ -335,10 +839,12 @@ mod tests {
         let room1 = Room {
             id: "1".to_string(),
             name: "Room 1".to_string(),
+            ..Default::default()
         };
         let room2 = Room {
             id: "2".to_string(),
             name: "Room 2".to_string(),
+            ..Default::default()
         };
 
         floor_plan.add_room(room1.clone());
@@ -357,10 +863,12 @@ mod tests {
         let room1 = Room {
             id: "1".to_string(),
             name: "Room 1".to_string(),
+            ..Default::default()
         };
         let room2 = Room {
             id: "2".to_string(),
             name: "Room 2".to_string(),
+            ..Default::default()
         };
         let room2a = room2.clone();
         assert!(room2.eq(&room2a));
@@ -379,4 +887,342 @@ mod tests {
         assert!(plan1.eq(&plan2));
         assert!(Some(plan1).eq(&Some(plan2)));
     }
+
+    #[test]
+    fn test_shortest_path() {
+        let mut plan = FloorPlan::new();
+        let lobby = plan.add_room(create_room("cluster", "Cluster Lobby"));
+        let ns = plan.add_room(create_room("default", "default NS Hallway"));
+        let pods = plan.add_room(create_room("default-Pods", "default Pods Hallway"));
+        let pod = plan.add_room(create_room("default-Pod-a", "Pod a"));
+        let orphan = plan.add_room(create_room("orphan", "Orphan"));
+        plan.add_door(lobby, ns, create_door("0", "Door to default"));
+        plan.add_door(ns, lobby, create_door("1", "Door to Cluster Lobby"));
+        plan.add_door(ns, pods, create_door("2", "Door to Pods"));
+        plan.add_door(pods, ns, create_door("3", "Door to default"));
+        plan.add_door(pods, pod, create_door("4", "Door to Pod a"));
+        // a longer way around that BFS must not prefer
+        plan.add_door(lobby, orphan, create_door("5", "Door to Orphan"));
+        plan.add_door(orphan, lobby, create_door("6", "Door to Cluster Lobby"));
+
+        let path = plan.shortest_path("cluster", "default-Pod-a").unwrap();
+        let ids: Vec<&str> = path.iter().map(|door| door.id.as_str()).collect();
+        assert_eq!(ids, vec!["0", "2", "4"]);
+
+        let path = plan.shortest_path("default-Pods", "orphan").unwrap();
+        let ids: Vec<&str> = path.iter().map(|door| door.id.as_str()).collect();
+        assert_eq!(ids, vec!["3", "1", "5"]);
+
+        assert!(plan.shortest_path("default", "default").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_shortest_path_errors() {
+        let mut plan = FloorPlan::new();
+        let a = plan.add_room(create_room("a", "Room a"));
+        let b = plan.add_room(create_room("b", "Room b"));
+        plan.add_door(a, b, create_door("0", "Door to b"));
+
+        // doors are one way
+        assert_eq!(
+            plan.shortest_path("b", "a"),
+            Err(FloorPlanError::PathNotFound("b -> a".to_string()))
+        );
+        assert_eq!(
+            plan.shortest_path("a", "nowhere"),
+            Err(FloorPlanError::RoomNotFound("nowhere".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_resource_status_health() {
+        let crash_looping = ResourceStatus {
+            phase: Some("Running".to_string()),
+            ready: 0,
+            total: 1,
+            restarts: 12,
+            reason: Some("CrashLoopBackOff".to_string()),
+        };
+        assert_eq!(crash_looping.health(), Health::Failing);
+        assert_eq!(
+            crash_looping.to_string(),
+            "Running 0/1 ready, 12 restarts, CrashLoopBackOff"
+        );
+
+        let pending = ResourceStatus {
+            phase: Some("Pending".to_string()),
+            total: 1,
+            ..Default::default()
+        };
+        assert_eq!(pending.health(), Health::Pending);
+
+        let starting = ResourceStatus {
+            phase: Some("Running".to_string()),
+            ready: 1,
+            total: 2,
+            ..Default::default()
+        };
+        assert_eq!(starting.health(), Health::Pending);
+
+        let completed_job_pod = ResourceStatus {
+            phase: Some("Succeeded".to_string()),
+            total: 1,
+            ..Default::default()
+        };
+        assert_eq!(completed_job_pod.health(), Health::Healthy);
+
+        let unavailable_deployment = ResourceStatus {
+            total: 3,
+            ..Default::default()
+        };
+        assert_eq!(unavailable_deployment.health(), Health::Failing);
+
+        let degraded_deployment = ResourceStatus {
+            ready: 2,
+            total: 3,
+            ..Default::default()
+        };
+        assert_eq!(degraded_deployment.health(), Health::Pending);
+
+        assert_eq!(create_room("a", "Room a").health(), Health::Healthy);
+    }
+
+    fn create_room(id: &str, name: &str) -> Room {
+        Room {
+            id: id.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn create_door(id: &str, name: &str) -> Door {
+        Door {
+            id: id.to_string(),
+            name: name.to_string(),
+            is_exit: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_add_cluster() {
+        let mut cluster = FloorPlan::new();
+        let lobby = cluster.add_room(Room {
+            id: "cluster".to_string(),
+            name: "Cluster Lobby".to_string(),
+            kind: RoomKind::Cluster,
+            ..Default::default()
+        });
+        let shop = cluster.add_room(Room {
+            id: "shop".to_string(),
+            name: "shop NS Hallway".to_string(),
+            ..Default::default()
+        });
+        cluster.add_door(
+            lobby,
+            shop,
+            Door {
+                id: "0".to_string(),
+                ..Default::default()
+            },
+        );
+
+        let mut multiverse = FloorPlan::new();
+        multiverse.add_room(Room {
+            id: "multiverse".to_string(),
+            kind: RoomKind::Multiverse,
+            ..Default::default()
+        });
+        let staging = multiverse.add_cluster(&cluster, "staging").unwrap();
+        let prod = multiverse.add_cluster(&cluster, "prod").unwrap();
+        assert_eq!(staging, "staging/cluster");
+        assert_eq!(prod, "prod/cluster");
+        assert_eq!(multiverse.get_world_size(), (5, 2));
+        assert_eq!(multiverse.get_start_room_id(), Some("multiverse"));
+
+        let shop = multiverse.get_room_by_id("prod/shop").unwrap();
+        assert_eq!(shop.context.as_deref(), Some("prod"));
+        let path = multiverse
+            .shortest_path("staging/cluster", "staging/shop")
+            .unwrap();
+        assert_eq!(path[0].id, "staging/0");
+        assert!(
+            multiverse
+                .shortest_path("staging/cluster", "prod/shop")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_diff_identical_plans() {
+        let mut plan = FloorPlan::new();
+        let lobby = plan.add_room(create_room("cluster", "Cluster Lobby"));
+        let pod = plan.add_room(create_room("default-Pod-a", "Pod a"));
+        plan.add_door(lobby, pod, create_door("0", "Door to Pod a"));
+
+        let diff = plan.diff(&plan.clone());
+        assert!(diff.is_empty());
+        assert_eq!(diff.summary(), "rooms +0 -0 ~0 *0 doors +0 -0 ~0");
+    }
+
+    #[test]
+    fn test_diff_replaced_pod() {
+        let mut plan1 = FloorPlan::new();
+        let lobby = plan1.add_room(create_room("cluster", "Cluster Lobby"));
+        let pod = plan1.add_room(create_room("default-Pod-a", "Pod a"));
+        plan1.add_door(lobby, pod, create_door("0", "Door to Pod a"));
+
+        // same room and door counts - the old PartialEq can't tell these apart
+        let mut plan2 = FloorPlan::new();
+        let lobby = plan2.add_room(create_room("cluster", "Cluster Lobby"));
+        let pod = plan2.add_room(create_room("default-Pod-b", "Pod b"));
+        plan2.add_door(lobby, pod, create_door("0", "Door to Pod b"));
+        assert!(plan1 == plan2);
+
+        let diff = plan1.diff(&plan2);
+        assert!(!diff.is_empty());
+        assert_eq!(
+            diff.added_rooms,
+            vec![create_room("default-Pod-b", "Pod b")]
+        );
+        assert_eq!(
+            diff.removed_rooms,
+            vec![create_room("default-Pod-a", "Pod a")]
+        );
+        assert_eq!(diff.added_doors.len(), 1);
+        assert_eq!(diff.added_doors[0].to, "default-Pod-b");
+        assert_eq!(diff.removed_doors.len(), 1);
+        assert_eq!(diff.removed_doors[0].to, "default-Pod-a");
+        assert!(diff.renamed_rooms.is_empty());
+        assert!(diff.renamed_doors.is_empty());
+    }
+
+    #[test]
+    fn test_diff_parallel_doors() {
+        let mut plan1 = FloorPlan::new();
+        let service = plan1.add_room(create_room("default-Service-web", "Service web"));
+        let pod = plan1.add_room(create_room("default-Pod-web", "Pod web"));
+        plan1.add_door(service, pod, create_door("0", "port http"));
+        plan1.add_door(service, pod, create_door("1", "port metrics"));
+
+        let mut plan2 = FloorPlan::new();
+        let service = plan2.add_room(create_room("default-Service-web", "Service web"));
+        let pod = plan2.add_room(create_room("default-Pod-web", "Pod web"));
+        plan2.add_door(service, pod, create_door("0", "port http"));
+
+        let diff = plan1.diff(&plan2);
+        assert_eq!(diff.removed_doors.len(), 1);
+        assert_eq!(diff.removed_doors[0].door.name, "port metrics");
+        assert!(diff.added_doors.is_empty());
+        assert!(diff.renamed_doors.is_empty());
+
+        let diff = plan2.diff(&plan1);
+        assert_eq!(diff.added_doors.len(), 1);
+        assert_eq!(diff.added_doors[0].door.name, "port metrics");
+        assert!(plan1.diff(&plan1.clone()).is_empty());
+
+        // the same pair of rooms joined by a passage and by an owner door
+        let mut plan3 = plan2.clone();
+        let service = plan3.get_room_idx_by_id("default-Service-web").unwrap();
+        let pod = plan3.get_room_idx_by_id("default-Pod-web").unwrap();
+        plan3.add_door(
+            service,
+            pod,
+            Door {
+                kind: DoorKind::ToOwned { controller: false },
+                ..create_door("2", "port http")
+            },
+        );
+        let diff = plan2.diff(&plan3);
+        assert_eq!(diff.added_doors.len(), 1);
+        assert_eq!(
+            diff.added_doors[0].door.kind,
+            DoorKind::ToOwned { controller: false }
+        );
+        assert!(diff.renamed_doors.is_empty());
+    }
+
+    #[test]
+    fn test_diff_renamed_room_and_door() {
+        let mut plan1 = FloorPlan::new();
+        let lobby = plan1.add_room(create_room("cluster", "Cluster Lobby"));
+        let hallway = plan1.add_room(create_room("default", "default NS Hallway"));
+        plan1.add_door(lobby, hallway, create_door("0", "Door to default"));
+
+        let mut plan2 = FloorPlan::new();
+        let lobby = plan2.add_room(create_room("cluster", "Cluster Lobby"));
+        let hallway = plan2.add_room(create_room("default", "default Namespace"));
+        plan2.add_door(
+            lobby,
+            hallway,
+            create_door("7", "Door to default Namespace"),
+        );
+
+        let diff = plan1.diff(&plan2);
+        assert_eq!(diff.renamed_rooms.len(), 1);
+        assert_eq!(diff.renamed_rooms[0].1.name, "default Namespace");
+        assert_eq!(diff.renamed_doors.len(), 1);
+        assert_eq!(
+            diff.renamed_doors[0].1.door.name,
+            "Door to default Namespace"
+        );
+        assert!(diff.added_rooms.is_empty());
+        assert!(diff.removed_doors.is_empty());
+        assert_eq!(diff.summary(), "rooms +0 -0 ~1 *0 doors +0 -0 ~1");
+    }
+
+    #[test]
+    fn test_diff_updated_room_status() {
+        let mut plan1 = FloorPlan::new();
+        plan1.add_room(create_room("default-Pod-web", "Pod web"));
+
+        let mut plan2 = FloorPlan::new();
+        plan2.add_room(Room {
+            status: Some(ResourceStatus {
+                phase: Some("Running".to_string()),
+                total: 1,
+                restarts: 3,
+                reason: Some("CrashLoopBackOff".to_string()),
+                ..Default::default()
+            }),
+            ..create_room("default-Pod-web", "Pod web")
+        });
+
+        let diff = plan1.diff(&plan2);
+        assert!(!diff.is_empty());
+        assert!(diff.renamed_rooms.is_empty());
+        assert_eq!(diff.updated_rooms.len(), 1);
+        assert_eq!(diff.updated_rooms[0].1.health(), Health::Failing);
+        assert_eq!(diff.summary(), "rooms +0 -0 ~0 *1 doors +0 -0 ~0");
+    }
+
+    #[test]
+    fn test_diff_ignores_source() {
+        let source = |resource_version: &str| {
+            Some(format!(
+                "kind: Pod\nmetadata:\n  name: web\n  resourceVersion: '{resource_version}'\n"
+            ))
+        };
+        let mut plan1 = FloorPlan::new();
+        plan1.add_room(Room {
+            source: source("100"),
+            ..create_room("default-Pod-web", "Pod web")
+        });
+        let mut plan2 = FloorPlan::new();
+        plan2.add_room(Room {
+            source: source("101"),
+            ..create_room("default-Pod-web", "Pod web")
+        });
+        assert!(plan1.diff(&plan2).is_empty());
+
+        let mut plan3 = FloorPlan::new();
+        plan3.add_room(Room {
+            source: source("102"),
+            locked: Some("locked".to_string()),
+            ..create_room("default-Pod-web", "Pod web")
+        });
+        let diff = plan1.diff(&plan3);
+        assert_eq!(diff.updated_rooms.len(), 1);
+        assert!(diff.updated_rooms[0].1.source.is_none());
+    }
 }
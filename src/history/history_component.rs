@@ -0,0 +1,403 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use k8s_openapi::jiff::Timestamp;
+
+use crate::floorplan::{FloorPlan, FloorPlanError, FloorPlanResult, Room};
+use crate::integration::snapshot::snapshot_format::{load, save};
+
+/// how many appeared and vanished rooms the timeline lists before it just counts the rest
+pub const TIMELINE_ROOMS_LISTED: usize = 8;
+
+pub struct HistoryPlugin;
+
+/// one recorded floorplan - its file is named after the millisecond it was generated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub time: Timestamp,
+    pub path: PathBuf,
+}
+
+impl HistoryEntry {
+    fn from_path(path: PathBuf) -> Option<Self> {
+        if path.extension()? != "bin" {
+            return None;
+        }
+        let millis = path.file_stem()?.to_str()?.parse().ok()?;
+        Some(Self {
+            time: Timestamp::from_millisecond(millis).ok()?,
+            path,
+        })
+    }
+}
+
+/**
+* every distinct floorplan any generator produced, oldest first, each a binary snapshot in `dir`.
+* once there are more than `limit` the oldest files are deleted. without a directory nothing is
+* written, but the newest floorplan is still kept to return to after rewinding
+*/
+#[derive(Resource, Debug, Default)]
+pub struct FloorPlanHistory {
+    pub dir: Option<PathBuf>,
+    pub limit: usize,
+    pub entries: Vec<HistoryEntry>,
+    pub latest: Option<FloorPlan>,
+}
+
+impl FloorPlanHistory {
+    /// pick up what an earlier run recorded in `dir`, creating it if need be
+    pub fn open(dir: &Path, limit: usize) -> FloorPlanResult<Self> {
+        std::fs::create_dir_all(dir).map_err(|e| FloorPlanError::SnapshotError(e.to_string()))?;
+        let mut entries = std::fs::read_dir(dir)
+            .map_err(|e| FloorPlanError::SnapshotError(e.to_string()))?
+            .filter_map(|entry| HistoryEntry::from_path(entry.ok()?.path()))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.time);
+        let mut history = Self {
+            dir: Some(dir.to_path_buf()),
+            limit,
+            entries,
+            latest: None,
+        };
+        history.prune();
+        history.latest = history
+            .entries
+            .last()
+            .and_then(|entry| load(&entry.path).ok());
+        Ok(history)
+    }
+
+    /**
+     * keep a floorplan unless it is the same as the newest one in rooms, doors and health - a
+     * fresh `source` alone, ie: a status timestamp, is not worth a snapshot. true when it was
+     * written. a scan within the same millisecond as the newest one replaces it
+     */
+    pub fn record(&mut self, time: Timestamp, floorplan: &FloorPlan) -> FloorPlanResult<bool> {
+        if self
+            .latest
+            .as_ref()
+            .is_some_and(|latest| latest.diff(floorplan).is_empty())
+        {
+            return Ok(false);
+        }
+        self.latest = Some(floorplan.clone());
+        let Some(dir) = &self.dir else {
+            return Ok(false);
+        };
+        if self.limit == 0 {
+            return Ok(false);
+        }
+        let entry = HistoryEntry {
+            time,
+            path: dir.join(format!("{}.bin", time.as_millisecond())),
+        };
+        save(floorplan, &entry.path)?;
+        self.entries.retain(|kept| kept.time < entry.time);
+        self.entries.push(entry);
+        self.prune();
+        Ok(true)
+    }
+
+    fn prune(&mut self) {
+        let excess = self.entries.len().saturating_sub(self.limit);
+        for entry in self.entries.drain(..excess) {
+            if let Err(e) = std::fs::remove_file(&entry.path) {
+                warn!("Unable to prune {}: {e}", entry.path.display());
+            }
+        }
+    }
+
+    pub fn load(&self, index: usize) -> FloorPlanResult<FloorPlan> {
+        let entry = self
+            .entries
+            .get(index)
+            .ok_or_else(|| FloorPlanError::SnapshotError(format!("no history entry {index}")))?;
+        load(&entry.path)
+    }
+
+    /// the entry recorded at `time`, or the last one before it
+    pub fn position(&self, time: Timestamp) -> Option<usize> {
+        self.entries.iter().rposition(|entry| entry.time <= time)
+    }
+}
+
+fn clock(time: Timestamp) -> String {
+    time.strftime("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+fn describe(room: &Room) -> String {
+    match &room.namespace {
+        Some(namespace) => format!("{} ({namespace})", room.name),
+        None => room.name.clone(),
+    }
+}
+
+/**
+* scrubbing through the history - the entry under the cursor, compared with the world as it is
+* shown, and which recorded time the world was rewound to. `None` means it follows the cluster
+*/
+#[derive(Resource, Debug, Default)]
+pub struct Timeline {
+    pub selected: usize,
+    pub rewound_to: Option<Timestamp>,
+    /// rooms the selected entry has that the shown world does not
+    pub appeared: Vec<Room>,
+    pub vanished: Vec<Room>,
+    /// ids of the rooms the last jump added, they glow on the platform it builds
+    pub glowing: Vec<String>,
+    /// why the selected entry could not be compared, ie: a snapshot from an older version
+    pub status: Option<String>,
+}
+
+impl Timeline {
+    pub fn open(&mut self, history: &FloorPlanHistory) {
+        self.selected = self
+            .rewound_to
+            .and_then(|time| history.position(time))
+            .unwrap_or_else(|| history.entries.len().saturating_sub(1));
+    }
+
+    pub fn scrub_by(&mut self, offset: isize, history: &FloorPlanHistory) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(offset)
+            .min(history.entries.len().saturating_sub(1));
+    }
+
+    /// what showing `selected` instead of `shown` would add and take away
+    pub fn compare(&mut self, shown: Option<&FloorPlan>, selected: FloorPlanResult<FloorPlan>) {
+        match selected {
+            Ok(selected) => {
+                let diff = shown.map(|shown| shown.diff(&selected)).unwrap_or_default();
+                self.appeared = diff.added_rooms;
+                self.vanished = diff.removed_rooms;
+                self.status = None;
+            }
+            Err(e) => {
+                self.appeared.clear();
+                self.vanished.clear();
+                self.status = Some(e.to_string());
+            }
+        }
+    }
+
+    pub fn text(&self, history: &FloorPlanHistory) -> String {
+        let Some(entry) = history.entries.get(self.selected) else {
+            let why = if history.dir.is_some() {
+                "nothing recorded yet"
+            } else {
+                "nothing is recorded, run with --history-dir to keep every distinct floorplan"
+            };
+            return format!("timeline - {why}\nesc closes");
+        };
+        let showing = self.rewound_to.map_or_else(|| "now".to_string(), clock);
+        let mut lines = vec![format!(
+            "timeline - {} ({} of {}), showing {showing}",
+            clock(entry.time),
+            self.selected + 1,
+            history.entries.len()
+        )];
+        if let Some(status) = &self.status {
+            lines.push(status.clone());
+        } else if self.appeared.is_empty() && self.vanished.is_empty() {
+            lines.push("the same rooms as shown".to_string());
+        } else {
+            lines.push(format!(
+                "compared with what is shown: {} appeared, {} vanished",
+                self.appeared.len(),
+                self.vanished.len()
+            ));
+            for (marker, rooms) in [("+", &self.appeared), ("-", &self.vanished)] {
+                lines.extend(
+                    rooms
+                        .iter()
+                        .take(TIMELINE_ROOMS_LISTED)
+                        .map(|room| format!("{marker} {}", describe(room))),
+                );
+                if rooms.len() > TIMELINE_ROOMS_LISTED {
+                    lines.push(format!(
+                        "{marker} ...and {} more",
+                        rooms.len() - TIMELINE_ROOMS_LISTED
+                    ));
+                }
+            }
+        }
+        lines.push(
+            "left/right scrub (shift x10), home/end, enter rewinds here, n back to now, esc closes"
+                .to_string(),
+        );
+        lines.join("\n")
+    }
+
+    /// the world is about to show the selected entry
+    pub fn jumped(&mut self, rewound_to: Option<Timestamp>) {
+        self.rewound_to = rewound_to;
+        self.glowing = self.appeared.iter().map(|room| room.id.clone()).collect();
+    }
+
+    /// the line left on screen while the world shows the past
+    pub fn banner(&self) -> Option<String> {
+        self.rewound_to
+            .map(|time| format!("rewound to {} - h for the timeline", clock(time)))
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct TimelineText;
+
+#[derive(Debug, Component)]
+pub struct RewoundBanner;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::floorplan::{Door, RoomKind};
+
+    fn room(id: &str) -> Room {
+        Room {
+            id: id.to_string(),
+            name: format!("Pod {id}"),
+            kind: RoomKind::Pod,
+            namespace: Some("shop".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn floorplan(pods: &[&str]) -> FloorPlan {
+        let mut plan = FloorPlan::new();
+        let lobby = plan.add_room(Room {
+            id: "cluster".to_string(),
+            name: "Cluster Lobby".to_string(),
+            kind: RoomKind::Cluster,
+            ..Default::default()
+        });
+        for pod in pods {
+            let pod = plan.add_room(room(pod));
+            plan.add_door(lobby, pod, Door::default());
+        }
+        plan
+    }
+
+    fn at(millis: i64) -> Timestamp {
+        Timestamp::from_millisecond(millis).unwrap()
+    }
+
+    fn history_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_record_distinct_floorplans() {
+        let dir = history_dir("doors-history-test-record");
+        let mut history = FloorPlanHistory::open(&dir, 2).unwrap();
+        assert!(history.record(at(1_000), &floorplan(&["a"])).unwrap());
+        assert!(!history.record(at(2_000), &floorplan(&["a"])).unwrap());
+        assert!(history.record(at(3_000), &floorplan(&["a", "b"])).unwrap());
+        assert!(history.record(at(4_000), &floorplan(&["b"])).unwrap());
+
+        // the oldest was pruned, from memory and from disk
+        let times: Vec<_> = history.entries.iter().map(|entry| entry.time).collect();
+        assert_eq!(times, vec![at(3_000), at(4_000)]);
+        assert!(!dir.join("1000.bin").exists());
+        assert_eq!(history.position(at(3_500)), Some(0));
+        assert_eq!(history.position(at(500)), None);
+
+        let reopened = FloorPlanHistory::open(&dir, 2).unwrap();
+        assert_eq!(reopened.entries, history.entries);
+        assert_eq!(reopened.latest, Some(floorplan(&["b"])));
+        assert!(reopened.load(0).unwrap().get_room_by_id("a").is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_record_skips_source_only_changes() {
+        let with_heartbeat = |heartbeat: &str| {
+            let mut plan = floorplan(&[]);
+            let lobby = plan.get_room_idx_by_id("cluster").unwrap();
+            let pod = plan.add_room(Room {
+                source: Some(format!(
+                    "kind: Pod\nstatus:\n  conditions:\n  - lastProbeTime: '{heartbeat}'\n"
+                )),
+                ..room("a")
+            });
+            plan.add_door(lobby, pod, Door::default());
+            plan
+        };
+        let dir = history_dir("doors-history-test-source");
+        let mut history = FloorPlanHistory::open(&dir, 10).unwrap();
+        assert!(
+            history
+                .record(at(1_000), &with_heartbeat("2026-10-18T10:00:00Z"))
+                .unwrap()
+        );
+        assert!(
+            !history
+                .record(at(2_000), &with_heartbeat("2026-10-18T10:00:30Z"))
+                .unwrap()
+        );
+        assert_eq!(history.entries.len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_record_without_dir() {
+        let mut history = FloorPlanHistory::default();
+        assert!(!history.record(at(1_000), &floorplan(&["a"])).unwrap());
+        assert!(history.entries.is_empty());
+        assert_eq!(history.latest, Some(floorplan(&["a"])));
+    }
+
+    #[test]
+    fn test_timeline_compare() {
+        let history = FloorPlanHistory {
+            entries: vec![
+                HistoryEntry {
+                    time: at(1_000),
+                    path: PathBuf::from("1000.bin"),
+                },
+                HistoryEntry {
+                    time: at(2_000),
+                    path: PathBuf::from("2000.bin"),
+                },
+            ],
+            ..Default::default()
+        };
+        let mut timeline = Timeline::default();
+        timeline.open(&history);
+        assert_eq!(timeline.selected, 1);
+        timeline.scrub_by(-10, &history);
+        assert_eq!(timeline.selected, 0);
+
+        let shown = floorplan(&["web-1", "web-2"]);
+        timeline.compare(Some(&shown), Ok(floorplan(&["web-2", "web-3"])));
+        assert_eq!(timeline.appeared, vec![room("web-3")]);
+        assert_eq!(timeline.vanished, vec![room("web-1")]);
+        let text = timeline.text(&history);
+        assert!(text.starts_with("timeline - 1970-01-01 00:00:01 UTC (1 of 2), showing now"));
+        assert!(text.contains("+ Pod web-3 (shop)\n- Pod web-1 (shop)"));
+
+        timeline.compare(
+            Some(&shown),
+            Err(FloorPlanError::SnapshotError("unsupported".to_string())),
+        );
+        assert!(timeline.appeared.is_empty());
+        assert!(
+            timeline
+                .text(&history)
+                .contains("snapshot error: unsupported")
+        );
+
+        assert_eq!(timeline.banner(), None);
+        timeline.compare(Some(&shown), Ok(floorplan(&["web-3"])));
+        timeline.jumped(Some(at(2_000)));
+        assert_eq!(timeline.glowing, vec!["web-3"]);
+        timeline.open(&history);
+        assert_eq!(timeline.selected, 1);
+        assert_eq!(
+            timeline.banner().as_deref(),
+            Some("rewound to 1970-01-01 00:00:02 UTC - h for the timeline")
+        );
+    }
+}
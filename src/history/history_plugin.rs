@@ -0,0 +1,31 @@
+use bevy::prelude::*;
+
+use super::{
+    history_component::{FloorPlanHistory, HistoryPlugin, Timeline},
+    history_systems::{
+        despawn_timeline, highlight_appeared_rooms, open_timeline, record_floorplan_history,
+        setup_history, timeline_input, update_rewound_banner, update_timeline,
+    },
+};
+use crate::state::GameState;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FloorPlanHistory::default())
+            .insert_resource(Timeline::default())
+            .add_systems(Startup, setup_history)
+            .add_systems(Update, (record_floorplan_history, update_rewound_banner))
+            .add_systems(Update, open_timeline.run_if(in_state(GameState::InGame)))
+            .add_systems(
+                Update,
+                (timeline_input, update_timeline)
+                    .chain()
+                    .run_if(in_state(GameState::TimeTravelling)),
+            )
+            .add_systems(OnExit(GameState::TimeTravelling), despawn_timeline)
+            .add_systems(
+                OnEnter(GameState::TransitioningComplete),
+                highlight_appeared_rooms,
+            );
+    }
+}
@@ -0,0 +1,244 @@
+use bevy::{color::palettes::tailwind::GREEN_400, prelude::*};
+use clap::Parser;
+use k8s_openapi::jiff::Timestamp;
+use leafwing_input_manager::prelude::ActionState;
+
+use super::history_component::{FloorPlanHistory, RewoundBanner, Timeline, TimelineText};
+use crate::{
+    cli::Cli,
+    floorplan::{FloorPlan, FloorPlanEvent, Room},
+    player::{Player, player_component::Action},
+    state::GameState,
+    world::world_component::{CurrentFloorPlan, NextFloorPlan},
+};
+
+pub fn setup_history(mut history: ResMut<FloorPlanHistory>) {
+    let cli = Cli::parse();
+    let limit = cli.history_limit;
+    if let Some(dir) = cli.history_dir
+        && limit > 0
+    {
+        match FloorPlanHistory::open(&dir, limit) {
+            Ok(opened) => *history = opened,
+            Err(e) => error!("Unable to open the history {}: {e}", dir.display()),
+        }
+    }
+}
+
+/// every floorplan any generator sends is recorded, whatever the world is busy with
+pub fn record_floorplan_history(
+    mut events: EventReader<FloorPlanEvent>,
+    mut history: ResMut<FloorPlanHistory>,
+) {
+    for event in events.read() {
+        match history.record(Timestamp::now(), &event.floorplan) {
+            Ok(true) => debug!("recorded floorplan {}", history.entries.len()),
+            Ok(false) => {}
+            Err(e) => warn!("Failed to record floorplan history: {e}"),
+        }
+    }
+}
+
+pub fn open_timeline(
+    player_query: Query<&ActionState<Action>, With<Player>>,
+    history: Res<FloorPlanHistory>,
+    current_floorplan: Res<CurrentFloorPlan>,
+    mut timeline: ResMut<Timeline>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let Ok(action_state) = player_query.get_single()
+        && action_state.just_pressed(&Action::Timeline)
+    {
+        timeline.open(&history);
+        let selected = history.load(timeline.selected);
+        timeline.compare(current_floorplan.floorplan.as_ref(), selected);
+        next_state.set(GameState::TimeTravelling);
+    }
+}
+
+/**
+* put a floorplan on deck and rebuild the world around it - the player stays in the same room when
+* it exists there too, and otherwise starts over in the lobby
+*/
+fn show(
+    floorplan: FloorPlan,
+    current_floorplan: &mut CurrentFloorPlan,
+    next_floorplan: &mut NextFloorPlan,
+    time: &Time,
+) {
+    let in_shown = |room: &Option<Room>| {
+        room.as_ref()
+            .and_then(|room| floorplan.get_room_by_id(&room.id).ok().cloned())
+    };
+    current_floorplan.you_are_here = in_shown(&current_floorplan.you_are_here)
+        .or_else(|| floorplan.get_start_room().ok().cloned());
+    current_floorplan.previous_room = in_shown(&current_floorplan.previous_room);
+    next_floorplan.diff = current_floorplan
+        .floorplan
+        .as_ref()
+        .map(|shown| shown.diff(&floorplan));
+    next_floorplan.floorplan = Some(floorplan);
+    next_floorplan.created = Some(time.elapsed());
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn timeline_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    history: Res<FloorPlanHistory>,
+    time: Res<Time>,
+    mut timeline: ResMut<Timeline>,
+    mut current_floorplan: ResMut<CurrentFloorPlan>,
+    mut next_floorplan: ResMut<NextFloorPlan>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keys.any_just_pressed([KeyCode::Escape, KeyCode::KeyH]) {
+        next_state.set(GameState::InGame);
+        return;
+    }
+
+    let step = if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        10
+    } else {
+        1
+    };
+    let before = timeline.selected;
+    for (key, offset) in [(KeyCode::ArrowRight, step), (KeyCode::ArrowLeft, -step)] {
+        if keys.just_pressed(key) {
+            timeline.scrub_by(offset, &history);
+        }
+    }
+    if keys.just_pressed(KeyCode::Home) {
+        timeline.selected = 0;
+    }
+    if keys.just_pressed(KeyCode::End) {
+        timeline.scrub_by(isize::MAX, &history);
+    }
+    if timeline.selected != before {
+        let selected = history.load(timeline.selected);
+        timeline.compare(current_floorplan.floorplan.as_ref(), selected);
+    }
+
+    if keys.just_pressed(KeyCode::Enter)
+        && let Some(entry) = history.entries.get(timeline.selected)
+    {
+        match history.load(timeline.selected) {
+            Ok(floorplan) => {
+                info!("Rewinding to {}", entry.time);
+                timeline.jumped(Some(entry.time));
+                current_floorplan.rewound = true;
+                show(
+                    floorplan,
+                    &mut current_floorplan,
+                    &mut next_floorplan,
+                    &time,
+                );
+                next_state.set(GameState::TransitioningOutSetup);
+            }
+            Err(e) => timeline.status = Some(e.to_string()),
+        }
+    } else if keys.just_pressed(KeyCode::KeyN)
+        && timeline.rewound_to.is_some()
+        && let Some(latest) = history.latest.clone()
+    {
+        info!("Back to now");
+        timeline.compare(current_floorplan.floorplan.as_ref(), Ok(latest.clone()));
+        timeline.jumped(None);
+        current_floorplan.rewound = false;
+        show(latest, &mut current_floorplan, &mut next_floorplan, &time);
+        next_state.set(GameState::TransitioningOutSetup);
+    }
+}
+
+/// rooms the jump added glow until the player walks on to the next platform
+pub fn highlight_appeared_rooms(
+    mut timeline: ResMut<Timeline>,
+    room_query: Query<(&Room, &MeshMaterial3d<StandardMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if timeline.glowing.is_empty() {
+        return;
+    }
+    for (room, material) in &room_query {
+        if timeline.glowing.contains(&room.id)
+            && let Some(material) = materials.get_mut(&material.0)
+        {
+            material.emissive = LinearRgba::from(GREEN_400) * 2.0;
+        }
+    }
+    timeline.glowing.clear();
+}
+
+pub fn update_timeline(
+    mut commands: Commands,
+    timeline: Res<Timeline>,
+    history: Res<FloorPlanHistory>,
+    mut text_query: Query<&mut Text, With<TimelineText>>,
+) {
+    if !timeline.is_changed() && !history.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = text_query.get_single_mut() {
+        text.0 = timeline.text(&history);
+    } else {
+        commands.spawn((
+            Text::new(timeline.text(&history)),
+            TextFont {
+                font_size: 15.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            BackgroundColor(Color::BLACK.with_alpha(0.85)),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Percent(20.0),
+                width: Val::Percent(60.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            TimelineText,
+        ));
+    }
+}
+
+pub fn despawn_timeline(mut commands: Commands, query: Query<Entity, With<TimelineText>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// while the world shows the past a banner says so
+pub fn update_rewound_banner(
+    mut commands: Commands,
+    timeline: Res<Timeline>,
+    mut text_query: Query<(Entity, &mut Text), With<RewoundBanner>>,
+) {
+    if !timeline.is_changed() {
+        return;
+    }
+
+    match (timeline.banner(), text_query.get_single_mut()) {
+        (Some(banner), Ok((_, mut text))) => text.0 = banner,
+        (Some(banner), Err(_)) => {
+            commands.spawn((
+                Text::new(banner),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::BLACK),
+                BackgroundColor(Color::from(GREEN_400)),
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(5.0),
+                    right: Val::Px(5.0),
+                    padding: UiRect::axes(Val::Px(6.0), Val::Px(3.0)),
+                    ..default()
+                },
+                RewoundBanner,
+            ));
+        }
+        (None, Ok((entity, _))) => commands.entity(entity).despawn_recursive(),
+        (None, Err(_)) => {}
+    }
+}
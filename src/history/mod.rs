@@ -0,0 +1,5 @@
+pub mod history_component;
+pub mod history_plugin;
+pub mod history_systems;
+
+pub use history_component::HistoryPlugin;
@@ -0,0 +1,272 @@
+use bevy::{prelude::*, tasks::Task};
+use k8s_openapi::jiff::Timestamp;
+
+use crate::floorplan::Room;
+
+/// how many yaml lines fit below the summary
+pub const INSPECTOR_LINES: usize = 30;
+/// label and annotation values longer than this are cut short in the summary
+const MAX_SUMMARY_VALUE: usize = 60;
+
+pub struct InspectorPlugin;
+
+/**
+* what the inspector shows - the room whose info was on screen when it was opened, or else the
+* room the player is in
+*/
+#[derive(Resource, Debug, Default)]
+pub struct Inspector {
+    /// the last room a door or target described, while its info text is up
+    pub candidate: Option<Room>,
+    pub room: Option<Room>,
+    /// the first yaml line on screen
+    pub scroll: usize,
+    /// the outcome of the last copy
+    pub message: Option<String>,
+    /// a copy still waiting on the clipboard tool, with the tool's name once it took the text
+    pub copying: Option<Task<Result<&'static str, String>>>,
+}
+
+impl Inspector {
+    pub fn yaml_lines(&self) -> Vec<&str> {
+        self.room
+            .as_ref()
+            .and_then(|room| room.source.as_deref())
+            .map(|source| source.lines().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn scroll_by(&mut self, lines: isize) {
+        let max_scroll = self.yaml_lines().len().saturating_sub(INSPECTOR_LINES);
+        self.scroll = self.scroll.saturating_add_signed(lines).min(max_scroll);
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct InspectorPanel;
+
+#[derive(Debug, Component)]
+pub struct InspectorText;
+
+/// what a piece of a yaml line is, for coloring it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YamlToken {
+    Key,
+    String,
+    Number,
+    Literal,
+    Punctuation,
+}
+
+fn value_token(value: &str) -> YamlToken {
+    match value {
+        "true" | "false" | "null" | "~" => YamlToken::Literal,
+        _ if value.parse::<f64>().is_ok() => YamlToken::Number,
+        _ => YamlToken::String,
+    }
+}
+
+/**
+* split one line of yaml as `serde_yaml` writes it into colorable pieces - indent and list dashes,
+* the key, and the value. block scalars and their text come out as strings
+*/
+pub fn highlight_yaml(line: &str) -> Vec<(String, YamlToken)> {
+    let body = line.trim_start_matches([' ', '-']);
+    let prefix = &line[..line.len() - body.len()];
+    let mut pieces = Vec::new();
+    if !prefix.is_empty() {
+        pieces.push((prefix.to_string(), YamlToken::Punctuation));
+    }
+    let key_end = if body.starts_with(['"', '\'']) {
+        None
+    } else {
+        body.find(": ")
+            .or_else(|| body.ends_with(':').then(|| body.len() - 1))
+    };
+    match key_end {
+        Some(end) => {
+            pieces.push((body[..end].to_string(), YamlToken::Key));
+            pieces.push((":".to_string(), YamlToken::Punctuation));
+            let value = &body[end + 1..];
+            if !value.is_empty() {
+                pieces.push((value.to_string(), value_token(value.trim())));
+            }
+        }
+        None if !body.is_empty() => pieces.push((body.to_string(), value_token(body))),
+        None => {}
+    }
+    pieces
+}
+
+/// ie: 3d4h, 5h12m, 7m or 42s - the two largest units, as kubectl shows ages
+pub fn format_age(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    let (days, hours, minutes) = (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60);
+    match (days, hours, minutes) {
+        (0, 0, 0) => format!("{seconds}s"),
+        (0, 0, m) => format!("{m}m"),
+        (0, h, m) => format!("{h}h{m}m"),
+        (d, h, _) => format!("{d}d{h}h"),
+    }
+}
+
+fn shorten(value: &str) -> String {
+    if value.chars().count() > MAX_SUMMARY_VALUE {
+        let short: String = value.chars().take(MAX_SUMMARY_VALUE).collect();
+        format!("{short}...")
+    } else {
+        value.to_string()
+    }
+}
+
+fn pairs(map: &serde_json::Value) -> Option<String> {
+    let pairs: Vec<String> = map
+        .as_object()?
+        .iter()
+        .map(|(key, value)| format!("{key}={}", shorten(value.as_str().unwrap_or_default())))
+        .collect();
+    (!pairs.is_empty()).then(|| pairs.join(", "))
+}
+
+/**
+* the header above the yaml - kind, name, namespace and age, then labels, annotations and
+* conditions when there are any. rooms without a source object only get their name
+*/
+pub fn summary(room: &Room, now: Timestamp) -> Vec<String> {
+    let object = room
+        .source
+        .as_deref()
+        .and_then(|source| serde_yaml::from_str::<serde_json::Value>(source).ok())
+        .unwrap_or_default();
+    let metadata = &object["metadata"];
+
+    let mut title = room.name.clone();
+    if let Some(namespace) = &room.namespace {
+        title.push_str(&format!(" in {namespace}"));
+    }
+    if let Some(created) = metadata["creationTimestamp"]
+        .as_str()
+        .and_then(|created| created.parse::<Timestamp>().ok())
+    {
+        let age = now.as_second() - created.as_second();
+        title.push_str(&format!(", age {}", format_age(age)));
+    }
+    let mut lines = vec![title];
+
+    if let Some(labels) = pairs(&metadata["labels"]) {
+        lines.push(format!("labels: {labels}"));
+    }
+    if let Some(annotations) = pairs(&metadata["annotations"]) {
+        lines.push(format!("annotations: {annotations}"));
+    }
+    let conditions: Vec<String> = object["status"]["conditions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|condition| {
+            Some(format!(
+                "{}={}",
+                condition["type"].as_str()?,
+                condition["status"].as_str()?
+            ))
+        })
+        .collect();
+    if !conditions.is_empty() {
+        lines.push(format!("conditions: {}", conditions.join(", ")));
+    }
+    if room.source.is_none() {
+        lines.push("nothing to inspect, this room has no object behind it".to_string());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::floorplan::RoomKind;
+
+    #[test]
+    fn test_highlight_yaml() {
+        assert_eq!(
+            highlight_yaml("  replicas: 3"),
+            vec![
+                ("  ".to_string(), YamlToken::Punctuation),
+                ("replicas".to_string(), YamlToken::Key),
+                (":".to_string(), YamlToken::Punctuation),
+                (" 3".to_string(), YamlToken::Number),
+            ]
+        );
+        assert_eq!(
+            highlight_yaml("- name: api"),
+            vec![
+                ("- ".to_string(), YamlToken::Punctuation),
+                ("name".to_string(), YamlToken::Key),
+                (":".to_string(), YamlToken::Punctuation),
+                (" api".to_string(), YamlToken::String),
+            ]
+        );
+        assert_eq!(highlight_yaml("metadata:")[0].1, YamlToken::Key);
+        assert_eq!(
+            highlight_yaml("  - 'http://a: b'"),
+            vec![
+                ("  - ".to_string(), YamlToken::Punctuation),
+                ("'http://a: b'".to_string(), YamlToken::String),
+            ]
+        );
+        assert_eq!(highlight_yaml("ready: true")[2].1, YamlToken::Literal);
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(42), "42s");
+        assert_eq!(format_age(7 * 60 + 3), "7m");
+        assert_eq!(format_age(5 * 3600 + 12 * 60), "5h12m");
+        assert_eq!(format_age(3 * 86400 + 4 * 3600 + 59), "3d4h");
+        assert_eq!(format_age(-5), "0s");
+    }
+
+    #[test]
+    fn test_summary() {
+        let room = Room {
+            name: "Pod web-1".to_string(),
+            kind: RoomKind::Pod,
+            namespace: Some("shop".to_string()),
+            source: Some(
+                "
+kind: Pod
+metadata:
+  name: web-1
+  creationTimestamp: 2024-05-01T03:12:00Z
+  labels:
+    app: web
+  annotations:
+    team: payments
+status:
+  conditions:
+  - type: Ready
+    status: 'False'
+  - type: PodScheduled
+    status: 'True'
+"
+                .to_string(),
+            ),
+            ..Default::default()
+        };
+        let now: Timestamp = "2024-05-01T05:20:00Z".parse().unwrap();
+        assert_eq!(
+            summary(&room, now),
+            vec![
+                "Pod web-1 in shop, age 2h8m",
+                "labels: app=web",
+                "annotations: team=payments",
+                "conditions: Ready=False, PodScheduled=True",
+            ]
+        );
+
+        let hallway = Room {
+            name: "shop Pods Hallway".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(summary(&hallway, now).len(), 2);
+    }
+}
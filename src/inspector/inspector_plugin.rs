@@ -0,0 +1,31 @@
+use bevy::prelude::*;
+
+use super::{
+    inspector_component::{Inspector, InspectorPlugin},
+    inspector_systems::{
+        despawn_inspector, finish_clipboard_copy, inspector_input, open_inspector,
+        record_inspector_candidate, spawn_inspector, update_inspector,
+    },
+};
+use crate::state::GameState;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Inspector::default())
+            .add_systems(
+                Update,
+                (record_inspector_candidate, open_inspector)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(OnEnter(GameState::Inspecting), spawn_inspector)
+            .add_systems(OnExit(GameState::Inspecting), despawn_inspector)
+            .add_systems(
+                Update,
+                (inspector_input, update_inspector)
+                    .chain()
+                    .run_if(in_state(GameState::Inspecting)),
+            )
+            .add_systems(Update, finish_clipboard_copy);
+    }
+}
@@ -0,0 +1,223 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use bevy::{
+    color::palettes::tailwind::{AMBER_400, GRAY_400, GREEN_400, PURPLE_400, SKY_400},
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, block_on},
+};
+use k8s_openapi::jiff::Timestamp;
+use leafwing_input_manager::prelude::ActionState;
+
+use super::inspector_component::{
+    INSPECTOR_LINES, Inspector, InspectorPanel, InspectorText, YamlToken, highlight_yaml, summary,
+};
+use crate::{
+    player::{Player, player_component::Action},
+    state::GameState,
+    world::world_component::{CurrentFloorPlan, DisplayRoomInfoEvent, RoomInfoDisplayTimer},
+};
+
+/// clipboard tools to try in turn - macos, wayland, x11 (two flavors) and windows
+const CLIPBOARD_COMMANDS: [&[&str]; 5] = [
+    &["pbcopy"],
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+    &["clip"],
+];
+
+const INSPECTOR_FONT_SIZE: f32 = 13.0;
+
+const fn token_color(token: YamlToken) -> Srgba {
+    match token {
+        YamlToken::Key => SKY_400,
+        YamlToken::String => GREEN_400,
+        YamlToken::Number => AMBER_400,
+        YamlToken::Literal => PURPLE_400,
+        YamlToken::Punctuation => GRAY_400,
+    }
+}
+
+/// hand the text to the first clipboard tool that is installed, naming the one that took it
+fn copy_to_clipboard(text: &str) -> Result<&'static str, String> {
+    for command in CLIPBOARD_COMMANDS {
+        let Ok(mut child) = Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        let written = child
+            .stdin
+            .take()
+            .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+        if written && child.wait().is_ok_and(|status| status.success()) {
+            return Ok(command[0]);
+        }
+    }
+    Err("no clipboard tool found (pbcopy, wl-copy, xclip, xsel or clip)".to_string())
+}
+
+/// bumping a door or picking a target makes that room the one to inspect while its info is up
+pub fn record_inspector_candidate(
+    mut events: EventReader<DisplayRoomInfoEvent>,
+    mut inspector: ResMut<Inspector>,
+) {
+    if let Some(event) = events.read().last() {
+        inspector.candidate = Some(event.room.clone());
+    }
+}
+
+pub fn open_inspector(
+    player_query: Query<&ActionState<Action>, With<Player>>,
+    current_floorplan: Res<CurrentFloorPlan>,
+    room_info_timer: Option<Res<RoomInfoDisplayTimer>>,
+    mut inspector: ResMut<Inspector>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let Ok(action_state) = player_query.get_single()
+        && action_state.just_pressed(&Action::Inspect)
+    {
+        let room = room_info_timer
+            .and(inspector.candidate.clone())
+            .or_else(|| current_floorplan.you_are_here.clone());
+        if room.is_some() {
+            inspector.room = room;
+            inspector.scroll = 0;
+            inspector.message = None;
+            next_state.set(GameState::Inspecting);
+        }
+    }
+}
+
+pub fn inspector_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut inspector: ResMut<Inspector>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keys.any_just_pressed([KeyCode::Escape, KeyCode::KeyI]) {
+        next_state.set(GameState::InGame);
+        return;
+    }
+    #[allow(clippy::cast_possible_wrap)]
+    let page = INSPECTOR_LINES as isize;
+    for (key, lines) in [
+        (KeyCode::ArrowDown, 1),
+        (KeyCode::ArrowUp, -1),
+        (KeyCode::PageDown, page),
+        (KeyCode::PageUp, -page),
+    ] {
+        if keys.just_pressed(key) {
+            inspector.scroll_by(lines);
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyC)
+        && inspector.copying.is_none()
+        && let Some(source) = inspector.room.as_ref().and_then(|room| room.source.clone())
+    {
+        // off the main thread, a clipboard tool that hangs must not freeze the game
+        inspector.copying =
+            Some(AsyncComputeTaskPool::get().spawn(async move { copy_to_clipboard(&source) }));
+        inspector.message = Some("copying...".to_string());
+    }
+}
+
+/// say how the copy went once the clipboard tool is done with it
+pub fn finish_clipboard_copy(mut inspector: ResMut<Inspector>) {
+    if !inspector.copying.as_ref().is_some_and(Task::is_finished) {
+        return;
+    }
+    if let Some(task) = inspector.copying.take() {
+        inspector.message = Some(match block_on(task) {
+            Ok(tool) => format!("copied with {tool}"),
+            Err(e) => e,
+        });
+    }
+}
+
+pub fn spawn_inspector(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(5.0),
+                left: Val::Percent(15.0),
+                width: Val::Percent(70.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.85)),
+            InspectorPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: INSPECTOR_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                InspectorText,
+            ));
+        });
+}
+
+pub fn despawn_inspector(mut commands: Commands, query: Query<Entity, With<InspectorPanel>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// the summary in white, then the visible yaml lines as colored spans
+pub fn update_inspector(
+    mut commands: Commands,
+    inspector: Res<Inspector>,
+    mut text_query: Query<(Entity, &mut Text, Ref<InspectorText>)>,
+) {
+    let Ok((entity, mut text, marker)) = text_query.get_single_mut() else {
+        return;
+    };
+    if !inspector.is_changed() && !marker.is_added() {
+        return;
+    }
+    let Some(room) = &inspector.room else {
+        return;
+    };
+
+    let yaml = inspector.yaml_lines();
+    let mut header = summary(room, Timestamp::now());
+    let shown = yaml.len().min(inspector.scroll + INSPECTOR_LINES);
+    header.push(format!(
+        "lines {}-{shown} of {} - arrows and page up/down scroll, c copies, i or esc closes",
+        (inspector.scroll + 1).min(shown),
+        yaml.len(),
+    ));
+    if let Some(message) = &inspector.message {
+        header.push(message.clone());
+    }
+    text.0 = header.join("\n");
+
+    let font = TextFont {
+        font_size: INSPECTOR_FONT_SIZE,
+        ..default()
+    };
+    commands.entity(entity).despawn_descendants();
+    commands.entity(entity).with_children(|spans| {
+        for line in yaml.iter().skip(inspector.scroll).take(INSPECTOR_LINES) {
+            spans.spawn((TextSpan::new("\n"), font.clone()));
+            for (piece, token) in highlight_yaml(line) {
+                spans.spawn((
+                    TextSpan::new(piece),
+                    TextColor(token_color(token).into()),
+                    font.clone(),
+                ));
+            }
+        }
+    });
+}
@@ -0,0 +1,5 @@
+pub mod inspector_component;
+pub mod inspector_plugin;
+pub mod inspector_systems;
+
+pub use inspector_component::InspectorPlugin;
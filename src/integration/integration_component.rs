@@ -0,0 +1,247 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::prelude::*;
+
+use crate::{cli::Grouping, floorplan::FloorPlanError};
+
+/**
+* how the floorplan source is doing - while it is failing the world keeps showing the last good
+* floorplan and a banner says why
+*/
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionStatus {
+    pub error: Option<FloorPlanError>,
+    pub retry: Retry,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Retry {
+    /// a one-shot source such as a file, fix it and restart
+    #[default]
+    Never,
+    /// the source recovers on its own, ie: a watch that reconnects
+    Automatic,
+    After {
+        attempt: u32,
+        delay: Duration,
+    },
+}
+
+impl ConnectionStatus {
+    pub const fn failed(error: FloorPlanError, retry: Retry) -> Self {
+        Self {
+            error: Some(error),
+            retry,
+        }
+    }
+
+    pub fn message(&self) -> Option<String> {
+        let error = self.error.as_ref()?;
+        let retry = match self.retry {
+            Retry::Never => String::new(),
+            Retry::Automatic => "reconnecting - ".to_string(),
+            Retry::After { attempt, delay } => {
+                format!("retrying in {}s (attempt {attempt}) - ", delay.as_secs())
+            }
+        };
+        Some(format!("{error}\n{retry}showing the last good floorplan"))
+    }
+}
+
+#[derive(Component)]
+pub struct ConnectionBanner;
+
+/// how the generators gather a namespace's rooms - starts from `--grouping`, "g" moves on
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RoomGrouping(pub Grouping);
+
+impl RoomGrouping {
+    pub const fn next(self) -> Self {
+        Self(match self.0 {
+            Grouping::Kind => Grouping::PartOf,
+            Grouping::PartOf => Grouping::Instance,
+            Grouping::Instance => Grouping::HelmRelease,
+            Grouping::HelmRelease => Grouping::Kind,
+        })
+    }
+}
+
+/// a room keeps this many of its most recent events
+pub const MAX_EVENTS_PER_ROOM: usize = 50;
+/// how many events the panel shows at once, page up and down scroll through the rest
+pub const EVENTS_PANEL_LINES: usize = 8;
+
+/// a kubernetes `Event` about one object, ie: a pod's `BackOff` or a deployment's `ScalingReplicaSet`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceEvent {
+    pub namespace: Option<String>,
+    pub kind: String,
+    pub name: String,
+    /// `Normal` or `Warning`
+    pub event_type: String,
+    pub reason: String,
+    pub message: String,
+    pub count: u32,
+    pub last_seen: Option<String>,
+}
+
+impl std::fmt::Display for ResourceEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(last_seen) = &self.last_seen {
+            write!(f, "{last_seen} ")?;
+        }
+        write!(f, "{} {}", self.event_type, self.reason)?;
+        if self.count > 1 {
+            write!(f, " (x{})", self.count)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/**
+* recent events keyed by the id of the room of the object they involve, oldest first - replaced
+* as a whole whenever the source has a newer set
+*/
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoomEvents(pub HashMap<String, Vec<ResourceEvent>>);
+
+impl RoomEvents {
+    pub fn new(events: impl IntoIterator<Item = (String, ResourceEvent)>) -> Self {
+        let mut by_room: HashMap<String, Vec<ResourceEvent>> = HashMap::new();
+        for (room_id, event) in events {
+            by_room.entry(room_id).or_default().push(event);
+        }
+        for events in by_room.values_mut() {
+            // rfc 3339 timestamps sort as strings
+            events.sort_by(|a, b| a.last_seen.cmp(&b.last_seen));
+            let excess = events.len().saturating_sub(MAX_EVENTS_PER_ROOM);
+            events.drain(..excess);
+        }
+        Self(by_room)
+    }
+
+    /// how far back the feed of a room can scroll
+    pub fn max_scroll(&self, room_id: &str) -> usize {
+        self.0
+            .get(room_id)
+            .map_or(0, |events| events.len().saturating_sub(EVENTS_PANEL_LINES))
+    }
+
+    /**
+     * the panel text for a room, newest event last - `scroll` counts events back from the newest.
+     * `None` when nothing happened to the object
+     */
+    pub fn feed(&self, room_id: &str, scroll: usize) -> Option<String> {
+        let events = self.0.get(room_id).filter(|events| !events.is_empty())?;
+        let end = events.len() - scroll.min(self.max_scroll(room_id));
+        let start = end.saturating_sub(EVENTS_PANEL_LINES);
+        let mut text = format!("Events {}-{} of {}", start + 1, end, events.len());
+        if events.len() > EVENTS_PANEL_LINES {
+            text.push_str(" (page up/down to scroll)");
+        }
+        for event in &events[start..end] {
+            text.push('\n');
+            text.push_str(&event.to_string());
+        }
+        Some(text)
+    }
+}
+
+/// the events of the room the player is in, `scroll` as in `RoomEvents::feed`
+#[derive(Component, Debug, Default)]
+pub struct EventsPanel {
+    pub room_id: String,
+    pub scroll: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grouping_cycles_back_to_kinds() {
+        let mut grouping = RoomGrouping::default();
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            grouping = grouping.next();
+            seen.push(grouping.0);
+        }
+        assert_eq!(
+            seen,
+            vec![
+                Grouping::PartOf,
+                Grouping::Instance,
+                Grouping::HelmRelease,
+                Grouping::Kind
+            ]
+        );
+    }
+
+    #[test]
+    fn test_connection_status_message() {
+        assert_eq!(ConnectionStatus::default().message(), None);
+
+        let status = ConnectionStatus::failed(
+            FloorPlanError::AuthError("token expired".to_string()),
+            Retry::After {
+                attempt: 3,
+                delay: Duration::from_secs(8),
+            },
+        );
+        assert_eq!(
+            status.message().as_deref(),
+            Some(
+                "not authorized: token expired\nretrying in 8s (attempt 3) - showing the last good floorplan"
+            )
+        );
+
+        let status = ConnectionStatus::failed(
+            FloorPlanError::ConnectionError("connection refused".to_string()),
+            Retry::Automatic,
+        );
+        assert!(status.message().unwrap().contains("reconnecting"));
+
+        let status = ConnectionStatus::failed(
+            FloorPlanError::ManifestError("bad indent".to_string()),
+            Retry::Never,
+        );
+        assert_eq!(
+            status.message().as_deref(),
+            Some("bad manifest: bad indent\nshowing the last good floorplan")
+        );
+    }
+
+    #[test]
+    fn test_room_events_feed() {
+        let event = |minute: usize, reason: &str| {
+            (
+                "default-Pod-web".to_string(),
+                ResourceEvent {
+                    event_type: "Warning".to_string(),
+                    reason: reason.to_string(),
+                    message: "Back-off restarting failed container".to_string(),
+                    count: 3,
+                    last_seen: Some(format!("2024-05-01T03:{minute:02}:00Z")),
+                    ..Default::default()
+                },
+            )
+        };
+        let room_events = RoomEvents::new((0..60).rev().map(|minute| event(minute, "BackOff")));
+        let events = &room_events.0["default-Pod-web"];
+        assert_eq!(events.len(), MAX_EVENTS_PER_ROOM);
+        assert_eq!(events[0].last_seen.as_deref(), Some("2024-05-01T03:10:00Z"));
+
+        let feed = room_events.feed("default-Pod-web", 0).unwrap();
+        let lines: Vec<&str> = feed.lines().collect();
+        assert_eq!(lines.len(), EVENTS_PANEL_LINES + 1);
+        assert!(lines[0].starts_with("Events 43-50 of 50"));
+        assert_eq!(
+            lines[EVENTS_PANEL_LINES],
+            "2024-05-01T03:59:00Z Warning BackOff (x3): Back-off restarting failed container"
+        );
+
+        let oldest = room_events.feed("default-Pod-web", usize::MAX).unwrap();
+        assert!(oldest.starts_with("Events 1-8 of 50"));
+        assert!(room_events.feed("default-Pod-db", 0).is_none());
+    }
+}
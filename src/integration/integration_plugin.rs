@@ -6,14 +6,24 @@ use clap::Parser;
 #[cfg(feature = "k8s")]
 use super::{k8s_file, k8s_live};
 
-use super::test_mode;
+use super::{
+    integration_component::{ConnectionStatus, RoomEvents, RoomGrouping},
+    integration_systems::{cycle_grouping, update_connection_banner, update_events_panel},
+    snapshot, test_mode,
+};
+use crate::state::GameState;
 
 pub struct IntegrationPlugin;
 
 impl Plugin for IntegrationPlugin {
     fn build(&self, app: &mut App) {
-        let generator_choise = cli::Cli::parse().room_generator;
-        match generator_choise {
+        let cli = cli::Cli::parse();
+        app.insert_resource(ConnectionStatus::default())
+            .insert_resource(RoomEvents::default())
+            .insert_resource(RoomGrouping(cli.grouping.unwrap_or_default()))
+            .add_systems(Update, (update_connection_banner, update_events_panel))
+            .add_systems(Update, cycle_grouping.run_if(in_state(GameState::InGame)));
+        match cli.room_generator {
             #[cfg(feature = "k8s")]
             Some(cli::RoomGeneratorType::K8sLive) => {
                 app.add_plugins(k8s_live::K8sIntegrationPlugin)
@@ -22,7 +32,13 @@ impl Plugin for IntegrationPlugin {
             None | Some(cli::RoomGeneratorType::K8sFile) => {
                 app.add_plugins(k8s_file::K8sIntegrationPlugin)
             }
+            Some(cli::RoomGeneratorType::Snapshot) => {
+                app.add_plugins(snapshot::SnapshotIntegrationPlugin)
+            }
             _ => app.add_plugins((test_mode::TestModeIntegrationPlugin,)),
         };
+        if cli.save_snapshot.is_some() {
+            app.add_plugins(snapshot::SnapshotRecorderPlugin);
+        }
     }
 }
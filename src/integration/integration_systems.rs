@@ -0,0 +1,132 @@
+use bevy::{color::palettes::tailwind::RED_800, prelude::*};
+use clap::ValueEnum;
+use leafwing_input_manager::prelude::ActionState;
+
+use super::integration_component::{
+    ConnectionBanner, ConnectionStatus, EventsPanel, RoomEvents, RoomGrouping,
+};
+use crate::{
+    player::{Player, player_component::Action},
+    state::GameState,
+    world::world_component::CurrentFloorPlan,
+};
+
+/// the regroup key moves on to the next grouping, the generator rebuilds the floorplan with it
+pub fn cycle_grouping(
+    player_query: Query<&ActionState<Action>, With<Player>>,
+    mut grouping: ResMut<RoomGrouping>,
+) {
+    if let Ok(action_state) = player_query.get_single()
+        && action_state.just_pressed(&Action::Regroup)
+    {
+        *grouping = grouping.next();
+        if let Some(value) = grouping.0.to_possible_value() {
+            info!("Grouping rooms by {}", value.get_name());
+        }
+    }
+}
+
+/// show, update or remove the banner whenever the connection status changes
+pub fn update_connection_banner(
+    mut commands: Commands,
+    status: Res<ConnectionStatus>,
+    mut banner_query: Query<(Entity, &mut Text), With<ConnectionBanner>>,
+) {
+    if !status.is_changed() {
+        return;
+    }
+
+    match (status.message(), banner_query.get_single_mut()) {
+        (Some(message), Ok((_, mut text))) => text.0 = message,
+        (Some(message), Err(_)) => {
+            commands.spawn((
+                Text::new(message),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                BackgroundColor(Color::from(RED_800)),
+                TextLayout::new_with_justify(JustifyText::Center),
+                Node {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(5.0),
+                    left: Val::Percent(25.0),
+                    width: Val::Percent(50.0),
+                    padding: UiRect::all(Val::Px(6.0)),
+                    ..default()
+                },
+                ConnectionBanner,
+            ));
+        }
+        (None, Ok((entity, _))) => commands.entity(entity).despawn_recursive(),
+        (None, Err(_)) => {}
+    }
+}
+
+/**
+* show the events of the room the player is in next to the room info, page up and down scroll
+* back through older ones while in game. the panel goes away in rooms nothing happened to
+*/
+pub fn update_events_panel(
+    mut commands: Commands,
+    room_events: Res<RoomEvents>,
+    current_floorplan: Res<CurrentFloorPlan>,
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut panel_query: Query<(Entity, &mut Text, &mut EventsPanel)>,
+) {
+    // the inspector pages with the same keys, the panel underneath it stays put
+    let pressed = |key| *state.get() == GameState::InGame && keys.just_pressed(key);
+    let scrolled = pressed(KeyCode::PageUp) || pressed(KeyCode::PageDown);
+    if !room_events.is_changed() && !current_floorplan.is_changed() && !scrolled {
+        return;
+    }
+
+    let room_id = current_floorplan
+        .you_are_here
+        .as_ref()
+        .map(|room| room.id.clone())
+        .unwrap_or_default();
+    let mut panel = panel_query.get_single_mut().ok();
+    let mut scroll = panel
+        .as_ref()
+        .filter(|(_, _, panel)| panel.room_id == room_id)
+        .map_or(0, |(_, _, panel)| panel.scroll);
+    if pressed(KeyCode::PageUp) {
+        scroll = (scroll + 1).min(room_events.max_scroll(&room_id));
+    }
+    if pressed(KeyCode::PageDown) {
+        scroll = scroll.saturating_sub(1);
+    }
+
+    match (room_events.feed(&room_id, scroll), panel.as_mut()) {
+        (Some(feed), Some((_, text, panel))) => {
+            text.0 = feed;
+            panel.room_id = room_id;
+            panel.scroll = scroll;
+        }
+        (Some(feed), None) => {
+            commands.spawn((
+                Text::new(feed),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                BackgroundColor(Color::BLACK.with_alpha(0.6)),
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(35.0),
+                    right: Val::Px(5.0),
+                    width: Val::Percent(35.0),
+                    padding: UiRect::all(Val::Px(6.0)),
+                    ..default()
+                },
+                EventsPanel { room_id, scroll },
+            ));
+        }
+        (None, Some((entity, _, _))) => commands.entity(*entity).despawn_recursive(),
+        (None, None) => {}
+    }
+}
@@ -1,18 +1,1050 @@
-#[derive(Debug, Clone, Eq, PartialEq)]
+use std::{collections::BTreeMap, time::Duration};
+
+use crate::cli::Grouping;
+use crate::floorplan::{
+    FloorPlan, FloorPlanResult, NO_MATCHING_PODS, ResourceStatus, Room, RoomKind,
+};
+
+use super::integration_component::ResourceEvent;
+use super::k8s_file::k8s_integration_systems::{
+    Suites, connect_rooms_with_doors, container_room_id, volume_mount_room_id,
+};
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
 pub struct IntegrationResource {
     pub name: String,
     pub kind: String,
-    pub parent: Option<Box<Self>>,
+    /// every owner reference, the controlling owner among them
+    pub owners: Vec<Owner>,
     pub children: Vec<Self>,
+    pub labels: BTreeMap<String, String>,
+    pub attributes: BTreeMap<String, String>,
+    pub status: Option<ResourceStatus>,
+    /// the labels a service routes to, empty for everything else
+    pub selector: BTreeMap<String, String>,
+    /// where an ingress sends traffic, empty for everything else
+    pub routes: Vec<IngressRoute>,
+    /// objects this one names, ie: the configmap behind a volume mount or the volume of a claim
+    pub references: Vec<Self>,
+    /// the object as yaml, see `trimmed_source`
+    pub source: Option<String>,
+}
+
+/// an entry of `metadata.ownerReferences`
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Owner {
+    pub kind: String,
+    pub name: String,
+    /// the owner that manages this object, ie: the replicaset of a pod, not a mere garbage collection owner
+    pub controller: bool,
+}
+
+/// one host and path of an ingress and the service behind it
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct IngressRoute {
+    pub host: String,
+    pub path: String,
+    pub path_type: Option<String>,
+    /// `None` for a resource backend, which has no service to lead to
+    pub service: Option<String>,
+    pub port: Option<String>,
 }
 
 impl IntegrationResource {
-    pub fn new(name: String, kind: String, parent: Option<Self>, children: Vec<Self>) -> Self {
+    pub fn new(name: String, kind: String, children: Vec<Self>) -> Self {
         Self {
             name,
             kind,
-            parent: parent.map(Box::new),
             children,
+            ..Default::default()
+        }
+    }
+
+    #[must_use]
+    pub fn with_owners(mut self, owners: Vec<Owner>) -> Self {
+        self.owners = owners;
+        self
+    }
+
+    #[must_use]
+    pub fn with_labels(mut self, labels: BTreeMap<String, String>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    #[must_use]
+    pub fn with_status(mut self, status: Option<ResourceStatus>) -> Self {
+        self.status = status;
+        self
+    }
+
+    #[must_use]
+    pub fn with_selector(mut self, selector: BTreeMap<String, String>) -> Self {
+        self.selector = selector;
+        self
+    }
+
+    #[must_use]
+    pub fn with_references(mut self, references: Vec<Self>) -> Self {
+        self.references = references;
+        self
+    }
+
+    #[must_use]
+    pub fn with_routes(mut self, routes: Vec<IngressRoute>) -> Self {
+        self.routes = routes;
+        self
+    }
+
+    #[must_use]
+    pub fn with_source(mut self, source: Option<String>) -> Self {
+        self.source = source;
+        self
+    }
+
+    #[must_use]
+    pub fn with_attributes(mut self, attributes: BTreeMap<String, String>) -> Self {
+        self.attributes = attributes;
+        self
+    }
+}
+
+/**
+* a room per resource behind a door from its suite, or from `outer_room` when it is in none - each
+* with a room per container, and each container with a room per volume mount. both the manifest
+* file and the live generator lay out namespaced resources with it
+*/
+pub fn add_resource_rooms(
+    plan: &mut FloorPlan,
+    resources: Vec<IntegrationResource>,
+    suites: &Suites,
+    namespace: &str,
+    outer_room: &Room,
+    door_id_generator: &mut usize,
+) -> FloorPlanResult<()> {
+    for r in resources {
+        let room = create_resource_room(namespace, &r);
+        plan.add_room(room.clone());
+        let outer_room = suites.room(&r).unwrap_or(outer_room);
+        connect_rooms_with_doors(plan, &room, outer_room, door_id_generator)?;
+
+        add_container_rooms(plan, namespace, &r, &room, door_id_generator)?;
+    }
+    Ok(())
+}
+
+fn create_resource_room(namespace: &str, r: &IntegrationResource) -> Room {
+    Room {
+        id: format!("{namespace}-{}-{}", r.kind, r.name),
+        name: format!("{} {}", r.kind, r.name),
+        kind: RoomKind::from_k8s_kind(&r.kind),
+        namespace: Some(namespace.to_string()),
+        labels: r.labels.clone(),
+        attributes: r.attributes.clone(),
+        status: r.status.clone(),
+        source: r.source.clone(),
+        ..Default::default()
+    }
+}
+
+fn add_container_rooms(
+    plan: &mut FloorPlan,
+    namespace: &str,
+    r: &IntegrationResource,
+    room: &Room,
+    door_id_generator: &mut usize,
+) -> FloorPlanResult<()> {
+    for container in &r.children {
+        let container_room = create_container_room(namespace, r, container);
+        plan.add_room(container_room.clone());
+        connect_rooms_with_doors(plan, &container_room, room, door_id_generator)?;
+        for volume_mount in &container.children {
+            let volume_mount_room = create_volume_mount_room(namespace, r, container, volume_mount);
+            plan.add_room(volume_mount_room.clone());
+            connect_rooms_with_doors(plan, &volume_mount_room, &container_room, door_id_generator)?;
+        }
+    }
+    Ok(())
+}
+
+fn create_container_room(
+    namespace: &str,
+    r: &IntegrationResource,
+    container: &IntegrationResource,
+) -> Room {
+    let mut attributes = container.attributes.clone();
+    attributes.insert(r.kind.to_lowercase(), r.name.clone());
+    attributes.insert("container".to_string(), container.name.clone());
+    Room {
+        id: container_room_id(namespace, r, container),
+        name: format!("{} {}", "container", container.name),
+        kind: RoomKind::Container,
+        namespace: Some(namespace.to_string()),
+        attributes,
+        status: container.status.clone(),
+        ..Default::default()
+    }
+}
+
+fn create_volume_mount_room(
+    namespace: &str,
+    r: &IntegrationResource,
+    container: &IntegrationResource,
+    volume_mount: &IntegrationResource,
+) -> Room {
+    Room {
+        id: volume_mount_room_id(namespace, r, container, volume_mount),
+        name: format!("{} {}", "volume mount", volume_mount.name),
+        kind: RoomKind::VolumeMount,
+        namespace: Some(namespace.to_string()),
+        attributes: volume_mount.attributes.clone(),
+        ..Default::default()
+    }
+}
+
+/// collect the string values of a json object such as `metadata.labels`
+pub fn string_map(value: &serde_json::Value) -> BTreeMap<String, String> {
+    value
+        .as_object()
+        .map(|map| {
+            map.iter()
+                .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// keep only the fields that are present, so room info doesn't fill up with blanks
+pub fn attribute_map<const N: usize>(
+    attributes: [(&str, Option<&str>); N],
+) -> BTreeMap<String, String> {
+    attributes
+        .into_iter()
+        .filter_map(|(k, v)| v.map(|v| (k.to_string(), v.to_string())))
+        .collect()
+}
+
+/// the annotation helm puts on every object a release installs, kept as the `release` attribute
+pub const HELM_RELEASE_ANNOTATION: &str = "meta.helm.sh/release-name";
+
+/// the application a resource itself says it is part of, never one under `Grouping::Kind`
+pub fn suite_of(grouping: Grouping, r: &IntegrationResource) -> Option<&str> {
+    match grouping {
+        Grouping::Kind => None,
+        Grouping::PartOf => r.labels.get("app.kubernetes.io/part-of"),
+        Grouping::Instance => r.labels.get("app.kubernetes.io/instance"),
+        Grouping::HelmRelease => r.attributes.get("release"),
+    }
+    .map(String::as_str)
+}
+
+/// exponential backoff for retrying a floorplan source - doubles from `initial` up to `max`
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    attempt: u32,
+}
+
+impl Backoff {
+    pub const fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            attempt: 0,
+        }
+    }
+
+    /// how long to wait before the next try, counting it as an attempt
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self
+            .initial
+            .saturating_mul(1 << self.attempt.min(16))
+            .min(self.max);
+        self.attempt += 1;
+        delay
+    }
+
+    pub const fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    pub const fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn as_u32(value: &serde_json::Value) -> u32 {
+    value.as_u64().unwrap_or_default() as u32
+}
+
+/// the waiting or terminated reason of a container, ie: `CrashLoopBackOff`
+fn container_reason(container_status: &serde_json::Value) -> Option<String> {
+    let state = &container_status["state"];
+    state["waiting"]["reason"]
+        .as_str()
+        .or_else(|| state["terminated"]["reason"].as_str())
+        .map(String::from)
+}
+
+fn get_pod_status(v: &serde_json::Value) -> ResourceStatus {
+    let container_statuses = v["status"]["containerStatuses"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    let total = v["spec"]["containers"]
+        .as_array()
+        .map_or(container_statuses.len(), Vec::len);
+    ResourceStatus {
+        phase: v["status"]["phase"].as_str().map(String::from),
+        #[allow(clippy::cast_possible_truncation)]
+        ready: container_statuses
+            .iter()
+            .filter(|status| status["ready"].as_bool().unwrap_or_default())
+            .count() as u32,
+        #[allow(clippy::cast_possible_truncation)]
+        total: total as u32,
+        restarts: container_statuses
+            .iter()
+            .map(|status| as_u32(&status["restartCount"]))
+            .sum(),
+        reason: container_statuses.iter().find_map(container_reason),
+    }
+}
+
+/**
+* a job is ready once its pods have succeeded - `completions` defaults to one, and a `Failed`
+* condition (ie: the backoff limit was reached) fails the job whatever the counts say. one still
+* running is pending rather than failing
+*/
+fn get_job_status(v: &serde_json::Value) -> ResourceStatus {
+    let finished = v["status"]["conditions"].as_array().and_then(|conditions| {
+        conditions.iter().find(|condition| {
+            condition["status"].as_str() == Some("True")
+                && matches!(condition["type"].as_str(), Some("Complete" | "Failed"))
+        })
+    });
+    ResourceStatus {
+        phase: Some(
+            match finished.and_then(|condition| condition["type"].as_str()) {
+                Some("Failed") => "Failed",
+                Some(_) => "Succeeded",
+                None if as_u32(&v["status"]["active"]) > 0 => "Running",
+                None => "Pending",
+            }
+            .to_string(),
+        ),
+        ready: as_u32(&v["status"]["succeeded"]),
+        total: v["spec"]["completions"].as_u64().map_or(1, |completions| {
+            #[allow(clippy::cast_possible_truncation)]
+            let completions = completions as u32;
+            completions
+        }),
+        reason: finished.and_then(|condition| condition["reason"].as_str().map(String::from)),
+        ..Default::default()
+    }
+}
+
+/// status of one container, looked up by name in the owning pod's `containerStatuses`
+pub fn get_container_status(pod: &serde_json::Value, name: &str) -> Option<ResourceStatus> {
+    let container_status = pod["status"]["containerStatuses"]
+        .as_array()?
+        .iter()
+        .find(|status| status["name"].as_str() == Some(name))?;
+    Some(ResourceStatus {
+        phase: None,
+        ready: u32::from(container_status["ready"].as_bool().unwrap_or_default()),
+        total: 1,
+        restarts: as_u32(&container_status["restartCount"]),
+        reason: container_reason(container_status),
+    })
+}
+
+/**
+* a controller with nothing available yet is still rolling out, unless a condition says the rollout
+* gave up (`Progressing=False`, ie: `ProgressDeadlineExceeded`) or can't create its pods
+* (`ReplicaFailure`)
+*/
+fn get_controller_status(v: &serde_json::Value) -> ResourceStatus {
+    let ready = as_u32(&v["status"]["availableReplicas"]);
+    let total = v["spec"]["replicas"].as_u64().map_or(1, |replicas| {
+        #[allow(clippy::cast_possible_truncation)]
+        let replicas = replicas as u32;
+        replicas
+    });
+    let failure = v["status"]["conditions"].as_array().and_then(|conditions| {
+        conditions.iter().find(|condition| {
+            matches!(
+                (condition["type"].as_str(), condition["status"].as_str()),
+                (Some("Progressing"), Some("False")) | (Some("ReplicaFailure"), Some("True"))
+            )
+        })
+    });
+    ResourceStatus {
+        phase: match failure {
+            Some(_) => Some("Failed".to_string()),
+            None if total > 0 && ready == 0 => Some("Pending".to_string()),
+            None => None,
+        },
+        ready,
+        total,
+        reason: failure.and_then(|condition| condition["reason"].as_str().map(String::from)),
+        ..Default::default()
+    }
+}
+
+/**
+* runtime status for the kinds that have one - `v` is the whole object as json, which both the
+* manifest file and the live api's `DynamicObject` provide
+*/
+pub fn get_status(kind: &str, v: &serde_json::Value) -> Option<ResourceStatus> {
+    match kind {
+        "Pod" => Some(get_pod_status(v)),
+        // a manifest that was never applied has no status to judge it by
+        _ if v["status"].is_null() => None,
+        "Deployment" | "ReplicaSet" | "StatefulSet" => Some(get_controller_status(v)),
+        "DaemonSet" => Some(ResourceStatus {
+            ready: as_u32(&v["status"]["numberAvailable"]),
+            total: as_u32(&v["status"]["desiredNumberScheduled"]),
+            ..Default::default()
+        }),
+        "Job" => Some(get_job_status(v)),
+        _ => None,
+    }
+}
+
+/// a service's `spec.selector` - other kinds select with `matchLabels`, which doors don't follow
+pub fn get_selector(kind: &str, v: &serde_json::Value) -> BTreeMap<String, String> {
+    match kind {
+        "Service" => string_map(&v["spec"]["selector"]),
+        _ => BTreeMap::new(),
+    }
+}
+
+/// an empty selector picks nothing, as with a service whose endpoints are managed by hand
+pub fn selects(selector: &BTreeMap<String, String>, labels: &BTreeMap<String, String>) -> bool {
+    !selector.is_empty()
+        && selector
+            .iter()
+            .all(|(key, value)| labels.get(key) == Some(value))
+}
+
+/**
+* a service is as ready as the pods it selects - one that selects none is flagged as failing,
+* since nothing can answer it
+*/
+pub fn get_service_status(selected_pods: &[&IntegrationResource]) -> ResourceStatus {
+    #[allow(clippy::cast_possible_truncation)]
+    ResourceStatus {
+        ready: selected_pods
+            .iter()
+            .filter(|pod| {
+                pod.status
+                    .as_ref()
+                    .is_some_and(|status| status.total > 0 && status.ready == status.total)
+            })
+            .count() as u32,
+        total: selected_pods.len() as u32,
+        reason: selected_pods
+            .is_empty()
+            .then(|| NO_MATCHING_PODS.to_string()),
+        ..Default::default()
+    }
+}
+
+fn reference(kind: &str, name: Option<&str>) -> Option<IntegrationResource> {
+    name.map(|name| IntegrationResource::new(name.to_string(), kind.to_string(), Vec::new()))
+}
+
+/// the configmaps and secrets a volume (or one source of a projected volume) is filled from
+fn volume_source_references(source: &serde_json::Value) -> Vec<IntegrationResource> {
+    [
+        reference("ConfigMap", source["configMap"]["name"].as_str()),
+        reference(
+            "Secret",
+            source["secret"]["secretName"]
+                .as_str()
+                .or_else(|| source["secret"]["name"].as_str()),
+        ),
+        reference(
+            "PersistentVolumeClaim",
+            source["persistentVolumeClaim"]["claimName"].as_str(),
+        ),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// the type of a volume or projected source, ie: `configMap`, `emptyDir` or `serviceAccountToken`
+fn volume_type(source: &serde_json::Value) -> Option<&str> {
+    source
+        .as_object()?
+        .keys()
+        .map(String::as_str)
+        .find(|key| *key != "name")
+}
+
+/**
+* join a container's volume mount to the pod volume it names - what backs it, to show in the room,
+* and the objects it is filled from. only names are ever read, never a secret's data
+*/
+pub fn get_volume_references(
+    pod: &serde_json::Value,
+    volume_name: &str,
+) -> (BTreeMap<String, String>, Vec<IntegrationResource>) {
+    let Some(volume) = pod["spec"]["volumes"].as_array().and_then(|volumes| {
+        volumes
+            .iter()
+            .find(|v| v["name"].as_str() == Some(volume_name))
+    }) else {
+        return (BTreeMap::new(), Vec::new());
+    };
+
+    let sources: Vec<&serde_json::Value> = volume["projected"]["sources"]
+        .as_array()
+        .map(|sources| sources.iter().collect())
+        .unwrap_or_default();
+    let source_types = sources
+        .iter()
+        .filter_map(|source| volume_type(source))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let attributes = attribute_map([
+        ("volume", volume_type(volume)),
+        ("hostPath", volume["hostPath"]["path"].as_str()),
+        (
+            "sources",
+            Some(source_types.as_str()).filter(|s| !s.is_empty()),
+        ),
+    ]);
+
+    let mut references = volume_source_references(volume);
+    for source in sources {
+        references.extend(volume_source_references(source));
+    }
+    (attributes, references)
+}
+
+/**
+* the cluster scoped objects something is tied to - the node a pod is scheduled on, the volume a
+* claim is bound to and the storage class of a claim or volume
+*/
+pub fn get_references(kind: &str, v: &serde_json::Value) -> Vec<IntegrationResource> {
+    match kind {
+        "Pod" => reference("Node", v["spec"]["nodeName"].as_str())
+            .into_iter()
+            .collect(),
+        "PersistentVolume" => reference("StorageClass", v["spec"]["storageClassName"].as_str())
+            .into_iter()
+            .collect(),
+        "PersistentVolumeClaim" => [
+            reference("PersistentVolume", v["spec"]["volumeName"].as_str()),
+            reference("StorageClass", v["spec"]["storageClassName"].as_str()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// matches any host, for rules without one and for the default backend
+pub const ANY_HOST: &str = "*";
+/// the path of the default backend, which takes whatever no rule matched
+pub const ANY_PATH: &str = "*";
+
+fn ingress_route(
+    host: &str,
+    path: &str,
+    path_type: Option<&str>,
+    backend: &serde_json::Value,
+) -> IngressRoute {
+    let port = &backend["service"]["port"];
+    IngressRoute {
+        host: host.to_string(),
+        path: path.to_string(),
+        path_type: path_type.map(String::from),
+        service: backend["service"]["name"].as_str().map(String::from),
+        port: port["number"]
+            .as_u64()
+            .map(|number| number.to_string())
+            .or_else(|| port["name"].as_str().map(String::from)),
+    }
+}
+
+/// an ingress's `spec.rules`, one route per path, then its default backend if it has one
+pub fn get_ingress_routes(kind: &str, v: &serde_json::Value) -> Vec<IngressRoute> {
+    if kind != "Ingress" {
+        return Vec::new();
+    }
+    let mut routes = Vec::new();
+    for rule in v["spec"]["rules"].as_array().into_iter().flatten() {
+        let host = rule["host"].as_str().unwrap_or(ANY_HOST);
+        for path in rule["http"]["paths"].as_array().into_iter().flatten() {
+            routes.push(ingress_route(
+                host,
+                path["path"].as_str().unwrap_or("/"),
+                path["pathType"].as_str(),
+                &path["backend"],
+            ));
+        }
+    }
+    let default_backend = &v["spec"]["defaultBackend"];
+    if !default_backend.is_null() {
+        routes.push(ingress_route(ANY_HOST, ANY_PATH, None, default_backend));
+    }
+    routes
+}
+
+/// annotations that repeat the object or hold nothing a person reads
+const NOISY_ANNOTATIONS: [&str; 1] = ["kubectl.kubernetes.io/last-applied-configuration"];
+
+/**
+* the object as yaml for the inspector, less what only the api server needs - managed fields,
+* the resource version and the last applied configuration. a secret's data never makes it in
+*/
+pub fn trimmed_source(v: &serde_json::Value) -> Option<String> {
+    let mut object = v.clone();
+    if let Some(metadata) = object["metadata"].as_object_mut() {
+        metadata.remove("managedFields");
+        metadata.remove("resourceVersion");
+        if let Some(annotations) = metadata
+            .get_mut("annotations")
+            .and_then(|annotations| annotations.as_object_mut())
+        {
+            for annotation in NOISY_ANNOTATIONS {
+                annotations.remove(annotation);
+            }
+        }
+    }
+    if object["kind"].as_str() == Some("Secret")
+        && let Some(secret) = object.as_object_mut()
+    {
+        secret.remove("data");
+        secret.remove("stringData");
+    }
+    serde_yaml::to_string(&object).ok()
+}
+
+/// the first of several places a field may be kept that holds a string
+fn first_str(values: &[&serde_json::Value]) -> Option<String> {
+    values
+        .iter()
+        .find_map(|value| value.as_str())
+        .map(String::from)
+}
+
+/**
+* an `Event` from either api - `core/v1` names the object in `involvedObject`, `events.k8s.io/v1`
+* in `regarding`, and the newer one keeps its count and time in `series` when it repeats
+*/
+pub fn get_resource_event(v: &serde_json::Value) -> Option<ResourceEvent> {
+    let involved = if v["involvedObject"].is_object() {
+        &v["involvedObject"]
+    } else {
+        &v["regarding"]
+    };
+    let count = [&v["count"], &v["series"]["count"], &v["deprecatedCount"]]
+        .iter()
+        .find_map(|count| count.as_u64());
+    Some(ResourceEvent {
+        namespace: first_str(&[&involved["namespace"], &v["metadata"]["namespace"]]),
+        kind: involved["kind"].as_str()?.to_string(),
+        name: involved["name"].as_str()?.to_string(),
+        event_type: v["type"].as_str().unwrap_or("Normal").to_string(),
+        reason: v["reason"].as_str().unwrap_or_default().to_string(),
+        message: first_str(&[&v["message"], &v["note"]]).unwrap_or_default(),
+        count: count.map_or(1, |count| {
+            #[allow(clippy::cast_possible_truncation)]
+            let count = count as u32;
+            count
+        }),
+        last_seen: first_str(&[
+            &v["lastTimestamp"],
+            &v["series"]["lastObservedTime"],
+            &v["eventTime"],
+            &v["metadata"]["creationTimestamp"],
+        ]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::floorplan::Health;
+    use serde_json::json;
+
+    #[test]
+    fn test_pod_status() {
+        let pod = json!({
+            "kind": "Pod",
+            "spec": { "containers": [{ "name": "api" }, { "name": "sidecar" }] },
+            "status": {
+                "phase": "Running",
+                "containerStatuses": [
+                    {
+                        "name": "api",
+                        "ready": false,
+                        "restartCount": 7,
+                        "state": { "waiting": { "reason": "CrashLoopBackOff" } }
+                    },
+                    {
+                        "name": "sidecar",
+                        "ready": true,
+                        "restartCount": 1,
+                        "state": { "running": {} }
+                    }
+                ]
+            }
+        });
+
+        let status = get_status("Pod", &pod).unwrap();
+        assert_eq!(status.ready, 1);
+        assert_eq!(status.total, 2);
+        assert_eq!(status.restarts, 8);
+        assert_eq!(status.reason.as_deref(), Some("CrashLoopBackOff"));
+        assert_eq!(status.health(), Health::Failing);
+
+        let api = get_container_status(&pod, "api").unwrap();
+        assert_eq!(api.restarts, 7);
+        assert_eq!(api.health(), Health::Failing);
+        let sidecar = get_container_status(&pod, "sidecar").unwrap();
+        assert_eq!(sidecar.health(), Health::Healthy);
+        assert!(get_container_status(&pod, "missing").is_none());
+    }
+
+    #[test]
+    fn test_backoff() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(10));
+        let delays: Vec<u64> = (0..6).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 10, 10]);
+        assert_eq!(backoff.attempt(), 6);
+
+        backoff.reset();
+        assert_eq!(backoff.attempt(), 0);
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+
+        for _ in 0..100 {
+            backoff.next_delay();
+        }
+        assert_eq!(backoff.next_delay(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_controller_status() {
+        let deployment = json!({
+            "spec": { "replicas": 3 },
+            "status": { "availableReplicas": 2 }
+        });
+        let status = get_status("Deployment", &deployment).unwrap();
+        assert_eq!((status.ready, status.total), (2, 3));
+        assert_eq!(status.health(), Health::Pending);
+
+        let scaled_to_zero = json!({ "spec": { "replicas": 0 }, "status": {} });
+        assert_eq!(
+            get_status("Deployment", &scaled_to_zero).unwrap().health(),
+            Health::Healthy
+        );
+
+        assert!(get_status("ConfigMap", &json!({})).is_none());
+    }
+
+    #[test]
+    fn test_controller_without_status() {
+        let manifest = json!({ "spec": { "replicas": 3 } });
+        assert!(get_status("Deployment", &manifest).is_none());
+        assert!(get_status("StatefulSet", &manifest).is_none());
+        assert!(get_status("DaemonSet", &json!({ "spec": {} })).is_none());
+    }
+
+    #[test]
+    fn test_controller_rollout() {
+        let fresh = json!({
+            "spec": { "replicas": 3 },
+            "status": {
+                "conditions": [
+                    { "type": "Available", "status": "False", "reason": "MinimumReplicasUnavailable" },
+                    { "type": "Progressing", "status": "True", "reason": "ReplicaSetUpdated" }
+                ]
+            }
+        });
+        let status = get_status("Deployment", &fresh).unwrap();
+        assert_eq!(status.health(), Health::Pending);
+        assert_eq!(status.to_string(), "Pending 0/3 ready");
+
+        let stuck = json!({
+            "spec": { "replicas": 3 },
+            "status": {
+                "conditions": [
+                    { "type": "Progressing", "status": "False", "reason": "ProgressDeadlineExceeded" }
+                ]
+            }
+        });
+        let status = get_status("Deployment", &stuck).unwrap();
+        assert_eq!(status.health(), Health::Failing);
+        assert_eq!(status.reason.as_deref(), Some("ProgressDeadlineExceeded"));
+
+        let quota = json!({
+            "spec": { "replicas": 2 },
+            "status": {
+                "replicas": 0,
+                "conditions": [
+                    { "type": "ReplicaFailure", "status": "True", "reason": "FailedCreate" }
+                ]
+            }
+        });
+        assert_eq!(
+            get_status("ReplicaSet", &quota).unwrap().health(),
+            Health::Failing
+        );
+    }
+
+    #[test]
+    fn test_job_status() {
+        let running = json!({ "spec": {}, "status": { "active": 1 } });
+        let status = get_status("Job", &running).unwrap();
+        assert_eq!(status.phase.as_deref(), Some("Running"));
+        assert_eq!(status.health(), Health::Pending);
+
+        let complete = json!({
+            "spec": { "completions": 2 },
+            "status": {
+                "succeeded": 2,
+                "conditions": [{ "type": "Complete", "status": "True" }]
+            }
+        });
+        let status = get_status("Job", &complete).unwrap();
+        assert_eq!((status.ready, status.total), (2, 2));
+        assert_eq!(status.health(), Health::Healthy);
+
+        let failed = json!({
+            "spec": {},
+            "status": {
+                "failed": 6,
+                "conditions": [
+                    { "type": "FailureTarget", "status": "True" },
+                    { "type": "Failed", "status": "True", "reason": "BackoffLimitExceeded" }
+                ]
+            }
+        });
+        let status = get_status("Job", &failed).unwrap();
+        assert_eq!(status.reason.as_deref(), Some("BackoffLimitExceeded"));
+        assert_eq!(status.health(), Health::Failing);
+    }
+
+    #[test]
+    fn test_service_selects_pods() {
+        let service = json!({ "kind": "Service", "spec": { "selector": { "app": "web" } } });
+        let selector = get_selector("Service", &service);
+        assert!(get_selector("Deployment", &service).is_empty());
+
+        let pod = |name: &str, app: &str, ready: u32| {
+            IntegrationResource::new(name.to_string(), "Pod".to_string(), Vec::new())
+                .with_labels(BTreeMap::from([
+                    ("app".to_string(), app.to_string()),
+                    ("tier".to_string(), "front".to_string()),
+                ]))
+                .with_status(Some(ResourceStatus {
+                    ready,
+                    total: 1,
+                    ..Default::default()
+                }))
+        };
+        let pods = [
+            pod("web-1", "web", 1),
+            pod("web-2", "web", 0),
+            pod("db-1", "db", 1),
+        ];
+        let selected: Vec<&IntegrationResource> = pods
+            .iter()
+            .filter(|pod| selects(&selector, &pod.labels))
+            .collect();
+        assert_eq!(selected.len(), 2);
+        assert!(!selects(&BTreeMap::new(), &pods[0].labels));
+
+        let status = get_service_status(&selected);
+        assert_eq!((status.ready, status.total), (1, 2));
+        assert_eq!(status.health(), Health::Pending);
+
+        let orphan = get_service_status(&[]);
+        assert_eq!(orphan.reason.as_deref(), Some(NO_MATCHING_PODS));
+        assert_eq!(orphan.health(), Health::Failing);
+    }
+
+    #[test]
+    fn test_ingress_routes() {
+        let ingress = json!({
+            "kind": "Ingress",
+            "spec": {
+                "defaultBackend": { "service": { "name": "fallback", "port": { "name": "http" } } },
+                "rules": [
+                    {
+                        "host": "shop.example.com",
+                        "http": { "paths": [
+                            { "path": "/api", "pathType": "Prefix",
+                              "backend": { "service": { "name": "api", "port": { "number": 8080 } } } },
+                            { "path": "/static", "pathType": "Prefix",
+                              "backend": { "resource": { "kind": "StorageBucket", "name": "assets" } } }
+                        ] }
+                    },
+                    { "http": { "paths": [
+                        { "backend": { "service": { "name": "web", "port": { "number": 80 } } } }
+                    ] } }
+                ]
+            }
+        });
+        let routes = get_ingress_routes("Ingress", &ingress);
+        assert!(get_ingress_routes("Service", &ingress).is_empty());
+        assert_eq!(routes.len(), 4);
+        assert_eq!(
+            routes[0],
+            IngressRoute {
+                host: "shop.example.com".to_string(),
+                path: "/api".to_string(),
+                path_type: Some("Prefix".to_string()),
+                service: Some("api".to_string()),
+                port: Some("8080".to_string()),
+            }
+        );
+        assert_eq!(routes[1].service, None);
+        assert_eq!(
+            (routes[2].host.as_str(), routes[2].path.as_str()),
+            (ANY_HOST, "/")
+        );
+        assert_eq!(
+            (routes[3].path.as_str(), routes[3].port.as_deref()),
+            (ANY_PATH, Some("http"))
+        );
+    }
+
+    #[test]
+    fn test_volume_references() {
+        let pod = json!({
+            "kind": "Pod",
+            "spec": {
+                "volumes": [
+                    { "name": "settings", "configMap": { "name": "web-settings" } },
+                    { "name": "tls", "secret": { "secretName": "web-tls" } },
+                    { "name": "data", "persistentVolumeClaim": { "claimName": "web-data" } },
+                    { "name": "logs", "hostPath": { "path": "/var/log" } },
+                    { "name": "api-access", "projected": { "sources": [
+                        { "serviceAccountToken": { "path": "token" } },
+                        { "configMap": { "name": "kube-root-ca.crt" } },
+                        { "secret": { "name": "extra-ca" } }
+                    ] } }
+                ]
+            }
+        });
+        let refs = |volume| -> Vec<(String, String)> {
+            get_volume_references(&pod, volume)
+                .1
+                .into_iter()
+                .map(|r| (r.kind, r.name))
+                .collect()
+        };
+        let pair = |kind: &str, name: &str| (kind.to_string(), name.to_string());
+
+        assert_eq!(refs("settings"), vec![pair("ConfigMap", "web-settings")]);
+        assert_eq!(refs("tls"), vec![pair("Secret", "web-tls")]);
+        assert_eq!(
+            refs("data"),
+            vec![pair("PersistentVolumeClaim", "web-data")]
+        );
+        assert!(refs("logs").is_empty());
+        assert!(refs("no-such-volume").is_empty());
+        assert_eq!(
+            refs("api-access"),
+            vec![
+                pair("ConfigMap", "kube-root-ca.crt"),
+                pair("Secret", "extra-ca")
+            ]
+        );
+
+        let (attributes, _) = get_volume_references(&pod, "logs");
+        assert_eq!(
+            attributes.get("volume").map(String::as_str),
+            Some("hostPath")
+        );
+        assert_eq!(
+            attributes.get("hostPath").map(String::as_str),
+            Some("/var/log")
+        );
+        let (attributes, _) = get_volume_references(&pod, "api-access");
+        assert_eq!(
+            attributes.get("sources").map(String::as_str),
+            Some("serviceAccountToken, configMap, secret")
+        );
+
+        let claim = json!({
+            "kind": "PersistentVolumeClaim",
+            "spec": { "volumeName": "pv-0042", "storageClassName": "fast" }
+        });
+        let bound: Vec<String> = get_references("PersistentVolumeClaim", &claim)
+            .into_iter()
+            .map(|r| format!("{} {}", r.kind, r.name))
+            .collect();
+        assert_eq!(bound, vec!["PersistentVolume pv-0042", "StorageClass fast"]);
+    }
+
+    #[test]
+    fn test_resource_events() {
+        let core = json!({
+            "kind": "Event",
+            "metadata": { "name": "web-1.17c", "namespace": "shop" },
+            "involvedObject": { "kind": "Pod", "name": "web-1", "namespace": "shop" },
+            "type": "Warning",
+            "reason": "BackOff",
+            "message": "Back-off restarting failed container",
+            "count": 12,
+            "lastTimestamp": "2024-05-01T03:12:00Z"
+        });
+        let event = get_resource_event(&core).unwrap();
+        assert_eq!((event.kind.as_str(), event.name.as_str()), ("Pod", "web-1"));
+        assert_eq!(event.namespace.as_deref(), Some("shop"));
+        assert_eq!(event.count, 12);
+
+        let events_api = json!({
+            "kind": "Event",
+            "metadata": { "name": "web.17d", "namespace": "shop" },
+            "regarding": { "kind": "Deployment", "name": "web" },
+            "reason": "ScalingReplicaSet",
+            "note": "Scaled up replica set web-7f9 to 3",
+            "eventTime": "2024-05-01T03:10:00.000000Z"
+        });
+        let event = get_resource_event(&events_api).unwrap();
+        assert_eq!(event.event_type, "Normal");
+        assert_eq!(event.namespace.as_deref(), Some("shop"));
+        assert_eq!(event.message, "Scaled up replica set web-7f9 to 3");
+        assert_eq!(event.count, 1);
+        assert_eq!(
+            event.last_seen.as_deref(),
+            Some("2024-05-01T03:10:00.000000Z")
+        );
+
+        assert!(get_resource_event(&json!({ "kind": "Event" })).is_none());
+    }
+
+    #[test]
+    fn test_trimmed_source() {
+        let secret = json!({
+            "kind": "Secret",
+            "metadata": {
+                "name": "db",
+                "resourceVersion": "4711",
+                "managedFields": [{ "manager": "kubectl" }],
+                "annotations": {
+                    "kubectl.kubernetes.io/last-applied-configuration": "{}",
+                    "owner": "payments"
+                }
+            },
+            "data": { "password": "aHVudGVyMg==" }
+        });
+        let source = trimmed_source(&secret).unwrap();
+        assert!(source.contains("owner: payments"));
+        for trimmed in ["4711", "managedFields", "last-applied", "password"] {
+            assert!(!source.contains(trimmed), "{source}");
         }
     }
 }
@@ -1,11 +1,16 @@
 use bevy::prelude::*;
 
 use super::k8s_integration_systems::fire_k8s_file_floorplan_event;
+use crate::integration::integration_component::RoomGrouping;
 
 pub struct K8sIntegrationPlugin;
 
 impl Plugin for K8sIntegrationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, fire_k8s_file_floorplan_event);
+        // once at the start, then again whenever the rooms are grouped differently
+        app.add_systems(
+            Update,
+            fire_k8s_file_floorplan_event.run_if(resource_changed::<RoomGrouping>),
+        );
     }
 }
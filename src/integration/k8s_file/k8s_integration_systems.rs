@@ -1,23 +1,53 @@
-use std::fs;
+use std::collections::{BTreeMap, HashMap};
 
-use crate::floorplan::{Door, FloorPlan, FloorPlanEvent, FloorPlanResult, Room};
+use crate::cli::{Cli, Grouping, Hierarchy};
+use crate::floorplan::{
+    Door, DoorKind, FloorPlan, FloorPlanError, FloorPlanEvent, FloorPlanResult, Room, RoomKind,
+};
+use crate::integration::integration_component::{
+    ConnectionStatus, ResourceEvent, Retry, RoomEvents, RoomGrouping,
+};
+use crate::integration::integration_utils::{
+    ANY_HOST, IngressRoute, IntegrationResource, Owner, add_resource_rooms, attribute_map,
+    get_service_status, selects, suite_of,
+};
 use bevy::prelude::*;
-use serde_json::json;
-use serde_yaml::Value;
+use clap::{Parser, ValueEnum};
 
-use super::k8s_json::{get_names, get_namespaces};
+use super::k8s_json::{
+    get_cluster_scoped_names, get_events, get_names, get_namespaces, get_node_names,
+};
+use super::k8s_manifest::{DEFAULT_K8S_FILE, load_manifests};
 
 pub fn connect_rooms_with_doors(
     plan: &mut FloorPlan,
     room1: &Room,
     room2: &Room,
     door_id: &mut usize,
+) -> FloorPlanResult<()> {
+    connect_rooms_with_door_kinds(
+        plan,
+        room1,
+        room2,
+        (DoorKind::Passage, DoorKind::Passage),
+        door_id,
+    )
+}
+
+/// a door each way between two rooms, the first `kind` leads from `room1` and the second back
+fn connect_rooms_with_door_kinds(
+    plan: &mut FloorPlan,
+    room1: &Room,
+    room2: &Room,
+    kinds: (DoorKind, DoorKind),
+    door_id: &mut usize,
 ) -> FloorPlanResult<()> {
     debug!("Connecting rooms with doors");
     let door1 = Door {
         id: door_id.to_string(),
         name: format!("Door to {}", room2.name),
         is_exit: false,
+        kind: kinds.0,
     };
     *door_id += 1;
     plan.add_door(
@@ -30,6 +60,7 @@ pub fn connect_rooms_with_doors(
         id: door_id.to_string(),
         name: format!("Door to {}", room1.name),
         is_exit: true, // second door is always the way out
+        kind: kinds.1,
     };
     *door_id += 1;
     plan.add_door(
@@ -42,171 +73,1210 @@ pub fn connect_rooms_with_doors(
     Ok(())
 }
 
-fn add_rooms(
+/// unique per pod - two pods of a deployment run containers with the same names
+pub fn container_room_id(
+    namespace: &str,
+    r: &IntegrationResource,
+    container: &IntegrationResource,
+) -> String {
+    format!(
+        "{namespace}-{}-{}-container-{}",
+        r.kind, r.name, container.name
+    )
+}
+
+pub fn volume_mount_room_id(
+    namespace: &str,
+    r: &IntegrationResource,
+    container: &IntegrationResource,
+    volume_mount: &IntegrationResource,
+) -> String {
+    format!(
+        "{}-{}",
+        container_room_id(namespace, r, container),
+        volume_mount.name
+    )
+}
+
+fn create_suite_room(grouping: Grouping, namespace: &str, suite: &str) -> Room {
+    let grouping = grouping.to_possible_value();
+    Room {
+        id: format!("{namespace}-suite-{suite}"),
+        name: format!("{namespace} {suite} Suite"),
+        kind: RoomKind::Suite,
+        namespace: Some(namespace.to_string()),
+        attributes: attribute_map([
+            ("suite", Some(suite)),
+            ("grouping", grouping.as_ref().map(|value| value.get_name())),
+        ]),
+        ..Default::default()
+    }
+}
+
+/**
+* the applications of one namespace under a `Grouping`, and which resources are in each. a resource
+* that names no application joins the one of its controller, so the pods of a release's deployment
+* are part of the release too. nothing is in a suite under `Grouping::Kind`
+*/
+#[derive(Debug, Default)]
+pub struct Suites {
+    rooms: BTreeMap<String, Room>,
+    members: HashMap<(String, String), String>,
+}
+
+impl Suites {
+    pub fn new(grouping: Grouping, namespace: &str, resources: &[IntegrationResource]) -> Self {
+        let by_name: HashMap<(&str, &str), &IntegrationResource> = resources
+            .iter()
+            .map(|r| ((r.kind.as_str(), r.name.as_str()), r))
+            .collect();
+        let controller = |r: &&IntegrationResource| {
+            let owner = r.owners.iter().find(|owner| owner.controller)?;
+            by_name
+                .get(&(owner.kind.as_str(), owner.name.as_str()))
+                .copied()
+        };
+        let mut suites = Self::default();
+        for r in resources {
+            // bounded, should owner references ever go round in a circle
+            let Some(suite) = std::iter::successors(Some(r), controller)
+                .take(resources.len())
+                .find_map(|resource| suite_of(grouping, resource))
+            else {
+                continue;
+            };
+            suites
+                .rooms
+                .entry(suite.to_string())
+                .or_insert_with(|| create_suite_room(grouping, namespace, suite));
+            suites
+                .members
+                .insert((r.kind.clone(), r.name.clone()), suite.to_string());
+        }
+        suites
+    }
+
+    /// a door from the namespace to every suite
+    pub fn add_rooms(
+        &self,
+        plan: &mut FloorPlan,
+        namespace_room: &Room,
+        door_id_generator: &mut usize,
+    ) -> FloorPlanResult<()> {
+        for room in self.rooms.values() {
+            plan.add_room(room.clone());
+            connect_rooms_with_doors(plan, namespace_room, room, door_id_generator)?;
+        }
+        Ok(())
+    }
+
+    pub fn room(&self, r: &IntegrationResource) -> Option<&Room> {
+        let suite = self.members.get(&(r.kind.clone(), r.name.clone()))?;
+        self.rooms.get(suite)
+    }
+
+    /// a kind keeps its hallway unless every one of its resources is in a suite
+    pub fn needs_hallway(&self, resources: &[IntegrationResource]) -> bool {
+        self.rooms.is_empty() || resources.iter().any(|r| self.room(r).is_none())
+    }
+}
+
+/// kinds that live outside any namespace, so their rooms do too
+const CLUSTER_SCOPED_KINDS: [&str; 3] = ["Node", "PersistentVolume", "StorageClass"];
+
+/**
+* kinds whose rooms can be made from a reference alone - secrets are never listed, and nodes,
+* volumes and storage classes may not be
+*/
+const KINDS_MADE_FROM_REFERENCES: [&str; 4] =
+    ["Secret", "Node", "PersistentVolume", "StorageClass"];
+
+/// namespaced rooms are prefixed with their namespace, cluster scoped ones with `cluster`
+pub fn resource_room_id(namespace: &str, kind: &str, name: &str) -> String {
+    if CLUSTER_SCOPED_KINDS.contains(&kind) {
+        format!("cluster-{kind}-{name}")
+    } else {
+        format!("{namespace}-{kind}-{name}")
+    }
+}
+
+/**
+* the room for an object that something names, made on the spot for kinds that may not be listed.
+* secrets are among them so that their data is never read, only the names pods use
+*/
+fn referenced_room(
+    plan: &mut FloorPlan,
+    namespace: &str,
+    reference: &IntegrationResource,
+) -> Option<Room> {
+    let id = resource_room_id(namespace, &reference.kind, &reference.name);
+    if let Ok(room) = plan.get_room_by_id(&id) {
+        return Some(room.clone());
+    }
+    if !KINDS_MADE_FROM_REFERENCES.contains(&reference.kind.as_str()) {
+        debug!("Referenced room not found: {id}");
+        return None;
+    }
+    let room = Room {
+        id,
+        name: format!("{} {}", reference.kind, reference.name),
+        kind: RoomKind::from_k8s_kind(&reference.kind),
+        namespace: (!CLUSTER_SCOPED_KINDS.contains(&reference.kind.as_str()))
+            .then(|| namespace.to_string()),
+        ..Default::default()
+    };
+    plan.add_room(room.clone());
+    Some(room)
+}
+
+fn connect_room_references(
     plan: &mut FloorPlan,
-    json_value: &serde_json::Value,
     namespace: &str,
-    outer_room: &Room,
+    room_id: &str,
+    references: &[IntegrationResource],
     door_id_generator: &mut usize,
-    kind: &str,
 ) -> FloorPlanResult<()> {
-    if let Ok(resources) = get_names(json_value, kind, namespace) {
+    let Ok(room) = plan.get_room_by_id(room_id).cloned() else {
+        return Ok(());
+    };
+    for reference in references {
+        if let Some(referenced) = referenced_room(plan, namespace, reference) {
+            connect_rooms_with_doors(plan, &room, &referenced, door_id_generator)?;
+        }
+    }
+    Ok(())
+}
+
+/**
+* doors from rooms to the objects they name - each volume mount to the configmaps, secrets or claim
+* its volume is filled from, and each claim to its volume and storage class
+*/
+pub fn connect_references(
+    plan: &mut FloorPlan,
+    namespace: &str,
+    resources: &[IntegrationResource],
+    door_id_generator: &mut usize,
+) -> FloorPlanResult<()> {
+    for r in resources {
+        let room_id = resource_room_id(namespace, &r.kind, &r.name);
+        connect_room_references(plan, namespace, &room_id, &r.references, door_id_generator)?;
+        for container in &r.children {
+            for volume_mount in &container.children {
+                connect_room_references(
+                    plan,
+                    namespace,
+                    &volume_mount_room_id(namespace, r, container, volume_mount),
+                    &volume_mount.references,
+                    door_id_generator,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// events keyed by the room of the object they are about, as `resource_room_id` names it
+pub fn to_room_events(events: Vec<ResourceEvent>) -> RoomEvents {
+    RoomEvents::new(events.into_iter().map(|event| {
+        let namespace = event.namespace.clone().unwrap_or_default();
+        (
+            resource_room_id(&namespace, &event.kind, &event.name),
+            event,
+        )
+    }))
+}
+
+/**
+* the room of an owner, made on the spot when its kind has no hallway so that pods of a custom
+* controller still lead somewhere - it is only reachable through what it owns
+*/
+fn owner_room(plan: &mut FloorPlan, namespace: &str, owner: &Owner) -> Room {
+    let id = resource_room_id(namespace, &owner.kind, &owner.name);
+    if let Ok(room) = plan.get_room_by_id(&id) {
+        return room.clone();
+    }
+    debug!("Owner room not listed, making it: {id}");
+    let room = Room {
+        id,
+        name: format!("{} {}", owner.kind, owner.name),
+        kind: RoomKind::from_k8s_kind(&owner.kind),
+        namespace: Some(namespace.to_string()),
+        ..Default::default()
+    };
+    plan.add_room(room.clone());
+    room
+}
+
+/**
+* doors from each resource to every one of its owners, ie: pod to job to cronjob. run once every
+* hallway is built so the owner's room exists whatever order the kinds were added in
+*/
+pub fn connect_owners(
+    plan: &mut FloorPlan,
+    namespace: &str,
+    resources: &[IntegrationResource],
+    door_id_generator: &mut usize,
+) -> FloorPlanResult<()> {
+    for r in resources {
+        let Ok(room) = plan
+            .get_room_by_id(&resource_room_id(namespace, &r.kind, &r.name))
+            .cloned()
+        else {
+            continue;
+        };
+        for owner in &r.owners {
+            let owner_room = owner_room(plan, namespace, owner);
+            let controller = owner.controller;
+            connect_rooms_with_door_kinds(
+                plan,
+                &room,
+                &owner_room,
+                (
+                    DoorKind::ToOwner { controller },
+                    DoorKind::ToOwned { controller },
+                ),
+                door_id_generator,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+pub fn add_cluster_hallway(
+    plan: &mut FloorPlan,
+    lobby: &Room,
+    kind: &str,
+    door_id_generator: &mut usize,
+) -> FloorPlanResult<Room> {
+    let hallway = Room {
+        id: format!("cluster-{kind}s"),
+        name: format!("{kind}s Hallway"),
+        kind: RoomKind::Hallway,
+        attributes: BTreeMap::from([("kind".to_string(), kind.to_string())]),
+        ..Default::default()
+    };
+    plan.add_room(hallway.clone());
+    connect_rooms_with_doors(plan, lobby, &hallway, door_id_generator)?;
+    Ok(hallway)
+}
+
+/**
+* lay out what lives outside any namespace and pick what opens off the lobby - namespaces with
+* nodes down a hallway, or nodes with namespaces down a hallway. nodes are the ones pods are
+* scheduled on, and persistent volumes and storage classes get a hallway each when there are any.
+* returns the room namespaces hang off
+*/
+pub fn add_cluster_scoped_rooms(
+    plan: &mut FloorPlan,
+    lobby: &Room,
+    hierarchy: Hierarchy,
+    nodes: &[String],
+    cluster_resources: &[IntegrationResource],
+    door_id_generator: &mut usize,
+) -> FloorPlanResult<Room> {
+    let (node_parent, namespace_parent) = match hierarchy {
+        Hierarchy::Namespace if nodes.is_empty() => (None, lobby.clone()),
+        Hierarchy::Namespace => (
+            Some(add_cluster_hallway(plan, lobby, "Node", door_id_generator)?),
+            lobby.clone(),
+        ),
+        Hierarchy::Node => (
+            Some(lobby.clone()),
+            add_cluster_hallway(plan, lobby, "Namespace", door_id_generator)?,
+        ),
+    };
+    if let Some(node_parent) = node_parent {
+        for node in nodes {
+            let node_room = Room {
+                id: resource_room_id("", "Node", node),
+                name: format!("Node {node}"),
+                kind: RoomKind::Node,
+                ..Default::default()
+            };
+            plan.add_room(node_room.clone());
+            connect_rooms_with_doors(plan, &node_room, &node_parent, door_id_generator)?;
+        }
+    }
+
+    for kind in ["StorageClass", "PersistentVolume"] {
+        let resources: Vec<&IntegrationResource> = cluster_resources
+            .iter()
+            .filter(|r| r.kind == kind)
+            .collect();
+        if resources.is_empty() {
+            continue;
+        }
+        let hallway = add_cluster_hallway(plan, lobby, kind, door_id_generator)?;
         for r in resources {
             let room = Room {
-                id: format!("{namespace}-{}-{}", r.kind, r.name),
+                id: resource_room_id("", &r.kind, &r.name),
                 name: format!("{} {}", r.kind, r.name),
+                kind: RoomKind::from_k8s_kind(&r.kind),
+                labels: r.labels.clone(),
+                attributes: r.attributes.clone(),
+                status: r.status.clone(),
+                source: r.source.clone(),
+                ..Default::default()
             };
             plan.add_room(room.clone());
-            connect_rooms_with_doors(plan, &room, outer_room, door_id_generator)?;
-
-            // if there is any parent, connect the room to the parent
-            if let Some(parent) = r.parent {
-                let parent_room_id = format!("{namespace}-{}-{}", parent.kind, parent.name);
-                let cplan = plan.clone(); //todo: is this really necessary?
-                let parent_room = cplan.get_room_by_id(&parent_room_id);
-                if let Ok(parent_room) = parent_room {
-                    connect_rooms_with_doors(plan, &room, parent_room, door_id_generator)?;
-                } else {
-                    warn!("Owner room not found: {parent_room_id}");
-                }
+            connect_rooms_with_doors(plan, &room, &hallway, door_id_generator)?;
+        }
+    }
+    connect_references(plan, "", cluster_resources, door_id_generator)?;
+    Ok(namespace_parent)
+}
+
+/**
+* doors from each service to the pods its selector picks, so a request can be followed to where it
+* lands. the service room's status counts its ready pods. services without a selector, ie:
+* `ExternalName`, are left as they are
+*/
+pub fn connect_services_to_pods(
+    plan: &mut FloorPlan,
+    namespace: &str,
+    services: &[IntegrationResource],
+    pods: &[IntegrationResource],
+    door_id_generator: &mut usize,
+) -> FloorPlanResult<()> {
+    for service in services.iter().filter(|s| !s.selector.is_empty()) {
+        let service_room_id = format!("{namespace}-{}-{}", service.kind, service.name);
+        let Ok(service_room) = plan.get_room_by_id(&service_room_id).cloned() else {
+            continue;
+        };
+        let selected: Vec<&IntegrationResource> = pods
+            .iter()
+            .filter(|pod| selects(&service.selector, &pod.labels))
+            .collect();
+        for pod in &selected {
+            let pod_room_id = format!("{namespace}-{}-{}", pod.kind, pod.name);
+            if let Ok(pod_room) = plan.get_room_by_id(&pod_room_id).cloned() {
+                connect_rooms_with_doors(plan, &service_room, &pod_room, door_id_generator)?;
             }
+        }
+        plan.get_room_by_id_mut(&service_room_id)?.status = Some(get_service_status(&selected));
+    }
+    Ok(())
+}
 
-            for container in r.children {
-                let container_room = Room {
-                    id: format!("{namespace}-{}-{}-{}", r.kind, "container", container.name),
-                    name: format!("{} {}", "container", container.name),
+fn create_ingress_path_room(namespace: &str, host_room: &Room, route: &IngressRoute) -> Room {
+    let host = if route.host == ANY_HOST {
+        ""
+    } else {
+        &route.host
+    };
+    Room {
+        id: format!("{}-path-{}", host_room.id, route.path),
+        name: format!("path {host}{}", route.path),
+        kind: RoomKind::IngressPath,
+        namespace: Some(namespace.to_string()),
+        attributes: attribute_map([
+            ("pathType", route.path_type.as_deref()),
+            ("service", route.service.as_deref()),
+            ("port", route.port.as_deref()),
+        ]),
+        ..Default::default()
+    }
+}
+
+/**
+* an ingress opens onto a room per host and each host onto a room per path, with a door from every
+* path to its backend service - together with the service's doors to its pods, a url can be walked
+* from the edge down to a container
+*/
+pub fn add_ingress_routes(
+    plan: &mut FloorPlan,
+    namespace: &str,
+    ingresses: &[IntegrationResource],
+    door_id_generator: &mut usize,
+) -> FloorPlanResult<()> {
+    for ingress in ingresses {
+        let ingress_room_id = format!("{namespace}-{}-{}", ingress.kind, ingress.name);
+        let Ok(ingress_room) = plan.get_room_by_id(&ingress_room_id).cloned() else {
+            continue;
+        };
+        for route in &ingress.routes {
+            let host_room_id = format!("{ingress_room_id}-host-{}", route.host);
+            let host_room = if let Ok(host_room) = plan.get_room_by_id(&host_room_id) {
+                host_room.clone()
+            } else {
+                let host_room = Room {
+                    id: host_room_id,
+                    name: format!("host {}", route.host),
+                    kind: RoomKind::IngressHost,
+                    namespace: Some(namespace.to_string()),
+                    attributes: attribute_map([("ingress", Some(ingress.name.as_str()))]),
+                    ..Default::default()
                 };
-                plan.add_room(container_room.clone());
-                connect_rooms_with_doors(plan, &container_room, &room, door_id_generator)?;
-                for volume_mount in container.children {
-                    let volume_mount_room = Room {
-                        id: format!(
-                            "{namespace}-{}-{}-{}-{}",
-                            r.kind, "container", container.name, volume_mount.name
-                        ),
-                        name: format!("{} {}", "volume mount", volume_mount.name),
-                    };
-                    plan.add_room(volume_mount_room.clone());
-                    connect_rooms_with_doors(
-                        plan,
-                        &volume_mount_room,
-                        &container_room,
-                        door_id_generator,
-                    )?;
-                }
+                plan.add_room(host_room.clone());
+                connect_rooms_with_doors(plan, &host_room, &ingress_room, door_id_generator)?;
+                host_room
+            };
+
+            let path_room = create_ingress_path_room(namespace, &host_room, route);
+            if plan.get_room_by_id(&path_room.id).is_ok() {
+                debug!("Duplicate ingress path: {}", path_room.id);
+                continue;
+            }
+            plan.add_room(path_room.clone());
+            connect_rooms_with_doors(plan, &path_room, &host_room, door_id_generator)?;
+
+            let service_room = route.service.as_ref().and_then(|service| {
+                plan.get_room_by_id(&format!("{namespace}-Service-{service}"))
+                    .ok()
+                    .cloned()
+            });
+            if let Some(service_room) = service_room {
+                connect_rooms_with_doors(plan, &path_room, &service_room, door_id_generator)?;
             }
         }
-        Ok(())
-    } else {
-        Err(crate::floorplan::FloorPlanError::RoomNotFound(
-            "no resources".to_string(),
-        ))
     }
+    Ok(())
 }
 
+/// the kinds given a hallway in every namespace
+const KINDS: [&str; 11] = [
+    "Deployment",
+    "StatefulSet",
+    "DaemonSet",
+    "CronJob",
+    "Job",
+    "ReplicaSet",
+    "Service",
+    "ConfigMap",
+    "Ingress",
+    "PersistentVolumeClaim",
+    "Pod",
+];
+
 fn setup_hallway_and_rooms(
     plan: &mut FloorPlan,
     json_value: &serde_json::Value, // might want to pass this in a pre-parsed format: TODO
+    suites: &Suites,
     namespace: &str,
     outer_room: &Room, // will often be the cluster lobby
     door_id_generator: &mut usize,
     kind: &str, // hallways collect similar resources
 ) -> FloorPlanResult<()> {
+    let resources = get_names(json_value, kind, namespace)
+        .map_err(|_| FloorPlanError::RoomNotFound("no resources".to_string()))?;
+    if !suites.needs_hallway(&resources) {
+        return add_resource_rooms(
+            plan,
+            resources,
+            suites,
+            namespace,
+            outer_room,
+            door_id_generator,
+        );
+    }
     let hallway = Room {
         id: format!("{namespace}-{kind}s"),
         name: format!("{namespace} {kind}s Hallway"),
+        kind: RoomKind::Hallway,
+        namespace: Some(namespace.to_string()),
+        attributes: BTreeMap::from([("kind".to_string(), kind.to_string())]),
+        ..Default::default()
     };
     plan.add_room(hallway.clone());
     connect_rooms_with_doors(plan, outer_room, &hallway, door_id_generator)?;
-    add_rooms(
+    add_resource_rooms(
         plan,
-        json_value,
+        resources,
+        suites,
         namespace,
         &hallway,
         door_id_generator,
-        kind,
-    )?;
-    Ok(())
+    )
+}
+
+#[cfg(test)]
+fn generate_k8s_floorplan_from_file(
+    patterns: &[String],
+    hierarchy: Hierarchy,
+    grouping: Grouping,
+) -> FloorPlanResult<FloorPlan> {
+    let json_value = load_manifests(patterns)?;
+    generate_k8s_floorplan_from_json(&json_value, hierarchy, grouping)
 }
 
-fn generate_k8s_floorplan_from_file() -> FloorPlanResult<FloorPlan> {
+fn generate_k8s_floorplan_from_json(
+    json_value: &serde_json::Value,
+    hierarchy: Hierarchy,
+    grouping: Grouping,
+) -> FloorPlanResult<FloorPlan> {
     let mut floorplan = FloorPlan::new();
-    if let Ok(yaml_content) = fs::read_to_string("assets/k8s.yaml") {
-        if let Ok(yaml_value) = serde_yaml::from_str::<Value>(&yaml_content) {
-            let json_value = json!(yaml_value);
+    let cluster_room = Room {
+        id: "cluster".to_string(),
+        name: "Cluster Lobby".to_string(),
+        kind: RoomKind::Cluster,
+        ..Default::default()
+    };
+    floorplan.add_room(cluster_room.clone());
+
+    let mut door_id = 0;
+    let mut cluster_resources = Vec::new();
+    for kind in ["StorageClass", "PersistentVolume"] {
+        cluster_resources.extend(get_cluster_scoped_names(json_value, kind).unwrap_or_default());
+    }
+    let namespace_parent = add_cluster_scoped_rooms(
+        &mut floorplan,
+        &cluster_room,
+        hierarchy,
+        &get_node_names(json_value).unwrap_or_default(),
+        &cluster_resources,
+        &mut door_id,
+    )?;
 
-            let cluster_room = Room {
-                id: "cluster".to_string(),
-                name: "Cluster Lobby".to_string(),
+    if let Ok(namespaces) = get_namespaces(json_value) {
+        for namespace in namespaces {
+            let namespace_room = Room {
+                id: namespace.clone(),
+                name: format!("{namespace} NS Hallway"),
+                kind: RoomKind::Namespace,
+                namespace: Some(namespace.clone()),
+                ..Default::default()
             };
-            floorplan.add_room(cluster_room.clone());
-
-            let mut door_id = 0;
-            if let Ok(namespaces) = get_namespaces(&json_value) {
-                for namespace in namespaces {
-                    let namespace_room = Room {
-                        id: namespace.clone(),
-                        name: format!("{namespace} NS Hallway"),
-                    };
-                    floorplan.add_room(namespace_room.clone());
-                    connect_rooms_with_doors(
-                        &mut floorplan,
-                        &cluster_room,
-                        &namespace_room,
-                        &mut door_id,
-                    )?;
-
-                    for kind in &[
-                        "Deployments",
-                        "DaemonSets",
-                        "ReplicaSets",
-                        "Services",
-                        "ConfigMaps",
-                        "Pod",
-                    ] {
-                        setup_hallway_and_rooms(
-                            &mut floorplan,
-                            &json_value,
-                            &namespace,
-                            &namespace_room,
-                            &mut door_id,
-                            kind,
-                        )?;
-                    }
-                }
+            floorplan.add_room(namespace_room.clone());
+            connect_rooms_with_doors(
+                &mut floorplan,
+                &namespace_parent,
+                &namespace_room,
+                &mut door_id,
+            )?;
+
+            let suites = Suites::new(
+                grouping,
+                &namespace,
+                &KINDS
+                    .iter()
+                    .flat_map(|kind| get_names(json_value, kind, &namespace).unwrap_or_default())
+                    .collect::<Vec<_>>(),
+            );
+            suites.add_rooms(&mut floorplan, &namespace_room, &mut door_id)?;
+            for kind in KINDS {
+                setup_hallway_and_rooms(
+                    &mut floorplan,
+                    json_value,
+                    &suites,
+                    &namespace,
+                    &namespace_room,
+                    &mut door_id,
+                    kind,
+                )?;
+            }
+            for kind in KINDS {
+                connect_owners(
+                    &mut floorplan,
+                    &namespace,
+                    &get_names(json_value, kind, &namespace).unwrap_or_default(),
+                    &mut door_id,
+                )?;
+            }
+            connect_services_to_pods(
+                &mut floorplan,
+                &namespace,
+                &get_names(json_value, "Service", &namespace).unwrap_or_default(),
+                &get_names(json_value, "Pod", &namespace).unwrap_or_default(),
+                &mut door_id,
+            )?;
+            add_ingress_routes(
+                &mut floorplan,
+                &namespace,
+                &get_names(json_value, "Ingress", &namespace).unwrap_or_default(),
+                &mut door_id,
+            )?;
+            for kind in ["Pod", "PersistentVolumeClaim"] {
+                connect_references(
+                    &mut floorplan,
+                    &namespace,
+                    &get_names(json_value, kind, &namespace).unwrap_or_default(),
+                    &mut door_id,
+                )?;
             }
-        } else {
-            panic!("Failed to parse k8s yaml content");
         }
-
-        Ok(floorplan)
-    } else {
-        error!("No k8s yaml file found");
-        Err(crate::floorplan::FloorPlanError::RoomNotFound(
-            "no file".to_string(),
-        ))
     }
+
+    Ok(floorplan)
 }
 
-pub fn fire_k8s_file_floorplan_event(mut events: EventWriter<FloorPlanEvent>) {
-    if let Ok(floorplan) = generate_k8s_floorplan_from_file() {
-        events.send(FloorPlanEvent { floorplan });
-    } else {
-        warn!("No K8S FloorPlanEvent");
+pub fn fire_k8s_file_floorplan_event(
+    grouping: Res<RoomGrouping>,
+    mut events: EventWriter<FloorPlanEvent>,
+    mut status: ResMut<ConnectionStatus>,
+    mut room_events: ResMut<RoomEvents>,
+) {
+    let cli = Cli::parse();
+    let mut patterns = cli.k8s_files;
+    if patterns.is_empty() {
+        patterns.push(DEFAULT_K8S_FILE.to_string());
+    }
+    let generated = load_manifests(&patterns).and_then(|json_value| {
+        let floorplan = generate_k8s_floorplan_from_json(
+            &json_value,
+            cli.hierarchy.unwrap_or_default(),
+            grouping.0,
+        )?;
+        Ok((floorplan, get_events(&json_value).unwrap_or_default()))
+    });
+    match generated {
+        Ok((floorplan, resource_events)) => {
+            events.send(FloorPlanEvent { floorplan });
+            *room_events = to_room_events(resource_events);
+        }
+        Err(e) => {
+            error!("No K8S FloorPlanEvent: {e}");
+            *status = ConnectionStatus::failed(e, Retry::Never);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::generate_k8s_floorplan_from_file;
+    use super::{
+        generate_k8s_floorplan_from_file, generate_k8s_floorplan_from_json, get_events,
+        to_room_events,
+    };
+    use crate::cli::{Grouping, Hierarchy};
+    use crate::floorplan::{
+        DoorKind, FloorPlan, FloorPlanError, FloorPlanResult, Health, RoomKind,
+    };
+    use crate::integration::k8s_file::k8s_manifest::{DEFAULT_K8S_FILE, parse_manifest};
+    use serde_json::json;
+
+    fn generate_default_floorplan() -> FloorPlanResult<FloorPlan> {
+        generate_k8s_floorplan_from_file(
+            &[DEFAULT_K8S_FILE.to_string()],
+            Hierarchy::default(),
+            Grouping::Kind,
+        )
+    }
 
     #[test]
     fn test_floorplan_equivalence() {
-        if let Ok(plan1) = generate_k8s_floorplan_from_file() {
+        if let Ok(plan1) = generate_default_floorplan() {
             let plan2 = plan1.clone();
             assert!(plan1 == plan2, "Floorplans are not equivalent");
         } else {
             panic!("Failed to generate floorplan");
         }
     }
+
+    #[test]
+    fn test_missing_file_is_a_manifest_error() {
+        assert!(matches!(
+            generate_k8s_floorplan_from_file(
+                &["assets/no-such-manifest.yaml".to_string()],
+                Hierarchy::default(),
+                Grouping::Kind,
+            ),
+            Err(FloorPlanError::ManifestError(_))
+        ));
+    }
+
+    #[test]
+    fn test_rendered_chart_without_namespaces() {
+        let rendered = "
+kind: Deployment
+metadata:
+  name: web
+---
+kind: Service
+metadata:
+  name: web
+";
+        let objects = parse_manifest(rendered, false).unwrap();
+        let plan = generate_k8s_floorplan_from_json(
+            &json!({ "items": objects }),
+            Hierarchy::default(),
+            Grouping::Kind,
+        )
+        .unwrap();
+        assert!(plan.get_room_by_id("default-Deployment-web").is_ok());
+        assert!(plan.get_room_by_id("default-Service-web").is_ok());
+    }
+
+    #[test]
+    fn test_rooms_are_typed() {
+        let plan = generate_default_floorplan().unwrap();
+        assert_eq!(plan.get_start_room().unwrap().kind, RoomKind::Cluster);
+
+        let pod = plan
+            .graph
+            .node_weights()
+            .find(|room| room.kind == RoomKind::Pod)
+            .expect("no pod rooms");
+        assert_eq!(pod.namespace.as_deref(), Some("kube-system"));
+        assert!(!pod.labels.is_empty());
+        assert!(pod.status.as_ref().is_some_and(|status| status.total > 0));
+
+        let hallway = plan.get_room_by_id("kube-system-Deployments").unwrap();
+        assert_eq!(hallway.kind, RoomKind::Hallway);
+        assert!(
+            plan.graph
+                .node_weights()
+                .any(|room| room.kind == RoomKind::Deployment)
+        );
+    }
+
+    #[test]
+    fn test_services_lead_to_selected_pods() {
+        let manifest = "
+kind: Service
+metadata:
+  name: web
+spec:
+  selector:
+    app: web
+---
+kind: Service
+metadata:
+  name: orphan
+spec:
+  selector:
+    app: gone
+---
+kind: Service
+metadata:
+  name: external
+spec:
+  type: ExternalName
+---
+kind: Pod
+metadata:
+  name: web-1
+  labels:
+    app: web
+---
+kind: Pod
+metadata:
+  name: db-1
+  labels:
+    app: db
+";
+        let objects = parse_manifest(manifest, false).unwrap();
+        let plan = generate_k8s_floorplan_from_json(
+            &json!({ "items": objects }),
+            Hierarchy::default(),
+            Grouping::Kind,
+        )
+        .unwrap();
+
+        let path = plan
+            .shortest_path("default-Service-web", "default-Pod-web-1")
+            .unwrap();
+        assert_eq!(path.len(), 1, "a service is one door away from its pods");
+        assert!(
+            plan.shortest_path("default-Service-web", "default-Pod-db-1")
+                .unwrap()
+                .len()
+                > 1
+        );
+
+        let web = plan.get_room_by_id("default-Service-web").unwrap();
+        assert_eq!(web.status.as_ref().map(|status| status.total), Some(1));
+        let orphan = plan.get_room_by_id("default-Service-orphan").unwrap();
+        assert_eq!(orphan.health(), Health::Failing);
+        let external = plan.get_room_by_id("default-Service-external").unwrap();
+        assert_eq!(external.status, None);
+    }
+
+    #[test]
+    fn test_ingress_paths_lead_to_services() {
+        let manifest = "
+kind: Ingress
+metadata:
+  name: shop
+spec:
+  rules:
+    - host: shop.example.com
+      http:
+        paths:
+          - path: /api
+            pathType: Prefix
+            backend:
+              service:
+                name: api
+                port:
+                  number: 8080
+          - path: /web
+            pathType: Prefix
+            backend:
+              service:
+                name: web
+                port:
+                  number: 80
+---
+kind: Service
+metadata:
+  name: api
+spec:
+  selector:
+    app: api
+---
+kind: Pod
+metadata:
+  name: api-1
+  labels:
+    app: api
+spec:
+  containers:
+    - name: server
+";
+        let objects = parse_manifest(manifest, false).unwrap();
+        let plan = generate_k8s_floorplan_from_json(
+            &json!({ "items": objects }),
+            Hierarchy::default(),
+            Grouping::Kind,
+        )
+        .unwrap();
+
+        let host = "default-Ingress-shop-host-shop.example.com";
+        assert_eq!(
+            plan.get_room_by_id(host).unwrap().kind,
+            RoomKind::IngressHost
+        );
+        let api_path = plan.get_room_by_id(&format!("{host}-path-/api")).unwrap();
+        assert_eq!(api_path.name, "path shop.example.com/api");
+        assert_eq!(
+            api_path.attributes.get("service").map(String::as_str),
+            Some("api")
+        );
+
+        // the edge down to the container: ingress, host, path, service, pod, container
+        let route = plan
+            .shortest_path("default-Ingress-shop", "default-Pod-api-1-container-server")
+            .unwrap();
+        assert_eq!(route.len(), 5);
+
+        // a path whose service is missing is still a room, just a dead end
+        assert!(plan.get_room_by_id(&format!("{host}-path-/web")).is_ok());
+    }
+
+    #[test]
+    fn test_volume_mounts_lead_to_what_fills_them() {
+        let manifest = "
+kind: Pod
+metadata:
+  name: db-0
+spec:
+  containers:
+    - name: postgres
+      volumeMounts:
+        - name: settings
+          mountPath: /etc/postgres
+        - name: credentials
+          mountPath: /run/secrets
+        - name: data
+          mountPath: /var/lib/postgresql
+  volumes:
+    - name: settings
+      configMap:
+        name: db-settings
+    - name: credentials
+      secret:
+        secretName: db-password
+    - name: data
+      persistentVolumeClaim:
+        claimName: data-db-0
+---
+kind: ConfigMap
+metadata:
+  name: db-settings
+data:
+  max_connections: '100'
+---
+kind: PersistentVolumeClaim
+metadata:
+  name: data-db-0
+spec:
+  volumeName: pvc-1234
+  storageClassName: fast-ssd
+";
+        let objects = parse_manifest(manifest, false).unwrap();
+        let plan = generate_k8s_floorplan_from_json(
+            &json!({ "items": objects }),
+            Hierarchy::default(),
+            Grouping::Kind,
+        )
+        .unwrap();
+
+        let mount = |name: &str| format!("default-Pod-db-0-container-postgres-{name}");
+        let doors_between = |from: &str, to: &str| plan.shortest_path(from, to).unwrap().len();
+        assert_eq!(
+            doors_between(&mount("settings"), "default-ConfigMap-db-settings"),
+            1
+        );
+        assert_eq!(
+            doors_between(&mount("data"), "default-PersistentVolumeClaim-data-db-0"),
+            1
+        );
+        assert_eq!(
+            doors_between(&mount("credentials"), "default-Secret-db-password"),
+            1
+        );
+        assert_eq!(
+            doors_between(
+                "default-PersistentVolumeClaim-data-db-0",
+                "cluster-PersistentVolume-pvc-1234"
+            ),
+            1
+        );
+        assert_eq!(
+            doors_between(
+                "default-PersistentVolumeClaim-data-db-0",
+                "cluster-StorageClass-fast-ssd"
+            ),
+            1
+        );
+
+        let secret = plan.get_room_by_id("default-Secret-db-password").unwrap();
+        assert_eq!(secret.kind, RoomKind::Secret);
+        assert!(
+            secret.attributes.is_empty(),
+            "only the name of a secret is known"
+        );
+        let settings = plan.get_room_by_id(&mount("settings")).unwrap();
+        assert_eq!(
+            settings.attributes.get("volume").map(String::as_str),
+            Some("configMap")
+        );
+    }
+
+    #[test]
+    fn test_node_and_namespace_hierarchies() {
+        let manifest = "
+kind: Pod
+metadata:
+  name: web-1
+spec:
+  nodeName: worker-a
+---
+kind: Pod
+metadata:
+  name: web-2
+spec:
+  nodeName: worker-b
+---
+kind: Pod
+metadata:
+  name: web-3
+---
+kind: PersistentVolume
+metadata:
+  name: pv-1
+spec:
+  storageClassName: fast-ssd
+---
+kind: StorageClass
+metadata:
+  name: fast-ssd
+";
+        let items = json!({ "items": parse_manifest(manifest, false).unwrap() });
+
+        let plan =
+            generate_k8s_floorplan_from_json(&items, Hierarchy::Namespace, Grouping::Kind).unwrap();
+        let doors_between =
+            |plan: &FloorPlan, from: &str, to: &str| plan.shortest_path(from, to).unwrap().len();
+        assert_eq!(doors_between(&plan, "cluster", "default"), 1);
+        assert_eq!(doors_between(&plan, "cluster", "cluster-Node-worker-a"), 2);
+        assert_eq!(
+            doors_between(&plan, "cluster-Node-worker-a", "default-Pod-web-1"),
+            1
+        );
+        assert!(plan.get_room_by_id("cluster-Node-").is_err());
+        assert_eq!(
+            plan.get_room_by_id("cluster-PersistentVolumes")
+                .unwrap()
+                .kind,
+            RoomKind::Hallway
+        );
+        assert_eq!(
+            doors_between(
+                &plan,
+                "cluster-PersistentVolume-pv-1",
+                "cluster-StorageClass-fast-ssd"
+            ),
+            1
+        );
+        let pv = plan
+            .get_room_by_id("cluster-PersistentVolume-pv-1")
+            .unwrap();
+        assert_eq!(pv.namespace, None);
+
+        let plan =
+            generate_k8s_floorplan_from_json(&items, Hierarchy::Node, Grouping::Kind).unwrap();
+        assert_eq!(doors_between(&plan, "cluster", "cluster-Node-worker-b"), 1);
+        assert_eq!(doors_between(&plan, "cluster", "default"), 2);
+        assert_eq!(
+            plan.get_room_by_id("cluster-Node-worker-b").unwrap().kind,
+            RoomKind::Node
+        );
+    }
+
+    #[test]
+    fn test_owner_chains() {
+        let manifest = "
+kind: CronJob
+metadata:
+  name: backup
+---
+kind: Job
+metadata:
+  name: backup-28001
+  ownerReferences:
+  - kind: CronJob
+    name: backup
+    controller: true
+---
+kind: Pod
+metadata:
+  name: backup-28001-x7k
+  ownerReferences:
+  - kind: Job
+    name: backup-28001
+    controller: true
+  - kind: Rollout
+    name: canary
+";
+        let items = json!({ "items": parse_manifest(manifest, false).unwrap() });
+        let plan =
+            generate_k8s_floorplan_from_json(&items, Hierarchy::default(), Grouping::Kind).unwrap();
+
+        let pod = "default-Pod-backup-28001-x7k";
+        assert_eq!(
+            plan.shortest_path(pod, "default-CronJob-backup")
+                .unwrap()
+                .len(),
+            2
+        );
+        let door_kind = |from: &str, to: &str| {
+            plan.get_doors_and_connected_rooms(from)
+                .unwrap()
+                .into_iter()
+                .find(|(_, room)| room.id == to)
+                .map(|(door, _)| door.kind)
+        };
+        assert_eq!(
+            door_kind(pod, "default-Job-backup-28001"),
+            Some(DoorKind::ToOwner { controller: true })
+        );
+        assert_eq!(
+            door_kind("default-Job-backup-28001", pod),
+            Some(DoorKind::ToOwned { controller: true })
+        );
+        // an owner without a hallway still gets a room, behind a door that is not the controller's
+        assert_eq!(
+            door_kind(pod, "default-Rollout-canary"),
+            Some(DoorKind::ToOwner { controller: false })
+        );
+        assert_eq!(door_kind("default-Pods", pod), Some(DoorKind::Passage));
+    }
+
+    #[test]
+    fn test_part_of_suites() {
+        let plan = generate_k8s_floorplan_from_file(
+            &[DEFAULT_K8S_FILE.to_string()],
+            Hierarchy::default(),
+            Grouping::PartOf,
+        )
+        .unwrap();
+        let suite = plan.get_room_by_id("kube-system-suite-cilium").unwrap();
+        assert_eq!(suite.kind, RoomKind::Suite);
+        assert_eq!(
+            plan.shortest_path("kube-system", "kube-system-suite-cilium")
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_helm_release_suites() {
+        let manifest = "
+kind: Deployment
+metadata:
+  name: web
+  annotations:
+    meta.helm.sh/release-name: shop
+---
+kind: ReplicaSet
+metadata:
+  name: web-5d4f
+  ownerReferences:
+  - kind: Deployment
+    name: web
+    controller: true
+---
+kind: Pod
+metadata:
+  name: web-5d4f-abc
+  ownerReferences:
+  - kind: ReplicaSet
+    name: web-5d4f
+    controller: true
+---
+kind: Pod
+metadata:
+  name: debug
+";
+        let items = json!({ "items": parse_manifest(manifest, false).unwrap() });
+        let plan =
+            generate_k8s_floorplan_from_json(&items, Hierarchy::default(), Grouping::HelmRelease)
+                .unwrap();
+
+        let suite = "default-suite-shop";
+        assert_eq!(
+            plan.get_room_by_id(suite).unwrap().name,
+            "default shop Suite"
+        );
+        let behind_suite = |room: &str| {
+            plan.get_doors_and_connected_rooms(room)
+                .unwrap()
+                .into_iter()
+                .any(|(_, outer)| outer.id == suite)
+        };
+        // the pod joins the release through its replicaset and deployment
+        assert!(behind_suite("default-Deployment-web"));
+        assert!(behind_suite("default-ReplicaSet-web-5d4f"));
+        assert!(behind_suite("default-Pod-web-5d4f-abc"));
+        assert!(!behind_suite("default-Pod-debug"));
+        // kinds entirely in suites need no hallway, the others keep theirs for the rest
+        assert!(plan.get_room_by_id("default-Deployments").is_err());
+        assert!(plan.get_room_by_id("default-Pods").is_ok());
+    }
+
+    #[test]
+    fn test_events_find_their_rooms() {
+        let manifest = "
+kind: Pod
+metadata:
+  name: web-1
+---
+kind: Event
+metadata:
+  name: web-1.17c
+  namespace: default
+involvedObject:
+  kind: Pod
+  name: web-1
+  namespace: default
+type: Warning
+reason: BackOff
+message: Back-off restarting failed container
+---
+kind: Event
+metadata:
+  name: worker-a.17d
+  namespace: default
+involvedObject:
+  kind: Node
+  name: worker-a
+reason: NodeReady
+";
+        let items = json!({ "items": parse_manifest(manifest, false).unwrap() });
+        let room_events = to_room_events(get_events(&items).unwrap());
+        let feed = room_events.feed("default-Pod-web-1", 0).unwrap();
+        assert!(feed.contains("Warning BackOff: Back-off restarting failed container"));
+        assert!(room_events.feed("cluster-Node-worker-a", 0).is_some());
+    }
 }
@@ -1,8 +1,16 @@
-use std::{collections::HashSet, error::Error};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    error::Error,
+};
 
 use jsonpath_lib::select;
 
-use crate::integration::integration_utils::IntegrationResource;
+use crate::integration::integration_component::ResourceEvent;
+use crate::integration::integration_utils::{
+    HELM_RELEASE_ANNOTATION, IntegrationResource, Owner, attribute_map, get_container_status,
+    get_ingress_routes, get_references, get_resource_event, get_selector, get_status,
+    get_volume_references, string_map, trimmed_source,
+};
 
 pub fn get_namespaces(json_value: &serde_json::Value) -> Result<Vec<String>, Box<dyn Error>> {
     let namespaces: HashSet<String> = select(json_value, "$..metadata.namespace")?
@@ -13,32 +21,49 @@ pub fn get_namespaces(json_value: &serde_json::Value) -> Result<Vec<String>, Box
     Ok(namespaces.into_iter().collect())
 }
 
-fn get_owner_reference(v: &serde_json::Value) -> Option<(String, String)> {
+/// every owner reference, a pod of a job that a cronjob created has the job as its one owner
+fn get_owners(v: &serde_json::Value) -> Vec<Owner> {
     v["metadata"]["ownerReferences"]
         .as_array()
-        .and_then(|refs| refs.first())
-        .and_then(|owner_ref| {
-            let owner_kind = owner_ref["kind"].as_str().map(String::from);
-            let owner_name = owner_ref["name"].as_str().map(String::from);
-            match (owner_kind, owner_name) {
-                (Some(kind), Some(name)) => Some((kind, name)),
-                _ => None,
-            }
+        .map(|refs| {
+            refs.iter()
+                .filter_map(|owner_ref| {
+                    let kind = owner_ref["kind"].as_str()?;
+                    let name = owner_ref["name"].as_str()?;
+                    Some(Owner {
+                        kind: kind.to_string(),
+                        name: name.to_string(),
+                        controller: owner_ref["controller"].as_bool().unwrap_or(false),
+                    })
+                })
+                .collect()
         })
+        .unwrap_or_default()
 }
 
-fn get_volume_mounts(container: &serde_json::Value) -> Vec<IntegrationResource> {
+fn get_volume_mounts(
+    pod: &serde_json::Value,
+    container: &serde_json::Value,
+) -> Vec<IntegrationResource> {
     container["volumeMounts"]
         .as_array()
         .map(|volume_mounts| {
             volume_mounts
                 .iter()
                 .filter_map(|volume_mount| {
-                    volume_mount["name"].as_str().map(|n| IntegrationResource {
-                        name: n.to_string(),
-                        kind: "VolumeMount".to_string(),
-                        parent: None,
-                        children: Vec::new(),
+                    volume_mount["name"].as_str().map(|n| {
+                        let (mut attributes, references) = get_volume_references(pod, n);
+                        attributes.extend(attribute_map([(
+                            "mountPath",
+                            volume_mount["mountPath"].as_str(),
+                        )]));
+                        IntegrationResource::new(
+                            n.to_string(),
+                            "VolumeMount".to_string(),
+                            Vec::new(),
+                        )
+                        .with_attributes(attributes)
+                        .with_references(references)
                     })
                 })
                 .collect()
@@ -54,12 +79,15 @@ fn get_containers(v: &serde_json::Value) -> Vec<IntegrationResource> {
                 .iter()
                 .filter_map(|container| {
                     let container_name = container["name"].as_str().map(String::from);
-                    let volume_mounts = get_volume_mounts(container);
-                    container_name.map(|n| IntegrationResource {
-                        name: n,
-                        kind: "Container".to_string(),
-                        parent: None,
-                        children: volume_mounts,
+                    let volume_mounts = get_volume_mounts(v, container);
+                    container_name.map(|n| {
+                        let status = get_container_status(v, &n);
+                        IntegrationResource::new(n, "Container".to_string(), volume_mounts)
+                            .with_attributes(attribute_map([(
+                                "image",
+                                container["image"].as_str(),
+                            )]))
+                            .with_status(status)
                     })
                 })
                 .collect()
@@ -67,28 +95,79 @@ fn get_containers(v: &serde_json::Value) -> Vec<IntegrationResource> {
         .unwrap_or_default()
 }
 
-pub fn get_names(
+fn get_attributes(v: &serde_json::Value) -> BTreeMap<String, String> {
+    attribute_map([
+        ("created", v["metadata"]["creationTimestamp"].as_str()),
+        ("node", v["spec"]["nodeName"].as_str()),
+        (
+            "release",
+            v["metadata"]["annotations"][HELM_RELEASE_ANNOTATION].as_str(),
+        ),
+    ])
+}
+
+fn extract_integration_resources(
     json_value: &serde_json::Value,
+    query: &str,
     kind: &str,
-    namespace: &str,
 ) -> Result<Vec<IntegrationResource>, Box<dyn Error>> {
-    let query = format!("$..[?(@.kind == '{kind}' && @.metadata.namespace == '{namespace}')]");
-
-    let resource: Vec<IntegrationResource> = select(json_value, &query)?
+    let resource: Vec<IntegrationResource> = select(json_value, query)?
         .iter()
         .filter_map(|v| {
             let name = v["metadata"]["name"].as_str().map(String::from);
-            let owner_reference = get_owner_reference(v);
             let containers = get_containers(v);
-            let owner = owner_reference
-                .map(|(kind, name)| IntegrationResource::new(name, kind, None, Vec::new()));
-            name.map(|n| IntegrationResource::new(n, kind.to_string(), owner, containers))
+            name.map(|n| {
+                IntegrationResource::new(n, kind.to_string(), containers)
+                    .with_owners(get_owners(v))
+                    .with_labels(string_map(&v["metadata"]["labels"]))
+                    .with_attributes(get_attributes(v))
+                    .with_status(get_status(kind, v))
+                    .with_selector(get_selector(kind, v))
+                    .with_routes(get_ingress_routes(kind, v))
+                    .with_references(get_references(kind, v))
+                    .with_source(trimmed_source(v))
+            })
         })
         .collect();
 
     Ok(resource)
 }
 
+pub fn get_names(
+    json_value: &serde_json::Value,
+    kind: &str,
+    namespace: &str,
+) -> Result<Vec<IntegrationResource>, Box<dyn Error>> {
+    let query = format!("$..[?(@.kind == '{kind}' && @.metadata.namespace == '{namespace}')]");
+    extract_integration_resources(json_value, &query, kind)
+}
+
+/// as `get_names` for kinds that live outside any namespace, ie: `PersistentVolume`
+pub fn get_cluster_scoped_names(
+    json_value: &serde_json::Value,
+    kind: &str,
+) -> Result<Vec<IntegrationResource>, Box<dyn Error>> {
+    let query = format!("$..[?(@.kind == '{kind}')]");
+    extract_integration_resources(json_value, &query, kind)
+}
+
+/// the nodes pods are scheduled on, sorted - pods that are not scheduled yet have none
+pub fn get_node_names(json_value: &serde_json::Value) -> Result<Vec<String>, Box<dyn Error>> {
+    let nodes: BTreeSet<String> = select(json_value, "$..[?(@.kind == 'Pod')]")?
+        .iter()
+        .filter_map(|pod| pod["spec"]["nodeName"].as_str().map(String::from))
+        .collect();
+    Ok(nodes.into_iter().collect())
+}
+
+/// every `Event` in the manifests, whatever namespace the object it is about lives in
+pub fn get_events(json_value: &serde_json::Value) -> Result<Vec<ResourceEvent>, Box<dyn Error>> {
+    Ok(select(json_value, "$..[?(@.kind == 'Event')]")?
+        .into_iter()
+        .filter_map(get_resource_event)
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,10 +182,18 @@ mod tests {
                     "metadata": {
                         "name": "pod1",
                         "namespace": "default",
+                        "labels": {
+                            "app": "web"
+                        },
                         "ownerReferences": [
+                            {
+                                "kind": "ConfigMap",
+                                "name": "cleanup-with"
+                            },
                             {
                                 "kind": "ReplicaSet",
-                                "name": "rs1"
+                                "name": "rs1",
+                                "controller": true
                             }
                         ]
                     },
@@ -144,23 +231,23 @@ mod tests {
         let pod1 = &result[0];
         assert_eq!(pod1.name, "pod1");
         assert_eq!(pod1.kind, "Pod");
-        assert!(pod1.parent.is_some());
-        let parent = pod1.parent.as_ref().unwrap();
-        assert_eq!(parent.name, "rs1");
-        assert_eq!(parent.kind, "ReplicaSet");
+        assert_eq!(pod1.owners.len(), 2);
+        assert_eq!(pod1.labels.get("app").map(String::as_str), Some("web"));
+        assert!(!pod1.owners[0].controller);
+        let controller = pod1.owners.iter().find(|owner| owner.controller).unwrap();
+        assert_eq!(controller.name, "rs1");
+        assert_eq!(controller.kind, "ReplicaSet");
         assert_eq!(
             pod1.children,
             vec![
                 IntegrationResource::new(
                     "container1".to_string(),
                     "Container".to_string(),
-                    None,
                     Vec::new()
                 ),
                 IntegrationResource::new(
                     "container2".to_string(),
                     "Container".to_string(),
-                    None,
                     Vec::new()
                 ),
             ]
@@ -169,13 +256,12 @@ mod tests {
         let pod2 = &result[1];
         assert_eq!(pod2.name, "pod2");
         assert_eq!(pod2.kind, "Pod");
-        assert!(pod2.parent.is_none());
+        assert!(pod2.owners.is_empty());
         assert_eq!(
             pod2.children,
             vec![IntegrationResource::new(
                 "container3".to_string(),
                 "Container".to_string(),
-                None,
                 Vec::new()
             ),]
         );
@@ -201,7 +287,7 @@ mod tests {
         let service = &result[0];
         assert_eq!(service.name, "service1");
         assert_eq!(service.kind, "Service");
-        assert!(service.parent.is_none());
+        assert!(service.owners.is_empty());
         assert!(service.children.is_empty());
     }
 }
@@ -0,0 +1,240 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::floorplan::{FloorPlanError, FloorPlanResult};
+
+/// used when no `--k8s-file` is given
+pub const DEFAULT_K8S_FILE: &str = "assets/k8s.yaml";
+
+const MANIFEST_EXTENSIONS: [&str; 3] = ["yaml", "yml", "json"];
+
+/// kinds that never live in a namespace, so they are not defaulted into one
+const CLUSTER_SCOPED_KINDS: [&str; 8] = [
+    "Namespace",
+    "Node",
+    "PersistentVolume",
+    "StorageClass",
+    "ClusterRole",
+    "ClusterRoleBinding",
+    "CustomResourceDefinition",
+    "PriorityClass",
+];
+
+fn manifest_error(path: &Path, e: impl std::fmt::Display) -> FloorPlanError {
+    FloorPlanError::ManifestError(format!("{}: {e}", path.display()))
+}
+
+fn is_manifest(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| MANIFEST_EXTENSIONS.contains(&extension))
+}
+
+/// every manifest below a directory, in a stable order
+fn manifests_in_dir(dir: &Path) -> FloorPlanResult<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| manifest_error(dir, e))? {
+        let path = entry.map_err(|e| manifest_error(dir, e))?.path();
+        if path.is_dir() {
+            paths.extend(manifests_in_dir(&path)?);
+        } else if is_manifest(&path) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/**
+* turn `--k8s-file` arguments into files - each one may be a file, a directory of manifests or a
+* glob, and one that matches nothing is an error rather than an empty cluster. a file that more
+* than one argument matches is only loaded once
+*/
+pub fn expand_paths(patterns: &[String]) -> FloorPlanResult<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for pattern in patterns {
+        let matches: Vec<PathBuf> = glob::glob(pattern)
+            .map_err(|e| FloorPlanError::ManifestError(format!("{pattern}: {e}")))?
+            .filter_map(Result::ok)
+            .collect();
+        if matches.is_empty() {
+            return Err(FloorPlanError::ManifestError(format!(
+                "{pattern}: no such file"
+            )));
+        }
+        for path in matches {
+            if path.is_dir() {
+                paths.extend(manifests_in_dir(&path)?);
+            } else {
+                paths.push(path);
+            }
+        }
+    }
+    let mut seen = HashSet::new();
+    paths.retain(|path| seen.insert(fs::canonicalize(path).unwrap_or_else(|_| path.clone())));
+    Ok(paths)
+}
+
+/**
+* helm and gitops manifests usually leave the namespace to `kubectl apply`, so give them the one
+* kubectl would
+*/
+fn default_namespace(mut object: Value) -> Value {
+    let namespaced = object["kind"]
+        .as_str()
+        .is_some_and(|kind| !CLUSTER_SCOPED_KINDS.contains(&kind));
+    if namespaced
+        && object["metadata"]["namespace"].is_null()
+        && let Some(metadata) = object["metadata"].as_object_mut()
+    {
+        metadata.insert("namespace".to_string(), json!("default"));
+    }
+    object
+}
+
+/// a `kind: List` (or any `*List`) contributes its items, anything else with a kind is an object
+fn collect_objects(document: Value, objects: &mut Vec<Value>) {
+    if let Some(items) = document["items"].as_array() {
+        for item in items {
+            collect_objects(item.clone(), objects);
+        }
+    } else if document["kind"].is_string() {
+        objects.push(default_namespace(document));
+    }
+}
+
+/**
+* parse one file's worth of manifests - yaml with any number of `---` documents, or json. json is
+* also yaml, but reading it as json gives better error messages
+*/
+pub fn parse_manifest(content: &str, is_json: bool) -> Result<Vec<Value>, String> {
+    let mut objects = Vec::new();
+    if is_json {
+        let document: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        collect_objects(document, &mut objects);
+    } else {
+        for document in serde_yaml::Deserializer::from_str(content) {
+            let document = serde_yaml::Value::deserialize(document).map_err(|e| e.to_string())?;
+            collect_objects(json!(document), &mut objects);
+        }
+    }
+    Ok(objects)
+}
+
+/**
+* read every manifest the patterns name into a single list, shaped like `kubectl get -o json`
+* output so it can be queried the same way no matter where it came from
+*/
+pub fn load_manifests(patterns: &[String]) -> FloorPlanResult<Value> {
+    let mut objects = Vec::new();
+    for path in expand_paths(patterns)? {
+        let content = fs::read_to_string(&path).map_err(|e| manifest_error(&path, e))?;
+        let is_json = path
+            .extension()
+            .is_some_and(|extension| extension == "json");
+        objects.extend(parse_manifest(&content, is_json).map_err(|e| manifest_error(&path, e))?);
+    }
+    Ok(json!({ "kind": "List", "items": objects }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("doors-k8s-manifest-{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_multi_document_yaml() {
+        let yaml = "
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: settings
+---
+# an empty document, as helm leaves behind for disabled templates
+---
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+  namespace: shop
+";
+        let objects = parse_manifest(yaml, false).unwrap();
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0]["metadata"]["namespace"], "default");
+        assert_eq!(objects[1]["metadata"]["namespace"], "shop");
+    }
+
+    #[test]
+    fn test_json_list() {
+        let json = r#"{
+            "apiVersion": "v1",
+            "kind": "List",
+            "items": [
+                { "kind": "Pod", "metadata": { "name": "a", "namespace": "x" } },
+                { "kind": "Namespace", "metadata": { "name": "x" } }
+            ]
+        }"#;
+        let objects = parse_manifest(json, true).unwrap();
+        assert_eq!(objects.len(), 2);
+        assert!(objects[1]["metadata"]["namespace"].is_null());
+
+        assert!(parse_manifest("{ not json", true).is_err());
+        assert!(parse_manifest("a: [unclosed", false).is_err());
+    }
+
+    #[test]
+    fn test_directories_and_globs() {
+        let dir = temp_dir("dirs");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("a.yaml"), "kind: Service\nmetadata:\n  name: a\n").unwrap();
+        fs::write(
+            dir.join("nested/b.json"),
+            r#"{ "kind": "Service", "metadata": { "name": "b" } }"#,
+        )
+        .unwrap();
+        fs::write(dir.join("README.md"), "not a manifest").unwrap();
+
+        let whole_dir = load_manifests(&[dir.display().to_string()]).unwrap();
+        let names: Vec<&str> = whole_dir["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|item| item["metadata"]["name"].as_str())
+            .collect();
+        assert_eq!(names, vec!["a", "b"]);
+
+        let globbed = load_manifests(&[format!("{}/*.yaml", dir.display())]).unwrap();
+        assert_eq!(globbed["items"].as_array().unwrap().len(), 1);
+
+        let overlapping = load_manifests(&[
+            format!("{}/*.yaml", dir.display()),
+            dir.join("nested/b.json").display().to_string(),
+            dir.display().to_string(),
+        ])
+        .unwrap();
+        assert_eq!(overlapping["items"].as_array().unwrap().len(), 2);
+
+        assert!(matches!(
+            load_manifests(&[format!("{}/missing-*.yaml", dir.display())]),
+            Err(FloorPlanError::ManifestError(_))
+        ));
+    }
+
+    #[test]
+    fn test_kubectl_list_file() {
+        let list = load_manifests(&[DEFAULT_K8S_FILE.to_string()]).unwrap();
+        assert!(!list["items"].as_array().unwrap().is_empty());
+    }
+}
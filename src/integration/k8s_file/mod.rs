@@ -1,5 +1,6 @@
 pub mod k8s_integration_plugin;
 pub mod k8s_integration_systems;
 pub mod k8s_json;
+pub mod k8s_manifest;
 
 pub use k8s_integration_plugin::K8sIntegrationPlugin;
@@ -0,0 +1,281 @@
+use super::k8s_api::{ClusterScope, SelectedKind, api_resource};
+use bevy::log::{debug, warn};
+use futures::future::join_all;
+use k8s_openapi::api::authorization::v1::{
+    ResourceAttributes, SelfSubjectAccessReview, SelfSubjectAccessReviewSpec,
+};
+use kube::{
+    Client,
+    api::{Api, PostParams},
+    core::ApiResource,
+};
+use std::fmt::{Display, Formatter};
+
+/// what watching a kind takes
+const WATCH_VERBS: [&str; 2] = ["list", "watch"];
+
+/**
+* a kind the user may not watch, in one namespace or cluster wide - its hallway is locked rather
+* than shown empty
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessDenial {
+    pub kind: String,
+    pub verb: String,
+    /// the resource as rbac names it, ie: `deployments.apps`
+    pub resource: String,
+    pub namespace: Option<String>,
+    /// what the authorizer said, rbac mostly says nothing
+    pub reason: Option<String>,
+}
+
+impl AccessDenial {
+    /// a list the server refused that no review had denied, ie: because the review itself failed
+    pub fn refused(resource: &ApiResource, namespace: Option<&str>, reason: &str) -> Self {
+        Self {
+            kind: resource.kind.clone(),
+            verb: "list".to_string(),
+            resource: rbac_resource(resource),
+            namespace: namespace.map(String::from),
+            reason: Some(reason.to_string()).filter(|reason| !reason.is_empty()),
+        }
+    }
+
+    /// a cluster wide denial covers every namespace
+    pub fn covers(&self, kind: &str, namespace: &str) -> bool {
+        self.kind == kind
+            && self
+                .namespace
+                .as_deref()
+                .is_none_or(|denied| denied == namespace)
+    }
+}
+
+impl Display for AccessDenial {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "locked: cannot {} {}", self.verb, self.resource)?;
+        match &self.namespace {
+            Some(namespace) => write!(f, " in {namespace}")?,
+            None => write!(f, " cluster wide, try --namespace")?,
+        }
+        match &self.reason {
+            Some(reason) => write!(f, " ({reason})"),
+            None => Ok(()),
+        }
+    }
+}
+
+fn rbac_resource(resource: &ApiResource) -> String {
+    if resource.group.is_empty() {
+        resource.plural.clone()
+    } else {
+        format!("{}.{}", resource.plural, resource.group)
+    }
+}
+
+/// none when the review itself fails - the watch will report what is wrong
+async fn review(
+    api: &Api<SelfSubjectAccessReview>,
+    resource: &ApiResource,
+    namespace: Option<&str>,
+    verb: &str,
+) -> Option<(bool, Option<String>)> {
+    let review = SelfSubjectAccessReview {
+        spec: SelfSubjectAccessReviewSpec {
+            resource_attributes: Some(ResourceAttributes {
+                group: Some(resource.group.clone()),
+                resource: Some(resource.plural.clone()),
+                namespace: namespace.map(String::from),
+                verb: Some(verb.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    match api.create(&PostParams::default(), &review).await {
+        Ok(answer) => answer.status.map(|status| {
+            (
+                status.allowed,
+                status.reason.filter(|reason| !reason.is_empty()),
+            )
+        }),
+        Err(e) => {
+            warn!(
+                "Unable to review access to {}: {e}",
+                rbac_resource(resource)
+            );
+            None
+        }
+    }
+}
+
+async fn check_kind(
+    api: &Api<SelfSubjectAccessReview>,
+    resource: &ApiResource,
+    namespace: Option<&str>,
+) -> Option<AccessDenial> {
+    for verb in WATCH_VERBS {
+        if let Some((false, reason)) = review(api, resource, namespace, verb).await {
+            return Some(AccessDenial {
+                kind: resource.kind.clone(),
+                verb: verb.to_string(),
+                resource: rbac_resource(resource),
+                namespace: namespace.map(String::from),
+                reason,
+            });
+        }
+    }
+    None
+}
+
+/**
+* ask the cluster, with a `SelfSubjectAccessReview` each, whether the user may list and watch every
+* kind and events in the scope - cluster wide, or in each named namespace. reviews run side by side, and one
+* that cannot be made counts as allowed until the list itself is refused
+*/
+pub async fn check_access(
+    client: &Client,
+    kinds: &[SelectedKind],
+    scope: &ClusterScope,
+) -> Vec<AccessDenial> {
+    let api: Api<SelfSubjectAccessReview> = Api::all(client.clone());
+    let listed_namespaces = scope.listed_namespaces();
+    // events are always watched for the events panel, whether or not they have a hallway
+    let events = SelectedKind {
+        resource: api_resource("Event"),
+        named: false,
+        namespaced: true,
+    };
+    let events = kinds
+        .iter()
+        .all(|selected| selected.resource.kind != "Event")
+        .then_some(&events);
+    let mut checks = Vec::new();
+    for selected in kinds.iter().chain(events) {
+        match (&listed_namespaces, selected.namespaced) {
+            (Some(_), false) => {}
+            (Some(namespaces), true) => checks.extend(
+                namespaces
+                    .iter()
+                    .map(|namespace| check_kind(&api, &selected.resource, Some(namespace))),
+            ),
+            (None, _) => checks.push(check_kind(&api, &selected.resource, None)),
+        }
+    }
+    let denials: Vec<AccessDenial> = join_all(checks).await.into_iter().flatten().collect();
+    debug!("Access denials: {denials:?}");
+    denials
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integration::k8s_live::k8s_api::KindFilter;
+    use crate::integration::k8s_live::k8s_mock_server::{MockApiServer, status_body};
+    use kube::core::GroupVersionKind;
+
+    fn served(kind: &str, group: &str, plural: &str) -> ApiResource {
+        ApiResource::from_gvk_with_plural(&GroupVersionKind::gvk(group, "v1", kind), plural)
+    }
+
+    /// an rbac that lets the user list but not watch secrets, and nothing at all in `vault`
+    fn reviewer(body: &str) -> (u16, String) {
+        let review: serde_json::Value = serde_json::from_str(body).unwrap();
+        let attributes = &review["spec"]["resourceAttributes"];
+        let allowed = attributes["namespace"] != "vault"
+            && !(attributes["resource"] == "secrets" && attributes["verb"] == "watch");
+        let mut answer = review.clone();
+        answer["status"] = serde_json::json!({"allowed": allowed});
+        (201, answer.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_check_access() {
+        let server = MockApiServer::start(|request| {
+            if request.method == "POST"
+                && request.path == "/apis/authorization.k8s.io/v1/selfsubjectaccessreviews?"
+            {
+                reviewer(&request.body)
+            } else {
+                (404, status_body(404, "NotFound", "not found"))
+            }
+        })
+        .await;
+        let filter = KindFilter {
+            allow: vec![
+                "Deployment".to_string(),
+                "Secret".to_string(),
+                "PersistentVolume".to_string(),
+            ],
+            deny: Vec::new(),
+        };
+        let kinds = filter.select(vec![
+            served("Deployment", "apps", "deployments"),
+            served("Secret", "", "secrets"),
+            served("PersistentVolume", "", "persistentvolumes"),
+        ]);
+
+        let everywhere = check_access(&server.client(), &kinds, &ClusterScope::default()).await;
+        assert_eq!(
+            everywhere,
+            vec![AccessDenial {
+                kind: "Secret".to_string(),
+                verb: "watch".to_string(),
+                resource: "secrets".to_string(),
+                namespace: None,
+                reason: None,
+            }]
+        );
+        assert!(everywhere[0].covers("Secret", "shop"));
+        assert_eq!(
+            everywhere[0].to_string(),
+            "locked: cannot watch secrets cluster wide, try --namespace"
+        );
+
+        let scope = ClusterScope {
+            namespaces: vec!["shop".to_string(), "vault".to_string()],
+            ..Default::default()
+        };
+        let named = check_access(&server.client(), &kinds, &scope).await;
+        let denied: Vec<(&str, Option<&str>, &str)> = named
+            .iter()
+            .map(|denial| {
+                (
+                    denial.kind.as_str(),
+                    denial.namespace.as_deref(),
+                    denial.verb.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            denied,
+            vec![
+                ("Deployment", Some("vault"), "list"),
+                ("Secret", Some("shop"), "watch"),
+                ("Secret", Some("vault"), "list"),
+                // events are reviewed for the events panel, without being asked for
+                ("Event", Some("vault"), "list"),
+            ]
+        );
+        assert!(!named[0].covers("Deployment", "shop"));
+        assert_eq!(
+            named[0].to_string(),
+            "locked: cannot list deployments.apps in vault"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_review_counts_as_allowed() {
+        let server = MockApiServer::start(|_| {
+            (
+                403,
+                status_body(403, "Forbidden", "selfsubjectaccessreviews are forbidden"),
+            )
+        })
+        .await;
+        let kinds = KindFilter::default().select(vec![served("Pod", "", "pods")]);
+        let denials = check_access(&server.client(), &kinds, &ClusterScope::default()).await;
+        assert!(denials.is_empty());
+    }
+}
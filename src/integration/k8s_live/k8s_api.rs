@@ -1,52 +1,448 @@
-use crate::integration::integration_utils::IntegrationResource;
-use bevy::log::debug;
-use kube::core::{ApiResource, DynamicObject};
+use super::k8s_access::AccessDenial;
+use crate::cli::Cli;
+use crate::floorplan::{FloorPlanError, FloorPlanResult};
+use crate::integration::integration_utils::{
+    HELM_RELEASE_ANNOTATION, IntegrationResource, Owner, attribute_map, get_container_status,
+    get_ingress_routes, get_references, get_selector, get_status, get_volume_references,
+    trimmed_source,
+};
+use bevy::log::{debug, warn};
+use k8s_openapi::api::core::v1::Namespace;
+use kube::core::{ApiResource, DynamicObject, GroupVersion, GroupVersionKind};
 use kube::{
     Client,
     api::{Api, ListParams},
+    discovery::{self, Scope, verbs},
+    runtime::watcher,
 };
-use std::error::Error;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/**
+* the slice of the cluster to explore - an empty `namespaces` means all of them, which also means
+* each kind is listed cluster wide rather than one namespace at a time
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClusterScope {
+    pub namespaces: Vec<String>,
+    pub excluded_namespaces: Vec<String>,
+    pub selector: Option<String>,
+}
+
+impl ClusterScope {
+    pub fn from_cli(cli: &Cli) -> Self {
+        Self {
+            namespaces: cli.namespaces.clone(),
+            excluded_namespaces: cli.excluded_namespaces.clone(),
+            selector: cli.selector.clone(),
+        }
+    }
+
+    pub fn includes(&self, namespace: &str) -> bool {
+        (self.namespaces.is_empty() || self.namespaces.iter().any(|ns| ns == namespace))
+            && !self.excluded_namespaces.iter().any(|ns| ns == namespace)
+    }
+
+    /// the namespaces to list or watch one by one, `None` when whole kinds are listed at once
+    pub fn listed_namespaces(&self) -> Option<Vec<String>> {
+        (!self.namespaces.is_empty()).then(|| {
+            self.namespaces
+                .iter()
+                .filter(|namespace| self.includes(namespace))
+                .cloned()
+                .collect()
+        })
+    }
+
+    pub fn list_params(&self) -> ListParams {
+        self.selector
+            .as_deref()
+            .map_or_else(ListParams::default, |selector| {
+                ListParams::default().labels(selector)
+            })
+    }
+
+    pub fn watcher_config(&self) -> watcher::Config {
+        self.selector
+            .as_deref()
+            .map_or_else(watcher::Config::default, |selector| {
+                watcher::Config::default().labels(selector)
+            })
+    }
+}
+
+/// the kinds that get a hallway unless `--kind` says otherwise
+const DEFAULT_KINDS: [&str; 13] = [
+    "Deployment",
+    "StatefulSet",
+    "DaemonSet",
+    "CronJob",
+    "Job",
+    "ReplicaSet",
+    "Service",
+    "ConfigMap",
+    "Ingress",
+    "PersistentVolumeClaim",
+    "Pod",
+    "PersistentVolume",
+    "StorageClass",
+];
+
+/**
+* the cluster scoped kinds that can get a hallway off the lobby - only watched when the scope
+* covers every namespace, since a team scoped to its own namespaces rarely may list them
+*/
+const CLUSTER_SCOPED_KINDS: [&str; 2] = ["PersistentVolume", "StorageClass"];
+
+/**
+* never picked up by `*`, only when named - events have a watch of their own and would flood
+* namespaces with rooms, and a listed secret would hold its data in memory
+*/
+const KINDS_ONLY_WHEN_NAMED: [&str; 2] = ["Event", "Secret"];
+
+/**
+* which of the kinds the cluster serves get a hallway - `allow` names kinds, or `*` for all of them
+* including custom resources but not events or secrets, and `deny` always wins. a kind can be named by kind, plural or either
+* one qualified with its group, ie: `Rollout`, `certificates` or `Certificate.cert-manager.io`
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KindFilter {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl Default for KindFilter {
+    fn default() -> Self {
+        Self {
+            allow: DEFAULT_KINDS.iter().map(ToString::to_string).collect(),
+            deny: Vec::new(),
+        }
+    }
+}
+
+/// a kind the cluster serves that made it through the `KindFilter`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectedKind {
+    pub resource: ApiResource,
+    /// named in the allow list, so it gets a hallway even in namespaces that have none of it
+    pub named: bool,
+    pub namespaced: bool,
+}
+
+impl KindFilter {
+    pub fn from_cli(cli: &Cli) -> Self {
+        let mut filter = Self {
+            deny: cli.excluded_kinds.clone(),
+            ..Default::default()
+        };
+        if !cli.kinds.is_empty() {
+            filter.allow.clone_from(&cli.kinds);
+        }
+        filter
+    }
+
+    fn matches(pattern: &str, resource: &ApiResource) -> bool {
+        [&resource.kind, &resource.plural].into_iter().any(|name| {
+            pattern.eq_ignore_ascii_case(name)
+                || pattern.eq_ignore_ascii_case(&format!("{name}.{}", resource.group))
+        })
+    }
+
+    /// where the allow list names the kind, so hallways keep the order they were asked for in
+    fn position(&self, resource: &ApiResource) -> Option<usize> {
+        self.allow
+            .iter()
+            .position(|pattern| Self::matches(pattern, resource))
+    }
+
+    pub fn allows(&self, resource: &ApiResource) -> bool {
+        (self.position(resource).is_some()
+            || (self.allow.iter().any(|pattern| pattern == "*")
+                && !KINDS_ONLY_WHEN_NAMED.contains(&resource.kind.as_str())))
+            && !self
+                .deny
+                .iter()
+                .any(|pattern| Self::matches(pattern, resource))
+    }
+
+    /**
+     * the allowed kinds out of everything discovered - named kinds first in the order given, then
+     * the rest by kind. a kind served by more than one group, such as `Event`, is kept once,
+     * preferring the group that was named and then the core group
+     */
+    pub fn select(&self, served: Vec<ApiResource>) -> Vec<SelectedKind> {
+        let mut selected: Vec<SelectedKind> = served
+            .into_iter()
+            .filter(|resource| self.allows(resource))
+            .map(|resource| SelectedKind {
+                named: self.position(&resource).is_some(),
+                namespaced: !CLUSTER_SCOPED_KINDS.contains(&resource.kind.as_str()),
+                resource,
+            })
+            .collect();
+        selected.sort_by(|a, b| {
+            let key = |kind: &SelectedKind| {
+                (
+                    self.position(&kind.resource).unwrap_or(usize::MAX),
+                    kind.resource.kind.clone(),
+                    !kind.resource.group.is_empty(),
+                    kind.resource.group.clone(),
+                )
+            };
+            key(a).cmp(&key(b))
+        });
+        let mut seen = HashSet::new();
+        selected.retain(|selected| seen.insert(selected.resource.kind.clone()));
+        selected
+    }
+}
+
+/**
+* everything a floorplan is built from - whether it came from one round of list calls or from the
+* local stores kept by the watchers
+*/
+#[derive(Debug, Clone, Default)]
+pub struct ClusterObjects {
+    pub namespaces: Vec<String>,
+    pub kinds: Vec<SelectedKind>,
+    pub objects: BTreeMap<String, Vec<DynamicObject>>,
+    /// kinds the user may not list or watch, which were never asked for
+    pub denials: Vec<AccessDenial>,
+}
+
+impl ClusterObjects {
+    /**
+     * sorts namespaces and objects by name so that a rebuild from an unchanged cluster lays the
+     * rooms out in the same places
+     */
+    pub fn new(
+        mut namespaces: Vec<String>,
+        kinds: Vec<SelectedKind>,
+        mut objects: BTreeMap<String, Vec<DynamicObject>>,
+    ) -> Self {
+        namespaces.sort();
+        namespaces.dedup();
+        for resource_list in objects.values_mut() {
+            resource_list.sort_by(|a, b| a.metadata.name.cmp(&b.metadata.name));
+        }
+        Self {
+            namespaces,
+            kinds,
+            objects,
+            denials: Vec::new(),
+        }
+    }
+
+    pub fn with_denials(mut self, denials: Vec<AccessDenial>) -> Self {
+        self.denials = denials;
+        self
+    }
+
+    /// why a kind's hallway in a namespace is locked, `""` for cluster scoped kinds
+    pub fn denial(&self, kind: &str, namespace: &str) -> Option<&AccessDenial> {
+        self.denials
+            .iter()
+            .find(|denial| denial.covers(kind, namespace))
+    }
+
+    pub fn resources(&self, kind: &str, namespace: &str) -> Vec<IntegrationResource> {
+        let resource_list = self
+            .objects
+            .get(kind)
+            .into_iter()
+            .flatten()
+            .filter(|resource| resource.metadata.namespace.as_deref() == Some(namespace))
+            .cloned()
+            .collect();
+        extract_integration_resources(kind, resource_list)
+    }
+
+    /// objects outside any namespace, ie: persistent volumes
+    pub fn cluster_resources(&self) -> Vec<IntegrationResource> {
+        self.kinds
+            .iter()
+            .filter(|selected| !selected.namespaced)
+            .flat_map(|selected| {
+                let kind = &selected.resource.kind;
+                extract_integration_resources(
+                    kind,
+                    self.objects.get(kind).cloned().unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    /// the nodes pods are scheduled on, sorted - pods that are not scheduled yet have none
+    pub fn node_names(&self) -> Vec<String> {
+        let nodes: BTreeSet<String> = self
+            .objects
+            .get("Pod")
+            .into_iter()
+            .flatten()
+            .filter_map(|pod| pod.data["spec"]["nodeName"].as_str().map(String::from))
+            .collect();
+        nodes.into_iter().collect()
+    }
+
+    /// the kinds that get a hallway in a namespace, in hallway order
+    pub fn hallway_kinds(&self, namespace: &str) -> Vec<&str> {
+        self.kinds
+            .iter()
+            .filter(|selected| selected.namespaced)
+            .filter(|selected| {
+                selected.named
+                    || self
+                        .objects
+                        .get(&selected.resource.kind)
+                        .is_some_and(|list| {
+                            list.iter()
+                                .any(|o| o.metadata.namespace.as_deref() == Some(namespace))
+                        })
+            })
+            .map(|selected| selected.resource.kind.as_str())
+            .collect()
+    }
+}
 
 /**
-* keep this updated for any 'kind' you want to support - note that different kinds are in different
-* groups
+* the group a well known kind lives in, for looking one up without discovery - the generator
+* uses what `discover_kinds` found instead
 */
 fn get_api_params(kind: &str) -> (&str, &str) {
     match kind {
-        "DaemonSet" | "ReplicaSet" | "Deployment" => ("apps", "v1"),
+        "DaemonSet" | "ReplicaSet" | "Deployment" | "StatefulSet" => ("apps", "v1"),
+        "Job" | "CronJob" => ("batch", "v1"),
         "Ingress" => ("networking.k8s.io", "v1"),
         _ => ("", "v1"),
     }
 }
 
-fn build_api_resource(kind: &str, group: &str, version: &str) -> ApiResource {
-    let api_version = if group.is_empty() {
-        version.to_string()
-    } else {
-        format!("{group}/{version}")
-    };
+pub fn api_resource(kind: &str) -> ApiResource {
+    let (group, version) = get_api_params(kind);
+    ApiResource::from_gvk(&GroupVersionKind::gvk(group, version, kind))
+}
+
+/**
+* the served kinds in a group version that can be listed and watched - every namespaced kind, and
+* the cluster scoped kinds that have a hallway
+*/
+fn watchable_kinds(group: &discovery::ApiGroup) -> impl Iterator<Item = ApiResource> {
+    group
+        .recommended_resources()
+        .into_iter()
+        .filter(|(resource, caps)| {
+            (caps.scope == Scope::Namespaced
+                || CLUSTER_SCOPED_KINDS.contains(&resource.kind.as_str()))
+                && caps.supports_operation(verbs::LIST)
+                && caps.supports_operation(verbs::WATCH)
+        })
+        .map(|(resource, _)| resource)
+}
+
+/**
+* ask the cluster which kinds it serves, custom resources included, at each group's
+* preferred version - then keep the ones the filter allows. a group that cannot be queried, such as
+* an aggregated api whose backend is down, is skipped rather than failing the whole cluster
+*/
+pub async fn discover_kinds(
+    client: &Client,
+    filter: &KindFilter,
+) -> FloorPlanResult<Vec<SelectedKind>> {
+    let mut group_versions = vec![GroupVersion::gv("", "v1")];
+    let groups = client
+        .list_api_groups()
+        .await
+        .map_err(|e| classify_error(&e))?;
+    group_versions.extend(
+        groups
+            .groups
+            .into_iter()
+            .filter_map(|group| {
+                group
+                    .preferred_version
+                    .or_else(|| group.versions.into_iter().next())
+            })
+            .filter_map(|version| version.group_version.parse().ok()),
+    );
+
+    let mut served = Vec::new();
+    for group_version in &group_versions {
+        match discovery::pinned_group(client, group_version).await {
+            Ok(group) => served.extend(watchable_kinds(&group)),
+            Err(e) if group_version.group.is_empty() => return Err(classify_error(&e)),
+            Err(e) => warn!("Skipping api group {}: {e}", group_version.api_version()),
+        }
+    }
+    let selected = filter.select(served);
+    debug!(
+        "Discovered kinds: {:?}",
+        selected
+            .iter()
+            .map(|selected| &selected.resource.kind)
+            .collect::<Vec<_>>()
+    );
+    Ok(selected)
+}
+
+/**
+* sort a kube error by what the player can do about it - a rejected token and an unreachable
+* cluster both get a banner, but they mean different things
+*/
+pub fn classify_error(e: &kube::Error) -> FloorPlanError {
+    match e {
+        kube::Error::Api(status) if status.code == 401 || status.code == 403 => {
+            FloorPlanError::AuthError(e.to_string())
+        }
+        kube::Error::Auth(_) => FloorPlanError::AuthError(e.to_string()),
+        kube::Error::HyperError(_) | kube::Error::Service(_) => {
+            FloorPlanError::ConnectionError(e.to_string())
+        }
+        _ => FloorPlanError::ServiceError(e.to_string()),
+    }
+}
+
+/// what the server said when it refused a request with a 403, none for any other error
+pub fn forbidden(e: &kube::Error) -> Option<&str> {
+    match e {
+        kube::Error::Api(status) if status.code == 403 => Some(&status.message),
+        _ => None,
+    }
+}
+
+/// as `forbidden` for the errors a watch stream reports
+pub fn forbidden_watch(e: &watcher::Error) -> Option<&str> {
+    match e {
+        watcher::Error::InitialListFailed(e)
+        | watcher::Error::WatchStartFailed(e)
+        | watcher::Error::WatchFailed(e) => forbidden(e),
+        watcher::Error::WatchError(status) if status.code == 403 => Some(&status.message),
+        _ => None,
+    }
+}
 
-    ApiResource {
-        group: group.to_string(),
-        version: version.to_string(),
-        api_version,
-        kind: kind.to_string(),
-        plural: format!("{}s", kind.to_lowercase()),
+/// as `classify_error` for the errors a watch stream reports
+pub fn classify_watch_error(e: &watcher::Error) -> FloorPlanError {
+    match e {
+        watcher::Error::InitialListFailed(e)
+        | watcher::Error::WatchStartFailed(e)
+        | watcher::Error::WatchFailed(e) => classify_error(e),
+        watcher::Error::WatchError(status) if status.code == 401 || status.code == 403 => {
+            FloorPlanError::AuthError(e.to_string())
+        }
+        _ => FloorPlanError::ServiceError(e.to_string()),
     }
 }
 
 async fn fetch_resource_list(
     client: &Client,
-    namespace: &str,
+    namespace: Option<&str>,
     resource: &ApiResource,
-) -> Result<Vec<DynamicObject>, Box<dyn Error>> {
-    let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), namespace, resource);
-    let lp = ListParams::default();
-    let resource_list = api
-        .list(&lp)
-        .await
-        .map_err(|e| format!("Failed to list resources: {e}"))?;
-    Ok(resource_list.items)
+    lp: &ListParams,
+) -> Result<Vec<DynamicObject>, kube::Error> {
+    let api: Api<DynamicObject> = namespace.map_or_else(
+        || Api::all_with(client.clone(), resource),
+        |namespace| Api::namespaced_with(client.clone(), namespace, resource),
+    );
+    Ok(api.list(lp).await?.items)
 }
 
 /**
@@ -60,30 +456,65 @@ fn extract_integration_resources(
     for resource in resource_list {
         if let Some(name) = resource.metadata.name.clone() {
             debug!("Found {kind} {name}");
-            let owner_reference = get_owner_reference(&resource);
             let containers = get_containers(&resource);
-            let owner = owner_reference
-                .map(|(kind, name)| IntegrationResource::new(name, kind, None, Vec::new()));
-            resources.push(IntegrationResource::new(
-                name,
-                kind.to_string(),
-                owner,
-                containers,
-            ));
+            let labels = resource.metadata.labels.clone().unwrap_or_default();
+            let attributes = get_attributes(&resource);
+            let status = get_status(kind, &resource.data);
+            let mut object = serde_json::to_value(&resource).unwrap_or_default();
+            // list items come without their kind, which tells a secret's data to stay out
+            if object["kind"].is_null() {
+                object["kind"] = kind.into();
+            }
+            let source = trimmed_source(&object);
+            resources.push(
+                IntegrationResource::new(name, kind.to_string(), containers)
+                    .with_owners(get_owners(&resource))
+                    .with_labels(labels)
+                    .with_attributes(attributes)
+                    .with_status(status)
+                    .with_selector(get_selector(kind, &resource.data))
+                    .with_routes(get_ingress_routes(kind, &resource.data))
+                    .with_references(get_references(kind, &resource.data))
+                    .with_source(source),
+            );
         }
     }
     resources
 }
 
+fn get_attributes(v: &DynamicObject) -> BTreeMap<String, String> {
+    let created = v
+        .metadata
+        .creation_timestamp
+        .as_ref()
+        .map(|t| t.0.to_string());
+    let release = v
+        .metadata
+        .annotations
+        .as_ref()
+        .and_then(|annotations| annotations.get(HELM_RELEASE_ANNOTATION));
+    attribute_map([
+        ("created", created.as_deref()),
+        ("node", v.data["spec"]["nodeName"].as_str()),
+        ("release", release.map(String::as_str)),
+    ])
+}
+
 /**
- * an `owner_references` could be a replicaset to a pod or a deployment to a replicaset
+ * every `owner_references` entry - a replicaset to a pod, a deployment to a replicaset or a cronjob
+ * to a job, plus any owners that only exist for garbage collection
 */
-fn get_owner_reference(v: &DynamicObject) -> Option<(String, String)> {
+fn get_owners(v: &DynamicObject) -> Vec<Owner> {
     v.metadata
         .owner_references
-        .as_ref()
-        .and_then(|refs| refs.first())
-        .map(|owner_ref| (owner_ref.kind.clone(), owner_ref.name.clone()))
+        .iter()
+        .flatten()
+        .map(|owner_ref| Owner {
+            kind: owner_ref.kind.clone(),
+            name: owner_ref.name.clone(),
+            controller: owner_ref.controller.unwrap_or(false),
+        })
+        .collect()
 }
 
 fn get_containers(v: &DynamicObject) -> Vec<IntegrationResource> {
@@ -94,12 +525,15 @@ fn get_containers(v: &DynamicObject) -> Vec<IntegrationResource> {
                 .iter()
                 .filter_map(|container| {
                     let container_name = container["name"].as_str().map(String::from);
-                    let volume_mounts = get_volume_mounts(container);
-                    container_name.map(|n| IntegrationResource {
-                        name: n,
-                        kind: "Container".to_string(),
-                        parent: None,
-                        children: volume_mounts,
+                    let volume_mounts = get_volume_mounts(&v.data, container);
+                    container_name.map(|n| {
+                        let status = get_container_status(&v.data, &n);
+                        IntegrationResource::new(n, "Container".to_string(), volume_mounts)
+                            .with_attributes(attribute_map([(
+                                "image",
+                                container["image"].as_str(),
+                            )]))
+                            .with_status(status)
                     })
                 })
                 .collect()
@@ -107,18 +541,29 @@ fn get_containers(v: &DynamicObject) -> Vec<IntegrationResource> {
         .unwrap_or_default()
 }
 
-fn get_volume_mounts(container: &serde_json::Value) -> Vec<IntegrationResource> {
+fn get_volume_mounts(
+    pod: &serde_json::Value,
+    container: &serde_json::Value,
+) -> Vec<IntegrationResource> {
     container["volumeMounts"]
         .as_array()
         .map(|volume_mounts| {
             volume_mounts
                 .iter()
                 .filter_map(|volume_mount| {
-                    volume_mount["name"].as_str().map(|n| IntegrationResource {
-                        name: n.to_string(),
-                        kind: "VolumeMount".to_string(),
-                        parent: None,
-                        children: Vec::new(),
+                    volume_mount["name"].as_str().map(|n| {
+                        let (mut attributes, references) = get_volume_references(pod, n);
+                        attributes.extend(attribute_map([(
+                            "mountPath",
+                            volume_mount["mountPath"].as_str(),
+                        )]));
+                        IntegrationResource::new(
+                            n.to_string(),
+                            "VolumeMount".to_string(),
+                            Vec::new(),
+                        )
+                        .with_attributes(attributes)
+                        .with_references(references)
                     })
                 })
                 .collect()
@@ -127,140 +572,437 @@ fn get_volume_mounts(container: &serde_json::Value) -> Vec<IntegrationResource>
 }
 
 /**
-* this is the main API for the k8s api - it fetches the names of resources of a given kind in a
-* given namespace
+* list a kind, where a refused list is a denial to lock the hallway with rather than an error that
+* takes the whole world down
 */
-pub async fn get_names(
+async fn list_or_deny(
     client: &Client,
-    kind: &str,
-    namespace: &str,
-) -> Result<Vec<IntegrationResource>, Box<dyn Error>> {
-    debug!("Getting names for {kind} in {namespace}");
+    namespace: Option<&str>,
+    resource: &ApiResource,
+    lp: &ListParams,
+    denials: &mut Vec<AccessDenial>,
+) -> FloorPlanResult<Vec<DynamicObject>> {
+    match fetch_resource_list(client, namespace, resource, lp).await {
+        Ok(resource_list) => Ok(resource_list),
+        Err(e) => match forbidden(&e) {
+            Some(reason) => {
+                warn!("Listing {} was refused: {e}", resource.plural);
+                denials.push(AccessDenial::refused(resource, namespace, reason));
+                Ok(Vec::new())
+            }
+            None => Err(classify_error(&e)),
+        },
+    }
+}
 
-    let (group, version) = get_api_params(kind);
-    let resource = build_api_resource(kind, group, version);
-    let resource_list = fetch_resource_list(client, namespace, &resource).await?;
-    Ok(extract_integration_resources(kind, resource_list))
+/**
+* this is the main API for the k8s api - one list call per kind across all namespaces, or per kind
+* and namespace when the scope names its namespaces
+*/
+pub async fn list_cluster_objects(
+    client: &Client,
+    kinds: &[SelectedKind],
+    scope: &ClusterScope,
+    denials: &[AccessDenial],
+) -> FloorPlanResult<ClusterObjects> {
+    let listed_namespaces = scope.listed_namespaces();
+    let namespaces = if let Some(namespaces) = &listed_namespaces {
+        namespaces.clone()
+    } else {
+        let namespaces: Api<Namespace> = Api::all(client.clone());
+        namespaces
+            .list(&ListParams::default())
+            .await
+            .map_err(|e| classify_error(&e))?
+            .items
+            .into_iter()
+            .filter_map(|ns| ns.metadata.name)
+            .filter(|namespace| scope.includes(namespace))
+            .collect()
+    };
+
+    let lp = scope.list_params();
+    let mut denials = denials.to_vec();
+    let mut objects = BTreeMap::new();
+    for SelectedKind {
+        resource,
+        namespaced,
+        ..
+    } in kinds
+    {
+        let mut resource_list = Vec::new();
+        if !namespaced && listed_namespaces.is_some() {
+            continue;
+        } else if let Some(namespaces) = &listed_namespaces {
+            for namespace in namespaces {
+                if denials
+                    .iter()
+                    .any(|denial| denial.covers(&resource.kind, namespace))
+                {
+                    continue;
+                }
+                resource_list.extend(
+                    list_or_deny(client, Some(namespace), resource, &lp, &mut denials).await?,
+                );
+            }
+        } else if denials
+            .iter()
+            .any(|denial| denial.kind == resource.kind && denial.namespace.is_none())
+        {
+            continue;
+        } else {
+            resource_list = list_or_deny(client, None, resource, &lp, &mut denials).await?;
+        }
+        objects.insert(resource.kind.clone(), resource_list);
+    }
+    Ok(ClusterObjects::new(namespaces, kinds.to_vec(), objects).with_denials(denials))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use bevy::log::{debug, info};
-    use kube::Client;
+    use crate::integration::k8s_live::k8s_mock_server::{MockApiServer, fixture_objects};
+
+    fn dynamic_object(kind: &str, namespace: &str, name: &str) -> DynamicObject {
+        serde_json::from_value(serde_json::json!({
+            "apiVersion": "v1",
+            "kind": kind,
+            "metadata": { "name": name, "namespace": namespace }
+        }))
+        .expect("bad test object")
+    }
+
+    fn served(kind: &str, group: &str, plural: &str) -> ApiResource {
+        ApiResource::from_gvk_with_plural(&GroupVersionKind::gvk(group, "v1", kind), plural)
+    }
+
+    #[test]
+    fn test_cluster_objects_resources() {
+        let objects = ClusterObjects::new(
+            vec!["web".to_string(), "db".to_string(), "web".to_string()],
+            KindFilter::default().select(vec![served("Pod", "", "pods")]),
+            BTreeMap::from([(
+                "Pod".to_string(),
+                vec![
+                    dynamic_object("Pod", "web", "web-b"),
+                    dynamic_object("Pod", "db", "db-a"),
+                    dynamic_object("Pod", "web", "web-a"),
+                ],
+            )]),
+        );
+        assert_eq!(objects.namespaces, vec!["db", "web"]);
+
+        let names: Vec<String> = objects
+            .resources("Pod", "web")
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        assert_eq!(names, vec!["web-a", "web-b"]);
+        assert!(objects.resources("Service", "web").is_empty());
+    }
+
+    #[test]
+    fn test_kind_filter() {
+        let cluster = vec![
+            served("Rollout", "argoproj.io", "rollouts"),
+            served("Pod", "", "pods"),
+            served("Event", "events.k8s.io", "events"),
+            served("Ingress", "networking.k8s.io", "ingresses"),
+            served("Event", "", "events"),
+            served("Secret", "", "secrets"),
+            served("Certificate", "cert-manager.io", "certificates"),
+        ];
+        let kinds = |filter: &KindFilter| -> Vec<String> {
+            filter
+                .select(cluster.clone())
+                .into_iter()
+                .map(|selected| selected.resource.kind)
+                .collect()
+        };
+
+        // the defaults keep their order and never pick up custom resources
+        assert_eq!(kinds(&KindFilter::default()), vec!["Ingress", "Pod"]);
+        assert_eq!(
+            api_resource("Ingress").plural,
+            "ingresses",
+            "a kind looked up without discovery still gets a real plural"
+        );
+
+        let named = KindFilter {
+            allow: vec![
+                "certificates".to_string(),
+                "rollout.argoproj.io".to_string(),
+                "Pod".to_string(),
+            ],
+            deny: Vec::new(),
+        };
+        assert_eq!(kinds(&named), vec!["Certificate", "Rollout", "Pod"]);
+
+        // events and secrets only when named
+        let everything = KindFilter {
+            allow: vec!["Pod".to_string(), "*".to_string()],
+            deny: vec!["Rollout".to_string()],
+        };
+        let selected = everything.select(cluster.clone());
+        assert_eq!(kinds(&everything), vec!["Pod", "Certificate", "Ingress"]);
+        assert!(selected[0].named);
+        assert!(!selected[1].named);
+
+        let with_events = KindFilter {
+            allow: vec!["events".to_string(), "*".to_string()],
+            deny: vec!["Secret".to_string()],
+        };
+        let selected = with_events.select(cluster.clone());
+        assert_eq!(
+            kinds(&with_events),
+            vec!["Event", "Certificate", "Ingress", "Pod", "Rollout"]
+        );
+        let event = selected
+            .iter()
+            .find(|s| s.resource.kind == "Event")
+            .unwrap();
+        assert_eq!(event.resource.group, "", "the core Event wins");
+    }
+
+    #[test]
+    fn test_hallway_kinds() {
+        let filter = KindFilter {
+            allow: vec!["Pod".to_string(), "*".to_string()],
+            deny: Vec::new(),
+        };
+        let objects = ClusterObjects::new(
+            vec!["web".to_string(), "db".to_string()],
+            filter.select(vec![
+                served("Pod", "", "pods"),
+                served("Rollout", "argoproj.io", "rollouts"),
+            ]),
+            BTreeMap::from([(
+                "Rollout".to_string(),
+                vec![dynamic_object("Rollout", "web", "checkout")],
+            )]),
+        );
+        assert_eq!(objects.hallway_kinds("web"), vec!["Pod", "Rollout"]);
+        assert_eq!(objects.hallway_kinds("db"), vec!["Pod"]);
+    }
+
+    #[test]
+    fn test_cluster_objects_denial() {
+        let denial = |namespace: Option<&str>| AccessDenial {
+            kind: "Secret".to_string(),
+            verb: "list".to_string(),
+            resource: "secrets".to_string(),
+            namespace: namespace.map(String::from),
+            reason: None,
+        };
+        let objects = ClusterObjects::default().with_denials(vec![denial(Some("vault"))]);
+        assert!(objects.denial("Secret", "vault").is_some());
+        assert!(objects.denial("Secret", "shop").is_none());
+        assert!(objects.denial("Pod", "vault").is_none());
+
+        let objects = ClusterObjects::default().with_denials(vec![denial(None)]);
+        assert!(objects.denial("Secret", "shop").is_some());
+    }
+
+    #[test]
+    fn test_cluster_scoped_objects() {
+        let mut pod = dynamic_object("Pod", "web", "web-1");
+        pod.data = serde_json::json!({ "spec": { "nodeName": "worker-a" } });
+        let volume: DynamicObject = serde_json::from_value(serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "PersistentVolume",
+            "metadata": { "name": "pv-1" },
+            "spec": { "storageClassName": "fast-ssd" }
+        }))
+        .unwrap();
+        let objects = ClusterObjects::new(
+            vec!["web".to_string()],
+            KindFilter::default().select(vec![
+                served("Pod", "", "pods"),
+                served("PersistentVolume", "", "persistentvolumes"),
+            ]),
+            BTreeMap::from([
+                (
+                    "Pod".to_string(),
+                    vec![pod, dynamic_object("Pod", "web", "web-2")],
+                ),
+                ("PersistentVolume".to_string(), vec![volume]),
+            ]),
+        );
+        assert_eq!(objects.node_names(), vec!["worker-a"]);
+        assert_eq!(objects.hallway_kinds("web"), vec!["Pod"]);
+
+        let cluster_resources = objects.cluster_resources();
+        assert_eq!(cluster_resources.len(), 1);
+        assert_eq!(cluster_resources[0].name, "pv-1");
+        assert_eq!(cluster_resources[0].references[0].name, "fast-ssd");
+    }
+
+    #[test]
+    fn test_cluster_scope() {
+        let everything = ClusterScope::default();
+        assert!(everything.includes("kube-system"));
+        assert_eq!(everything.listed_namespaces(), None);
+        assert_eq!(everything.list_params().label_selector, None);
+
+        let team = ClusterScope {
+            namespaces: vec!["payments".to_string(), "payments-jobs".to_string()],
+            excluded_namespaces: vec!["payments-jobs".to_string()],
+            selector: Some("app=api".to_string()),
+        };
+        assert!(team.includes("payments"));
+        assert!(!team.includes("payments-jobs"));
+        assert!(!team.includes("kube-system"));
+        assert_eq!(team.listed_namespaces(), Some(vec!["payments".to_string()]));
+        assert_eq!(
+            team.list_params().label_selector.as_deref(),
+            Some("app=api")
+        );
+        assert_eq!(
+            team.watcher_config().label_selector.as_deref(),
+            Some("app=api")
+        );
+
+        let not_system = ClusterScope {
+            excluded_namespaces: vec!["kube-system".to_string()],
+            ..Default::default()
+        };
+        assert!(!not_system.includes("kube-system"));
+        assert!(not_system.includes("default"));
+        assert_eq!(not_system.listed_namespaces(), None);
+    }
+
+    #[test]
+    fn test_classify_error() {
+        let status = |code| {
+            kube::Error::Api(Box::new(kube::core::Status {
+                code,
+                message: "nope".to_string(),
+                ..Default::default()
+            }))
+        };
+        assert!(matches!(
+            classify_error(&status(401)),
+            FloorPlanError::AuthError(_)
+        ));
+        assert!(matches!(
+            classify_error(&status(403)),
+            FloorPlanError::AuthError(_)
+        ));
+        assert!(matches!(
+            classify_error(&status(500)),
+            FloorPlanError::ServiceError(_)
+        ));
+        assert!(matches!(
+            classify_watch_error(&watcher::Error::InitialListFailed(status(401))),
+            FloorPlanError::AuthError(_)
+        ));
+        assert!(matches!(
+            classify_watch_error(&watcher::Error::NoResourceVersion),
+            FloorPlanError::ServiceError(_)
+        ));
+    }
+
+    /// `assets/k8s.yaml` plus a config map, as a fake api server
+    async fn fake_cluster() -> MockApiServer {
+        MockApiServer::with_objects(fixture_objects(
+            &["assets/k8s.yaml"],
+            "
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: coredns
+  namespace: kube-system
+data:
+  Corefile: .:53 {}
+",
+        ))
+        .await
+    }
 
     #[tokio::test]
     async fn test_list_namespaces() {
-        let client = Client::try_default()
+        let server = fake_cluster().await;
+        let namespaces: Api<Namespace> = Api::all(server.client());
+        let names: Vec<String> = namespaces
+            .list(&ListParams::default())
             .await
-            .expect("Failed to create client");
-        let namespaces: Api<k8s_openapi::api::core::v1::Namespace> = Api::all(client);
-
-        match namespaces.list(&ListParams::default()).await {
-            Ok(ns_list) => {
-                println!("Found {} namespaces", ns_list.items.len());
-                for ns in ns_list {
-                    println!("Namespace: {}", ns.metadata.name.unwrap_or_default());
-                }
-            }
-            Err(e) => {
-                eprintln!("Error fetching namespaces: {}", e);
-                panic!("Failed to fetch namespaces");
-            }
-        }
+            .unwrap()
+            .into_iter()
+            .filter_map(|ns| ns.metadata.name)
+            .collect();
+        assert_eq!(names, vec!["default", "kube-system"]);
     }
 
-    #[tokio::test]
-    async fn test_get_names_pods() {
-        let client = Client::try_default()
+    async fn names(kind: &str) -> Vec<String> {
+        let server = fake_cluster().await;
+        let client = server.client();
+        let filter = KindFilter {
+            allow: vec![kind.to_string()],
+            ..Default::default()
+        };
+        let kinds = discover_kinds(&client, &filter).await.unwrap();
+        let objects = list_cluster_objects(&client, &kinds, &ClusterScope::default(), &[])
             .await
-            .expect("Failed to create client");
-        let namespace = "kube-system";
-        let kind = "Pod";
-
-        match get_names(&client, kind, namespace).await {
-            Ok(resources) => {
-                assert!(!resources.is_empty());
-                println!("Found {} Pods", resources.len());
-                for resource in resources {
-                    println!("Pod: {}", resource.name);
-                }
-            }
-            Err(e) => {
-                eprintln!("Error fetching Pods: {}", e);
-                panic!("Failed to fetch Pods");
-            }
-        }
+            .unwrap();
+        let mut names: Vec<String> = objects
+            .resources(kind, "kube-system")
+            .into_iter()
+            .map(|resource| resource.name)
+            .collect();
+        names.sort();
+        names
     }
 
     #[tokio::test]
-    async fn test_get_names_replicasets() {
-        info!("info");
-        debug!("debug");
-        let client = Client::try_default()
-            .await
-            .expect("Failed to create client");
-        let namespace = "kube-system";
-        let kind = "ReplicaSet";
-
-        match get_names(&client, kind, namespace).await {
-            Ok(resources) => {
-                assert!(!resources.is_empty());
-                println!("Found {} ReplicaSets", resources.len());
-                for resource in resources {
-                    println!("ReplicaSet: {}", resource.name);
-                }
-            }
-            Err(e) => {
-                eprintln!("Error fetching {kind}: {e}");
-                panic!("Failed to fetch {kind}");
-            }
-        }
+    async fn test_list_pods() {
+        let pods = names("Pod").await;
+        assert_eq!(pods.len(), 10);
+        assert!(pods.contains(&"cilium-z5pm7".to_string()));
     }
 
     #[tokio::test]
-    async fn test_get_names_services() {
-        let client = Client::try_default()
-            .await
-            .expect("Failed to create client");
-        let namespace = "kube-system";
-        let kind = "Service";
-
-        match get_names(&client, kind, namespace).await {
-            Ok(resources) => {
-                assert!(!resources.is_empty());
-                println!("Found {} Services", resources.len());
-                for resource in resources {
-                    println!("Service: {}", resource.name);
-                }
-            }
-            Err(e) => {
-                eprintln!("Error fetching Services: {}", e);
-                panic!("Failed to fetch Services");
-            }
-        }
+    async fn test_list_replicasets() {
+        assert_eq!(names("ReplicaSet").await.len(), 3);
     }
 
     #[tokio::test]
-    async fn test_get_names_configmaps() {
-        let client = Client::try_default()
+    async fn test_list_services() {
+        assert_eq!(
+            names("Service").await,
+            vec!["hubble-peer", "hubble-relay", "hubble-ui", "kube-dns"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_configmaps() {
+        assert_eq!(names("ConfigMap").await, vec!["coredns"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_cluster_objects() {
+        let server = fake_cluster().await;
+        let client = server.client();
+        let kinds = discover_kinds(&client, &KindFilter::default())
             .await
-            .expect("Failed to create client");
-        let namespace = "kube-system";
-        let kind = "ConfigMap";
-
-        match get_names(&client, kind, namespace).await {
-            Ok(resources) => {
-                assert!(!resources.is_empty());
-                println!("Found {} ConfigMaps", resources.len());
-                for resource in resources {
-                    println!("ConfigMap: {}", resource.name);
-                }
-            }
-            Err(e) => {
-                eprintln!("Error fetching ConfigMaps: {}", e);
-                panic!("Failed to fetch ConfigMaps");
-            }
-        }
+            .unwrap();
+        let kind_names: Vec<&str> = kinds.iter().map(|k| k.resource.kind.as_str()).collect();
+        assert!(kind_names.starts_with(&["Deployment", "StatefulSet", "DaemonSet"]));
+        assert!(kind_names.contains(&"StorageClass"));
+
+        let scope = ClusterScope {
+            selector: Some("k8s-app=cilium".to_string()),
+            ..Default::default()
+        };
+        let objects = list_cluster_objects(&client, &kinds, &scope, &[])
+            .await
+            .unwrap();
+        assert_eq!(objects.namespaces, vec!["default", "kube-system"]);
+        let pods = objects.resources("Pod", "kube-system");
+        assert!(!pods.is_empty());
+        assert!(
+            pods.iter()
+                .all(|pod| pod.labels.get("k8s-app").map(String::as_str) == Some("cilium"))
+        );
+        assert!(objects.resources("ConfigMap", "kube-system").is_empty());
     }
 }
@@ -1,11 +1,32 @@
 use bevy::prelude::*;
 
-use super::k8s_integration_systems::init_k8s_live_floorplan_publisher;
+use clap::Parser;
+
+use super::{
+    k8s_integration_systems::{
+        LiveGrouping, LiveSettings, forward_grouping, init_k8s_live_floorplan_publisher,
+    },
+    k8s_logs::stream_requested_logs,
+    k8s_mutations::apply_requested_mutations,
+};
+use crate::{
+    cli::Cli, integration::integration_component::RoomGrouping, logs::logs_component::LogSource,
+    mutation::mutation_component::MutationSource,
+};
 
 pub struct K8sIntegrationPlugin;
 
 impl Plugin for K8sIntegrationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, init_k8s_live_floorplan_publisher);
+        app.insert_resource(LiveSettings::from_cli(&Cli::parse()))
+            .insert_resource(LiveGrouping::default())
+            .insert_resource(LogSource)
+            .insert_resource(MutationSource)
+            .add_systems(Startup, init_k8s_live_floorplan_publisher)
+            .add_systems(Update, (stream_requested_logs, apply_requested_mutations))
+            .add_systems(
+                Update,
+                forward_grouping.run_if(resource_changed::<RoomGrouping>),
+            );
     }
 }
@@ -1,39 +1,127 @@
-use super::k8s_api::get_names;
-use crate::cli::Cli;
-use crate::floorplan::{FloorPlan, FloorPlanEvent, FloorPlanResult, Room};
-use crate::integration::integration_utils::IntegrationResource;
-use crate::integration::k8s_file::k8s_integration_systems::connect_rooms_with_doors;
+use super::k8s_access::check_access;
+use super::k8s_api::{
+    ClusterObjects, ClusterScope, KindFilter, classify_error, discover_kinds, list_cluster_objects,
+};
+use super::k8s_multiverse::Multiverse;
+use super::k8s_watch::{WatchSignal, settle, watch_cluster};
+use crate::cli::{Cli, Grouping, Hierarchy};
+use crate::floorplan::{
+    FloorPlan, FloorPlanError, FloorPlanEvent, FloorPlanResult, Room, RoomKind,
+};
+use crate::integration::integration_component::{
+    ConnectionStatus, Retry, RoomEvents, RoomGrouping,
+};
+use crate::integration::integration_utils::{Backoff, add_resource_rooms};
+use crate::integration::k8s_file::k8s_integration_systems::{
+    Suites, add_cluster_hallway, add_cluster_scoped_rooms, add_ingress_routes, connect_owners,
+    connect_references, connect_rooms_with_doors, connect_services_to_pods, to_room_events,
+};
 use bevy::prelude::*;
 use bevy_tokio_tasks::{TaskContext, TokioTasksRuntime};
-use clap::Parser;
 use kube::{
     Client,
-    api::{Api, ListParams},
+    config::{Config, KubeConfigOptions, Kubeconfig},
 };
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::path::PathBuf;
 use std::time::Duration;
+use tokio::sync::{mpsc::UnboundedSender, watch};
 
-async fn create_k8s_client() -> FloorPlanResult<Client> {
-    Client::try_default()
-        .await
-        .map_err(|e| crate::floorplan::FloorPlanError::ServiceError(e.to_string()))
+/// the grouping the watches build floorplans with, kept in step with `RoomGrouping`
+#[derive(Resource)]
+pub struct LiveGrouping(pub watch::Sender<Grouping>);
+
+impl Default for LiveGrouping {
+    fn default() -> Self {
+        Self(watch::Sender::new(Grouping::default()))
+    }
 }
 
-async fn fetch_namespaces(
-    client: &Client,
-) -> FloorPlanResult<Vec<k8s_openapi::api::core::v1::Namespace>> {
-    let namespaces: Api<k8s_openapi::api::core::v1::Namespace> = Api::all(client.clone());
-    let lp = ListParams::default();
-    namespaces
-        .list(&lp)
-        .await
-        .map(|ns_list| ns_list.items)
-        .map_err(|e| crate::floorplan::FloorPlanError::ServiceError(e.to_string()))
+/// every watch rebuilds its floorplan when the player picks another grouping
+pub fn forward_grouping(grouping: Res<RoomGrouping>, live: Res<LiveGrouping>) {
+    live.0
+        .send_if_modified(|current| std::mem::replace(current, grouping.0) != grouping.0);
+}
+
+/**
+* what the live generator, the log panel and cluster actions work with - read from the command
+* line, or made up by a test that hands in a client of its own
+*/
+#[derive(Resource, Clone)]
+pub struct LiveSettings {
+    /// used instead of one made from the kubeconfig, ie: a client of a fake api server
+    pub client: Option<Client>,
+    pub kubeconfig: Option<PathBuf>,
+    /// empty for the current context, more than one for a multiverse
+    pub contexts: Vec<String>,
+    pub filter: KindFilter,
+    pub scope: ClusterScope,
+    pub hierarchy: Hierarchy,
+    pub debounce: Duration,
+    pub max_delay: Duration,
+}
+
+impl LiveSettings {
+    pub fn from_cli(cli: &Cli) -> Self {
+        Self {
+            client: None,
+            kubeconfig: cli.kubeconfig.clone(),
+            contexts: cli.contexts.clone(),
+            filter: KindFilter::from_cli(cli),
+            scope: ClusterScope::from_cli(cli),
+            hierarchy: cli.hierarchy.unwrap_or_default(),
+            debounce: Duration::from_millis(cli.watch_debounce_millis.unwrap_or(500)),
+            max_delay: Duration::from_secs(cli.generator_poll_secs.unwrap_or(60).into()),
+        }
+    }
+
+    /// a client for one of the contexts, `None` for the first - or the current one if none was given
+    pub async fn client(&self, context: Option<&str>) -> FloorPlanResult<Client> {
+        match &self.client {
+            Some(client) => Ok(client.clone()),
+            None => {
+                let context = context.or(self.contexts.first().map(String::as_str));
+                create_k8s_client(self.kubeconfig.as_ref(), context.map(ToString::to_string)).await
+            }
+        }
+    }
+}
+
+/**
+* the default client unless a kubeconfig file or context was asked for - the default also covers
+* running inside a pod
+*/
+async fn create_k8s_client(
+    kubeconfig: Option<&PathBuf>,
+    context: Option<String>,
+) -> FloorPlanResult<Client> {
+    if kubeconfig.is_none() && context.is_none() {
+        return Client::try_default().await.map_err(|e| classify_error(&e));
+    }
+
+    let options = KubeConfigOptions {
+        context,
+        ..Default::default()
+    };
+    let config = match kubeconfig {
+        Some(path) => {
+            let kubeconfig = Kubeconfig::read_from(path)
+                .map_err(|e| FloorPlanError::ServiceError(e.to_string()))?;
+            Config::from_custom_kubeconfig(kubeconfig, &options).await
+        }
+        None => Config::from_kubeconfig(&options).await,
+    }
+    .map_err(|e| FloorPlanError::ServiceError(e.to_string()))?;
+    Client::try_from(config).map_err(|e| classify_error(&e))
 }
 
 fn create_cluster_room() -> Room {
     Room {
         id: "cluster".to_string(),
         name: "Cluster Lobby".to_string(),
+        kind: RoomKind::Cluster,
+        ..Default::default()
     }
 }
 
@@ -41,29 +129,76 @@ fn create_cluster_room() -> Room {
 * for a given namespace, walk through the resources and create rooms for each and add them to the
 * floorplan
 */
-async fn process_namespace(
+fn process_namespace(
     floorplan: &mut FloorPlan,
-    cluster_room: &Room,
+    parent_room: &Room,
     door_id: &mut usize,
-    client: &Client,
+    objects: &ClusterObjects,
     namespace: &str,
+    grouping: Grouping,
 ) -> FloorPlanResult<()> {
     debug!("processing namespace {namespace}");
     let namespace_room = create_namespace_room(namespace);
     floorplan.add_room(namespace_room.clone());
-    connect_rooms_with_doors(floorplan, cluster_room, &namespace_room, door_id)?;
-
-    for kind in &[
-        "Deployment",
-        "DaemonSet",
-        "ReplicaSet",
-        "Service",
-        "ConfigMap",
-        "Ingress",
-        "Pod",
-    ] {
-        setup_hallway_and_rooms(floorplan, namespace, &namespace_room, door_id, kind, client)
-            .await?;
+    connect_rooms_with_doors(floorplan, parent_room, &namespace_room, door_id)?;
+
+    let hallway_kinds = objects.hallway_kinds(namespace);
+    let suites = Suites::new(
+        grouping,
+        namespace,
+        &hallway_kinds
+            .iter()
+            .flat_map(|kind| objects.resources(kind, namespace))
+            .collect::<Vec<_>>(),
+    );
+    suites.add_rooms(floorplan, &namespace_room, door_id)?;
+    for kind in &hallway_kinds {
+        setup_hallway_and_rooms(
+            floorplan,
+            &suites,
+            namespace,
+            &namespace_room,
+            door_id,
+            kind,
+            objects,
+        )?;
+    }
+    for kind in &hallway_kinds {
+        connect_owners(
+            floorplan,
+            namespace,
+            &objects.resources(kind, namespace),
+            door_id,
+        )?;
+    }
+
+    // without pods in the walk every service would look like it selects nothing
+    if objects
+        .kinds
+        .iter()
+        .any(|selected| selected.resource.kind == "Pod")
+    {
+        connect_services_to_pods(
+            floorplan,
+            namespace,
+            &objects.resources("Service", namespace),
+            &objects.resources("Pod", namespace),
+            door_id,
+        )?;
+    }
+    add_ingress_routes(
+        floorplan,
+        namespace,
+        &objects.resources("Ingress", namespace),
+        door_id,
+    )?;
+    for kind in ["Pod", "PersistentVolumeClaim"] {
+        connect_references(
+            floorplan,
+            namespace,
+            &objects.resources(kind, namespace),
+            door_id,
+        )?;
     }
 
     Ok(())
@@ -73,23 +208,51 @@ fn create_namespace_room(namespace: &str) -> Room {
     Room {
         id: namespace.to_string(),
         name: format!("{namespace} NS Hallway"),
+        kind: RoomKind::Namespace,
+        namespace: Some(namespace.to_string()),
+        ..Default::default()
     }
 }
 
-async fn setup_hallway_and_rooms(
+fn setup_hallway_and_rooms(
     plan: &mut FloorPlan,
+    suites: &Suites,
     namespace: &str,
     outer_room: &Room,
     door_id_generator: &mut usize,
     kind: &str,
-    client: &Client,
+    objects: &ClusterObjects,
 ) -> FloorPlanResult<()> {
     debug!("Setting up {kind} hallway and rooms");
-    let hallway = create_hallway_room(namespace, kind);
+    let locked = objects.denial(kind, namespace).map(ToString::to_string);
+    let resources = objects.resources(kind, namespace);
+    if locked.is_none() && !suites.needs_hallway(&resources) {
+        return add_resource_rooms(
+            plan,
+            resources,
+            suites,
+            namespace,
+            outer_room,
+            door_id_generator,
+        );
+    }
+    let hallway = Room {
+        locked,
+        ..create_hallway_room(namespace, kind)
+    };
     plan.add_room(hallway.clone());
     connect_rooms_with_doors(plan, outer_room, &hallway, door_id_generator)?;
 
-    add_rooms(plan, client, namespace, &hallway, door_id_generator, kind).await?;
+    if hallway.locked.is_none() {
+        add_resource_rooms(
+            plan,
+            resources,
+            suites,
+            namespace,
+            &hallway,
+            door_id_generator,
+        )?;
+    }
     debug!("Finished setting up {kind} hallway and rooms");
     Ok(())
 }
@@ -98,172 +261,526 @@ fn create_hallway_room(namespace: &str, kind: &str) -> Room {
     Room {
         id: format!("{namespace}-{kind}s"),
         name: format!("{namespace} {kind}s Hallway"),
+        kind: RoomKind::Hallway,
+        namespace: Some(namespace.to_string()),
+        attributes: BTreeMap::from([("kind".to_string(), kind.to_string())]),
+        ..Default::default()
     }
 }
 
-async fn add_rooms(
-    plan: &mut FloorPlan,
+/**
+* lay out the whole cluster - pure and in memory, so it is cheap to call again every time the
+* watched objects change
+*/
+fn build_floorplan(
+    objects: &ClusterObjects,
+    hierarchy: Hierarchy,
+    grouping: Grouping,
+) -> FloorPlanResult<FloorPlan> {
+    let mut floorplan = FloorPlan::new();
+    let cluster_room = create_cluster_room();
+    floorplan.add_room(cluster_room.clone());
+
+    let mut door_id = 0;
+    let namespace_parent = add_cluster_scoped_rooms(
+        &mut floorplan,
+        &cluster_room,
+        hierarchy,
+        &objects.node_names(),
+        &objects.cluster_resources(),
+        &mut door_id,
+    )?;
+    for denial in objects.denials.iter().filter(|denial| {
+        objects
+            .kinds
+            .iter()
+            .any(|selected| !selected.namespaced && selected.resource.kind == denial.kind)
+    }) {
+        let hallway =
+            add_cluster_hallway(&mut floorplan, &cluster_room, &denial.kind, &mut door_id)?;
+        floorplan.get_room_by_id_mut(&hallway.id)?.locked = Some(denial.to_string());
+    }
+    for namespace in &objects.namespaces {
+        process_namespace(
+            &mut floorplan,
+            &namespace_parent,
+            &mut door_id,
+            objects,
+            namespace,
+            grouping,
+        )?;
+    }
+
+    Ok(floorplan)
+}
+
+/// one round of list calls, a floorplan without waiting for any watch to sync
+pub async fn generate(
     client: &Client,
-    namespace: &str,
-    outer_room: &Room,
-    door_id_generator: &mut usize,
-    kind: &str,
-) -> FloorPlanResult<()> {
-    debug!("Adding {kind} rooms");
-    if let Ok(resources) = get_names(client, kind, namespace).await {
-        for r in resources {
-            let room = create_resource_room(namespace, &r);
-            plan.add_room(room.clone());
-            connect_rooms_with_doors(plan, &room, outer_room, door_id_generator)?;
-
-            if let Some(parent) = &r.parent {
-                connect_to_parent_room(plan, namespace, &room, parent, door_id_generator)?;
-            }
+    filter: &KindFilter,
+    scope: &ClusterScope,
+    hierarchy: Hierarchy,
+    grouping: Grouping,
+) -> FloorPlanResult<FloorPlan> {
+    let kinds = discover_kinds(client, filter).await?;
+    let denials = check_access(client, &kinds, scope).await;
+    let objects = list_cluster_objects(client, &kinds, scope, &denials).await?;
+    build_floorplan(&objects, hierarchy, grouping)
+}
+
+async fn publish_floorplan(ctx: &mut TaskContext, floorplan: FloorPlan) -> FloorPlanResult<()> {
+    ctx.run_on_main_thread(move |ctx| {
+        let mut events = ctx
+            .world
+            .get_resource_mut::<Events<FloorPlanEvent>>()
+            .ok_or(FloorPlanError::EventChannelMissing)?;
+        events.send(FloorPlanEvent { floorplan });
+        debug!("...Generated new floorplan");
+        Ok(())
+    })
+    .await
+}
 
-            add_container_rooms(plan, namespace, &r, &room, door_id_generator)?;
+async fn publish_room_events(ctx: &mut TaskContext, room_events: RoomEvents) {
+    ctx.run_on_main_thread(move |ctx| {
+        if let Some(mut current) = ctx.world.get_resource_mut::<RoomEvents>() {
+            current.set_if_neq(room_events);
         }
-        debug!("Finished adding {kind} rooms");
-    }
-    Ok(())
+    })
+    .await;
 }
 
-fn create_resource_room(namespace: &str, r: &IntegrationResource) -> Room {
-    Room {
-        id: format!("{namespace}-{}-{}", r.kind, r.name),
-        name: format!("{} {}", r.kind, r.name),
-    }
+async fn report_status(ctx: &mut TaskContext, status: ConnectionStatus) {
+    ctx.run_on_main_thread(move |ctx| {
+        if let Some(mut current) = ctx.world.get_resource_mut::<ConnectionStatus>() {
+            current.set_if_neq(status);
+        }
+    })
+    .await;
 }
 
-fn connect_to_parent_room(
-    plan: &mut FloorPlan,
-    namespace: &str,
-    room: &Room,
-    parent: &IntegrationResource,
-    door_id_generator: &mut usize,
-) -> FloorPlanResult<()> {
-    let parent_room_id = format!("{namespace}-{}-{}", parent.kind, parent.name);
-    let cplan = plan.clone();
-    let parent_room = cplan.get_room_by_id(&parent_room_id);
-    if let Ok(parent_room) = parent_room {
-        connect_rooms_with_doors(plan, room, parent_room, door_id_generator)?;
-    } else {
-        debug!("Owner room not found: {parent_room_id}");
-    }
-    Ok(())
+/// what one cluster's watch has to tell the world
+enum LiveUpdate {
+    FloorPlan(FloorPlan),
+    Events(RoomEvents),
+    Status(ConnectionStatus),
 }
 
-fn add_container_rooms(
-    plan: &mut FloorPlan,
-    namespace: &str,
-    r: &IntegrationResource,
-    room: &Room,
-    door_id_generator: &mut usize,
-) -> FloorPlanResult<()> {
-    for container in r.children.clone() {
-        let container_room = create_container_room(namespace, r, &container);
-        plan.add_room(container_room.clone());
-        connect_rooms_with_doors(plan, &container_room, room, door_id_generator)?;
-        add_volume_mount_rooms(
-            plan,
-            namespace,
-            r,
-            &container,
-            &container_room,
-            door_id_generator,
-        )?;
-    }
-    Ok(())
+/// where one cluster's watch sends its updates, tagged with its context
+struct ClusterFeed {
+    context: Option<String>,
+    updates: UnboundedSender<(Option<String>, LiveUpdate)>,
 }
 
-fn create_container_room(
-    namespace: &str,
-    r: &IntegrationResource,
-    container: &IntegrationResource,
-) -> Room {
-    Room {
-        id: format!("{namespace}-{}-{}-{}", r.kind, "container", container.name),
-        name: format!("{} {}", "container", container.name),
+impl ClusterFeed {
+    fn send(&self, update: LiveUpdate) -> FloorPlanResult<()> {
+        self.updates
+            .send((self.context.clone(), update))
+            .map_err(|_| FloorPlanError::EventChannelMissing)
     }
 }
 
-fn add_volume_mount_rooms(
-    plan: &mut FloorPlan,
-    namespace: &str,
-    r: &IntegrationResource,
-    container: &IntegrationResource,
-    container_room: &Room,
-    door_id_generator: &mut usize,
-) -> FloorPlanResult<()> {
-    for volume_mount in container.children.clone() {
-        let volume_mount_room = create_volume_mount_room(namespace, r, container, &volume_mount);
-        plan.add_room(volume_mount_room.clone());
-        connect_rooms_with_doors(plan, &volume_mount_room, container_room, door_id_generator)?;
+/**
+* watch the cluster and publish a floorplan once the initial lists are in, then again after every
+* burst of changes has settled. returns only when it has to start over with a new client - a watch
+* that fails and recovers on its own just shows the banner until it does
+*/
+async fn watch_and_publish(
+    feed: &ClusterFeed,
+    backoff: &mut Backoff,
+    settings: &LiveSettings,
+    grouping: &mut watch::Receiver<Grouping>,
+) -> FloorPlanResult<Infallible> {
+    let LiveSettings {
+        debounce,
+        max_delay,
+        hierarchy,
+        ..
+    } = *settings;
+
+    let client = settings.client(feed.context.as_deref()).await?;
+    // kinds are discovered once per client, a newly installed CRD shows up on the next reconnect
+    let kinds = discover_kinds(&client, &settings.filter).await?;
+    // so are permissions, a role granted later opens its hallways on the next reconnect
+    let denials = check_access(&client, &kinds, &settings.scope).await;
+    let (changes_tx, mut changes) = tokio::sync::mpsc::unbounded_channel();
+    let store = watch_cluster(&client, &kinds, &settings.scope, &denials, &changes_tx);
+    drop(changes_tx);
+
+    let synced = store.wait_until_ready(max_delay).await;
+    // the first floorplan already includes everything the initial lists announced
+    let mut failure = None;
+    while let Ok(signal) = changes.try_recv() {
+        failure = match signal {
+            WatchSignal::Changed => None,
+            WatchSignal::Failed(e) => Some(e),
+        };
+    }
+    if !synced && let Some(e) = failure {
+        // nothing trustworthy to show - keep the last good floorplan and start over
+        return Err(e);
     }
-    Ok(())
-}
 
-fn create_volume_mount_room(
-    namespace: &str,
-    r: &IntegrationResource,
-    container: &IntegrationResource,
-    volume_mount: &IntegrationResource,
-) -> Room {
-    Room {
-        id: format!(
-            "{namespace}-{}-{}-{}-{}",
-            r.kind, "container", container.name, volume_mount.name
-        ),
-        name: format!("{} {}", "volume mount", volume_mount.name),
+    loop {
+        debug!("Generating new floorplan...");
+        feed.send(LiveUpdate::FloorPlan(build_floorplan(
+            &store.objects(),
+            hierarchy,
+            *grouping.borrow_and_update(),
+        )?))?;
+        feed.send(LiveUpdate::Events(to_room_events(store.events())))?;
+        backoff.reset();
+        feed.send(LiveUpdate::Status(ConnectionStatus::default()))?;
+
+        loop {
+            // a new grouping needs no changes to settle, the objects are all in the store
+            let signal = tokio::select! {
+                signal = changes.recv() => signal,
+                Ok(()) = grouping.changed() => break,
+            };
+            match signal {
+                Some(WatchSignal::Changed) => {}
+                Some(WatchSignal::Failed(e)) => {
+                    feed.send(LiveUpdate::Status(ConnectionStatus::failed(
+                        e,
+                        Retry::Automatic,
+                    )))?;
+                    continue;
+                }
+                None => {
+                    return Err(FloorPlanError::ServiceError(
+                        "all cluster watches stopped".to_string(),
+                    ));
+                }
+            }
+            match settle(&mut changes, debounce, max_delay).await {
+                Some(e) => feed.send(LiveUpdate::Status(ConnectionStatus::failed(
+                    e,
+                    Retry::Automatic,
+                )))?,
+                None => break,
+            }
+        }
     }
 }
 
-async fn generate() -> FloorPlanResult<FloorPlan> {
-    let client = create_k8s_client().await?;
-    let ns_list = fetch_namespaces(&client).await?;
-    let mut floorplan = FloorPlan::new();
-    let cluster_room = create_cluster_room();
-    floorplan.add_room(cluster_room.clone());
+/**
+* the first floorplan comes from `generate`, so a context's lobby fills in as soon as its lists are
+* back instead of once every watch has synced - then start over with a new client whenever the
+* watch gives up, until nobody listens anymore
+*/
+async fn watch_forever(
+    feed: ClusterFeed,
+    settings: LiveSettings,
+    mut grouping: watch::Receiver<Grouping>,
+) {
+    let first = async {
+        let client = settings.client(feed.context.as_deref()).await?;
+        let grouping = *grouping.borrow();
+        generate(
+            &client,
+            &settings.filter,
+            &settings.scope,
+            settings.hierarchy,
+            grouping,
+        )
+        .await
+    };
+    match first.await {
+        Ok(floorplan) => {
+            if feed.send(LiveUpdate::FloorPlan(floorplan)).is_err() {
+                return;
+            }
+        }
+        // the watch reports what is wrong, and keeps retrying
+        Err(e) => debug!("No first K8S FloorPlanEvent: {e}"),
+    }
 
-    let mut door_id = 0;
-    for ns in ns_list {
-        if let Some(namespace) = ns.metadata.name {
-            process_namespace(
-                &mut floorplan,
-                &cluster_room,
-                &mut door_id,
-                &client,
-                &namespace,
-            )
-            .await?;
+    let mut backoff = Backoff::new(Duration::from_secs(1), settings.max_delay);
+    loop {
+        let Err(e) = watch_and_publish(&feed, &mut backoff, &settings, &mut grouping).await;
+        if feed.updates.is_closed() {
+            return;
         }
+        let retry_in = backoff.next_delay();
+        let context = feed.context.as_deref().unwrap_or("current context");
+        warn!("No K8S FloorPlanEvent from {context}: {e}, retrying in {retry_in:?}");
+        let status = ConnectionStatus::failed(
+            e,
+            Retry::After {
+                attempt: backoff.attempt(),
+                delay: retry_in,
+            },
+        );
+        if feed.send(LiveUpdate::Status(status)).is_err() {
+            return;
+        }
+        tokio::time::sleep(retry_in).await;
     }
+}
 
-    Ok(floorplan)
+async fn publish(ctx: &mut TaskContext, update: LiveUpdate) -> FloorPlanResult<()> {
+    match update {
+        LiveUpdate::FloorPlan(floorplan) => publish_floorplan(ctx, floorplan).await?,
+        LiveUpdate::Events(room_events) => publish_room_events(ctx, room_events).await,
+        LiveUpdate::Status(status) => report_status(ctx, status).await,
+    }
+    Ok(())
 }
 
-async fn publish_floorplan(ctx: &mut TaskContext) -> FloorPlanResult<()> {
-    let floorplan = generate().await?;
-    ctx.run_on_main_thread(move |ctx| {
-        if let Some(mut events) = ctx.world.get_resource_mut::<Events<FloorPlanEvent>>() {
-            events.send(FloorPlanEvent { floorplan });
-            debug!("...Generated new floorplan");
-        } else {
-            panic!("No FloorPlanEvent resource found");
+/// fold one cluster's update into the multiverse and publish whatever it changed
+async fn publish_merged(
+    ctx: &mut TaskContext,
+    multiverse: &mut Multiverse,
+    context: &str,
+    update: LiveUpdate,
+) -> FloorPlanResult<()> {
+    match update {
+        LiveUpdate::FloorPlan(floorplan) => {
+            multiverse.set_floorplan(context, floorplan);
+            publish_floorplan(ctx, multiverse.floorplan()?).await?;
         }
-    })
-    .await;
+        LiveUpdate::Events(room_events) => {
+            multiverse.set_events(context, room_events);
+            publish_room_events(ctx, multiverse.room_events()).await;
+        }
+        LiveUpdate::Status(status) => {
+            if multiverse.set_status(context, status) {
+                publish_floorplan(ctx, multiverse.floorplan()?).await?;
+            }
+            report_status(ctx, multiverse.status()).await;
+        }
+    }
     Ok(())
 }
 
-pub fn init_k8s_live_floorplan_publisher(runtime: ResMut<TokioTasksRuntime>) {
+/**
+* one watch per kubeconfig context - with several, their floorplans are merged into a multiverse
+* that is published whole whenever any cluster changes
+*/
+pub fn init_k8s_live_floorplan_publisher(
+    runtime: ResMut<TokioTasksRuntime>,
+    settings: Res<LiveSettings>,
+    grouping: Res<RoomGrouping>,
+    live_grouping: Res<LiveGrouping>,
+) {
+    let settings = settings.clone();
+    live_grouping.0.send_replace(grouping.0);
+    let grouping = live_grouping.0.subscribe();
     runtime.spawn_background_task(|mut ctx| async move {
-        loop {
-            if let Err(e) = publish_floorplan(&mut ctx).await {
-                panic!("No K8S FloorPlanEvent: {e:?}");
+        let (updates, mut received) = tokio::sync::mpsc::unbounded_channel();
+        let mut multiverse =
+            (settings.contexts.len() > 1).then(|| Multiverse::new(settings.contexts.clone()));
+        let contexts = match multiverse {
+            Some(_) => settings.contexts.iter().cloned().map(Some).collect(),
+            None => vec![None],
+        };
+        for context in contexts {
+            let feed = ClusterFeed {
+                context,
+                updates: updates.clone(),
+            };
+            tokio::spawn(watch_forever(feed, settings.clone(), grouping.clone()));
+        }
+        drop(updates);
+
+        while let Some((context, update)) = received.recv().await {
+            let published = match (&mut multiverse, context) {
+                (Some(multiverse), Some(context)) => {
+                    publish_merged(&mut ctx, multiverse, &context, update).await
+                }
+                _ => publish(&mut ctx, update).await,
+            };
+            match published {
+                Ok(()) => {}
+                Err(FloorPlanError::EventChannelMissing) => return,
+                Err(e) => warn!("No K8S FloorPlanEvent: {e}"),
             }
-            let generator_poll_secs = Cli::parse().generator_poll_secs.unwrap_or(60);
-            tokio::time::sleep(Duration::from_secs(generator_poll_secs.into())).await;
-            debug!("Generating new floorplan...");
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integration::k8s_live::k8s_mock_server::{MockApiServer, fixture_objects, refusing};
+    use bevy_tokio_tasks::TokioTasksPlugin;
+    use std::time::Instant;
+
+    fn settings(client: Client) -> LiveSettings {
+        LiveSettings {
+            client: Some(client),
+            kubeconfig: None,
+            contexts: Vec::new(),
+            filter: KindFilter::default(),
+            scope: ClusterScope::default(),
+            hierarchy: Hierarchy::Namespace,
+            debounce: Duration::from_millis(50),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+
+    fn assert_kube_system(floorplan: &FloorPlan) {
+        for id in [
+            "kube-system",
+            "kube-system-Pods",
+            "kube-system-Pod-cilium-z5pm7",
+            "kube-system-Deployment-coredns",
+            "kube-system-Service-kube-dns",
+        ] {
+            assert!(floorplan.get_room_by_id(id).is_ok(), "no room {id}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_from_fake_cluster() {
+        let server = MockApiServer::with_objects(fixture_objects(&["assets/k8s.yaml"], "")).await;
+        let settings = settings(server.client());
+        let floorplan = generate(
+            &settings.client(None).await.unwrap(),
+            &settings.filter,
+            &settings.scope,
+            settings.hierarchy,
+            Grouping::Kind,
+        )
+        .await
+        .unwrap();
+        assert_kube_system(&floorplan);
+    }
+
+    #[tokio::test]
+    async fn test_refused_list_locks_the_hallway() {
+        let server = MockApiServer::with_objects_and(
+            fixture_objects(&["assets/k8s.yaml"], ""),
+            refusing(&["pods"]),
+        )
+        .await;
+        let settings = settings(server.client());
+        let floorplan = generate(
+            &settings.client(None).await.unwrap(),
+            &settings.filter,
+            &settings.scope,
+            settings.hierarchy,
+            Grouping::Kind,
+        )
+        .await
+        .unwrap();
+        let pods = floorplan.get_room_by_id("kube-system-Pods").unwrap();
+        assert_eq!(
+            pods.locked.as_deref(),
+            Some(
+                "locked: cannot list pods cluster wide, try --namespace (the role does not allow it)"
+            )
+        );
+        assert!(
+            floorplan
+                .get_room_by_id("kube-system-Pod-cilium-z5pm7")
+                .is_err()
+        );
+        assert!(
+            floorplan
+                .get_room_by_id("kube-system-Deployment-coredns")
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_live_suites_match_the_file_ones() {
+        let server = MockApiServer::with_objects(fixture_objects(&["assets/k8s.yaml"], "")).await;
+        let settings = settings(server.client());
+        let floorplan = generate(
+            &settings.client(None).await.unwrap(),
+            &settings.filter,
+            &settings.scope,
+            settings.hierarchy,
+            Grouping::PartOf,
+        )
+        .await
+        .unwrap();
+        let suite = floorplan
+            .get_room_by_id("kube-system-suite-cilium")
+            .unwrap();
+        assert_eq!(suite.kind, RoomKind::Suite);
+        assert!(
+            floorplan
+                .get_doors_and_connected_rooms("kube-system-Pod-cilium-z5pm7")
+                .unwrap()
+                .into_iter()
+                .any(|(_, room)| room.id == suite.id)
+        );
+    }
+
+    #[derive(Resource, Default)]
+    struct Published(Vec<FloorPlan>);
+
+    fn collect_floorplans(
+        mut events: EventReader<FloorPlanEvent>,
+        mut published: ResMut<Published>,
+    ) {
+        published
+            .0
+            .extend(events.read().map(|event| event.floorplan.clone()));
+    }
+
+    /// the whole watching generator, inside a bevy app, against a fake api server
+    #[test]
+    fn test_live_publisher_with_injected_client() {
+        let server_runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = server_runtime.enter();
+        let server = server_runtime.block_on(MockApiServer::with_objects(fixture_objects(
+            &["assets/k8s.yaml"],
+            "",
+        )));
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, TokioTasksPlugin::default()))
+            .add_event::<FloorPlanEvent>()
+            .insert_resource(ConnectionStatus::default())
+            .insert_resource(RoomEvents::default())
+            .insert_resource(settings(server.client()))
+            .insert_resource(RoomGrouping::default())
+            .insert_resource(LiveGrouping::default())
+            .insert_resource(Published::default())
+            .add_systems(Startup, init_k8s_live_floorplan_publisher)
+            .add_systems(
+                Update,
+                (
+                    forward_grouping.run_if(resource_changed::<RoomGrouping>),
+                    collect_floorplans,
+                ),
+            );
+
+        let has_suite =
+            |floorplan: &FloorPlan| floorplan.get_room_by_id("kube-system-suite-cilium").is_ok();
+        let publish_until = |app: &mut App, done: &dyn Fn(&[FloorPlan]) -> bool| {
+            let started = Instant::now();
+            while !done(&app.world().resource::<Published>().0) {
+                assert!(
+                    started.elapsed() < Duration::from_secs(20),
+                    "no floorplan published"
+                );
+                app.update();
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        };
+        // the first from one round of lists, the next once the watches have synced
+        publish_until(&mut app, &|published| published.len() >= 2);
+        let published = &app.world().resource::<Published>().0;
+        assert_kube_system(&published[0]);
+        assert!(published.iter().all(|floorplan| !has_suite(floorplan)));
+
+        // regrouping rebuilds from the watched objects without waiting for the cluster to change
+        app.world_mut().resource_mut::<RoomGrouping>().0 = Grouping::PartOf;
+        publish_until(&mut app, &|published| published.iter().any(has_suite));
+        assert_eq!(
+            *app.world().resource::<ConnectionStatus>(),
+            ConnectionStatus::default()
+        );
+        let requests = server.requests();
+        assert!(
+            requests
+                .iter()
+                .any(|request| request.path.contains("watch=true"))
+        );
+    }
+}
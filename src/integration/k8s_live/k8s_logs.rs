@@ -0,0 +1,176 @@
+use super::k8s_api::classify_error;
+use super::k8s_integration_systems::LiveSettings;
+use crate::floorplan::{FloorPlanError, FloorPlanResult};
+use crate::logs::logs_component::{LogOptions, LogPanel, LogRequest, LogTarget};
+use bevy::prelude::*;
+use bevy_tokio_tasks::{TaskContext, TokioTasksRuntime};
+use futures::{AsyncBufReadExt, Stream, StreamExt};
+use k8s_openapi::api::core::v1::Pod;
+use kube::{
+    Client,
+    api::{Api, LogParams},
+};
+use std::time::Duration;
+
+/// how often a quiet followed stream checks whether its lines are still wanted
+const STILL_WANTED_CHECK: Duration = Duration::from_secs(1);
+/// lines that arrive together are handed to the panel together
+const LINES_PER_DELIVERY: usize = 64;
+
+/**
+* the log lines of one container - the stream ends with the log, unless `follow` keeps it open
+* for as long as the container runs
+*/
+pub async fn log_lines(
+    client: Client,
+    target: &LogTarget,
+    options: LogOptions,
+) -> FloorPlanResult<impl Stream<Item = FloorPlanResult<String>> + use<>> {
+    let api: Api<Pod> = Api::namespaced(client, &target.namespace);
+    let params = LogParams {
+        container: Some(target.container.clone()),
+        follow: options.follow,
+        previous: options.previous,
+        tail_lines: i64::try_from(options.lines).ok(),
+        ..Default::default()
+    };
+    let reader = api
+        .log_stream(&target.pod, &params)
+        .await
+        .map_err(|e| classify_error(&e))?;
+    Ok(reader
+        .lines()
+        .map(|line| line.map_err(|e| FloorPlanError::ConnectionError(e.to_string()))))
+}
+
+/**
+* false once the panel has moved on to another request, or was closed - a keepalive with no lines
+* only asks, so the panel is not marked changed for nothing
+*/
+async fn deliver(ctx: &mut TaskContext, generation: u64, lines: Vec<String>) -> bool {
+    ctx.run_on_main_thread(move |ctx| {
+        if lines.is_empty() {
+            return ctx
+                .world
+                .get_resource::<LogPanel>()
+                .is_some_and(|panel| panel.wants(generation));
+        }
+        ctx.world
+            .get_resource_mut::<LogPanel>()
+            .is_some_and(|mut panel| panel.push_lines(generation, lines))
+    })
+    .await
+}
+
+async fn stream_to_panel(
+    ctx: &mut TaskContext,
+    settings: &LiveSettings,
+    request: &LogRequest,
+) -> FloorPlanResult<()> {
+    let client = settings.client(request.target.context.as_deref()).await?;
+    let lines = log_lines(client, &request.target, request.options).await?;
+    let mut chunks = std::pin::pin!(lines.ready_chunks(LINES_PER_DELIVERY));
+    loop {
+        let lines = match tokio::time::timeout(STILL_WANTED_CHECK, chunks.next()).await {
+            Ok(Some(chunk)) => chunk.into_iter().collect::<FloorPlanResult<Vec<_>>>()?,
+            Ok(None) => return Ok(()),
+            Err(_) => Vec::new(),
+        };
+        if !deliver(ctx, request.generation, lines).await {
+            debug!("Log panel moved on, stopping the stream");
+            return Ok(());
+        }
+    }
+}
+
+/// each request streams on the tokio runtime, so a slow or endless log never holds up a frame
+pub fn stream_requested_logs(
+    runtime: ResMut<TokioTasksRuntime>,
+    settings: Res<LiveSettings>,
+    mut requests: EventReader<LogRequest>,
+) {
+    for request in requests.read() {
+        let request = request.clone();
+        let settings = settings.clone();
+        runtime.spawn_background_task(move |mut ctx| async move {
+            let status = stream_to_panel(&mut ctx, &settings, &request)
+                .await
+                .err()
+                .map(|e| e.to_string());
+            ctx.run_on_main_thread(move |ctx| {
+                if let Some(mut panel) = ctx.world.get_resource_mut::<LogPanel>() {
+                    panel.finish(request.generation, status);
+                }
+            })
+            .await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integration::k8s_live::k8s_mock_server::{MockApiServer, status_body};
+
+    fn target() -> LogTarget {
+        LogTarget {
+            namespace: "shop".to_string(),
+            pod: "web-1".to_string(),
+            container: "api".to_string(),
+            context: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_log_lines() {
+        let server = MockApiServer::start(|request| {
+            if request
+                .path
+                .starts_with("/api/v1/namespaces/shop/pods/web-1/log?")
+            {
+                (200, "starting\nlistening on :8080\n".to_string())
+            } else {
+                (404, status_body(404, "NotFound", "no such pod"))
+            }
+        })
+        .await;
+
+        let options = LogOptions {
+            follow: false,
+            previous: true,
+            lines: 2,
+        };
+        let lines: Vec<String> = log_lines(server.client(), &target(), options)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(lines, vec!["starting", "listening on :8080"]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        for param in ["container=api", "previous=true", "tailLines=2"] {
+            assert!(
+                requests[0].path.contains(param),
+                "{} lacks {param}",
+                requests[0].path
+            );
+        }
+        assert_eq!(requests[0].method, "GET");
+        assert!(!requests[0].path.contains("follow=true"));
+    }
+
+    #[tokio::test]
+    async fn test_log_lines_of_a_missing_pod() {
+        let server = MockApiServer::start(|_| {
+            (
+                404,
+                status_body(404, "NotFound", "pods \"web-1\" not found"),
+            )
+        })
+        .await;
+        let result = log_lines(server.client(), &target(), LogOptions::default()).await;
+        assert!(matches!(result, Err(FloorPlanError::ServiceError(_))));
+    }
+}
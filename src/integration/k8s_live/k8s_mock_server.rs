@@ -0,0 +1,476 @@
+use crate::integration::k8s_file::k8s_manifest::{load_manifests, parse_manifest};
+use kube::{Client, Config};
+use std::sync::{Arc, Mutex};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+/// one request as the mock saw it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockRequest {
+    pub method: String,
+    /// the path and query, ie: /api/v1/namespaces/default/pods/web/log?container=api
+    pub path: String,
+    pub body: String,
+}
+
+/// what the mock answers a request with
+pub type Route = dyn Fn(&MockRequest) -> (u16, String) + Send + Sync;
+
+/**
+* a kubernetes api server on a local port that answers each request from `route` - just enough
+* http for a `kube::Client` pointed at it. every request is recorded so tests can check what was
+* asked for
+*/
+pub struct MockApiServer {
+    url: String,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for MockApiServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn header_end(request: &[u8]) -> Option<usize> {
+    request
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map(|position| position + 4)
+}
+
+/// none once the client has nothing more to say
+async fn read_more(stream: &mut TcpStream, request: &mut Vec<u8>) -> Option<()> {
+    let mut buffer = [0; 4096];
+    let read = stream.read(&mut buffer).await.ok()?;
+    request.extend_from_slice(&buffer[..read]);
+    (read > 0).then_some(())
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<MockRequest> {
+    let mut request = Vec::new();
+    let body_start = loop {
+        if let Some(end) = header_end(&request) {
+            break end;
+        }
+        read_more(stream, &mut request).await?;
+    };
+    let head = String::from_utf8_lossy(&request[..body_start]).to_string();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    while request.len() < body_start + content_length {
+        read_more(stream, &mut request).await?;
+    }
+    // ie: PATCH /apis/apps/v1/namespaces/shop/deployments/web?dryRun=All HTTP/1.1
+    let mut request_line = head.lines().next()?.split_whitespace();
+    Some(MockRequest {
+        method: request_line.next()?.to_string(),
+        path: request_line.next()?.to_string(),
+        body: String::from_utf8_lossy(&request[body_start..]).to_string(),
+    })
+}
+
+async fn answer(mut stream: TcpStream, route: Arc<Route>, requests: Arc<Mutex<Vec<MockRequest>>>) {
+    let Some(request) = read_request(&mut stream).await else {
+        return;
+    };
+    requests.lock().unwrap().push(request.clone());
+    let (status, body) = route(&request);
+    if status == 200 && body.is_empty() && request.path.contains("watch=true") {
+        // a watch with nothing to report stays open, as it does on a real api server
+        let _ = stream
+            .write_all(
+                b"HTTP/1.1 200 Mock\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n",
+            )
+            .await;
+        std::future::pending::<()>().await;
+    }
+    let response = format!(
+        "HTTP/1.1 {status} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+impl MockApiServer {
+    pub async fn start(
+        route: impl Fn(&MockRequest) -> (u16, String) + Send + Sync + 'static,
+    ) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let route: Arc<Route> = Arc::new(route);
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(answer(stream, route.clone(), recorded.clone()));
+            }
+        });
+        Self {
+            url,
+            requests,
+            task,
+        }
+    }
+
+    pub fn client(&self) -> Client {
+        Client::try_from(Config::new(self.url.parse().unwrap())).unwrap()
+    }
+
+    /// every request so far, in the order they arrived
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// a kubernetes `Status` body, as the api server sends with an error
+pub fn status_body(code: u16, reason: &str, message: &str) -> String {
+    serde_json::json!({
+        "kind": "Status",
+        "apiVersion": "v1",
+        "metadata": {},
+        "status": "Failure",
+        "message": message,
+        "reason": reason,
+        "code": code
+    })
+    .to_string()
+}
+
+/// what the fake serves: kind, group, version, plural and whether it lives in a namespace
+const FAKE_KINDS: [(&str, &str, &str, &str, bool); 18] = [
+    ("Namespace", "", "v1", "namespaces", false),
+    ("Node", "", "v1", "nodes", false),
+    ("PersistentVolume", "", "v1", "persistentvolumes", false),
+    ("Pod", "", "v1", "pods", true),
+    ("Service", "", "v1", "services", true),
+    ("ConfigMap", "", "v1", "configmaps", true),
+    ("Secret", "", "v1", "secrets", true),
+    (
+        "PersistentVolumeClaim",
+        "",
+        "v1",
+        "persistentvolumeclaims",
+        true,
+    ),
+    ("Event", "", "v1", "events", true),
+    ("Deployment", "apps", "v1", "deployments", true),
+    ("ReplicaSet", "apps", "v1", "replicasets", true),
+    ("DaemonSet", "apps", "v1", "daemonsets", true),
+    ("StatefulSet", "apps", "v1", "statefulsets", true),
+    ("Job", "batch", "v1", "jobs", true),
+    ("CronJob", "batch", "v1", "cronjobs", true),
+    ("Ingress", "networking.k8s.io", "v1", "ingresses", true),
+    (
+        "StorageClass",
+        "storage.k8s.io",
+        "v1",
+        "storageclasses",
+        false,
+    ),
+    (
+        "IngressClass",
+        "networking.k8s.io",
+        "v1",
+        "ingressclasses",
+        false,
+    ),
+];
+
+fn group_version(group: &str, version: &str) -> String {
+    if group.is_empty() {
+        version.to_string()
+    } else {
+        format!("{group}/{version}")
+    }
+}
+
+/// the `/apis` group list, every group at its only version
+fn api_groups() -> serde_json::Value {
+    let mut groups: Vec<(&str, &str)> = FAKE_KINDS
+        .iter()
+        .filter(|(_, group, ..)| !group.is_empty())
+        .map(|(_, group, version, ..)| (*group, *version))
+        .collect();
+    groups.sort_unstable();
+    groups.dedup();
+    let groups: Vec<serde_json::Value> = groups
+        .into_iter()
+        .map(|(group, version)| {
+            let version = serde_json::json!({
+                "groupVersion": group_version(group, version),
+                "version": version
+            });
+            serde_json::json!({
+                "name": group,
+                "versions": [version.clone()],
+                "preferredVersion": version
+            })
+        })
+        .collect();
+    serde_json::json!({"kind": "APIGroupList", "apiVersion": "v1", "groups": groups})
+}
+
+/// the kinds one group version serves, none when it serves nothing
+fn api_resources(group: &str, version: &str) -> Option<serde_json::Value> {
+    let resources: Vec<serde_json::Value> = FAKE_KINDS
+        .iter()
+        .filter(|(_, g, v, ..)| *g == group && *v == version)
+        .map(|(kind, _, _, plural, namespaced)| {
+            serde_json::json!({
+                "name": plural,
+                "singularName": kind.to_lowercase(),
+                "namespaced": namespaced,
+                "kind": kind,
+                "verbs": ["create", "delete", "get", "list", "patch", "update", "watch"]
+            })
+        })
+        .collect();
+    (!resources.is_empty()).then(|| {
+        serde_json::json!({
+            "kind": "APIResourceList",
+            "groupVersion": group_version(group, version),
+            "resources": resources
+        })
+    })
+}
+
+/// just enough percent decoding for label selectors, ie: `app%3Dweb%2Ctier%3Dfront`
+fn percent_decode(value: &str) -> String {
+    let mut decoded = Vec::new();
+    let mut bytes = value.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'%' => {
+                let hex: String = bytes.by_ref().take(2).map(char::from).collect();
+                decoded.push(u8::from_str_radix(&hex, 16).unwrap_or(b'?'));
+            }
+            b'+' => decoded.push(b' '),
+            _ => decoded.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// equality selectors only, ie: `app=web,tier=front`
+fn selects(selector: &str, object: &serde_json::Value) -> bool {
+    selector
+        .split(',')
+        .filter(|term| !term.is_empty())
+        .all(|term| {
+            term.split_once('=').is_some_and(|(key, value)| {
+                object["metadata"]["labels"][key] == value.trim_start_matches('=')
+            })
+        })
+}
+
+/**
+* the namespace, plural and name a request is about - for `/api/v1/namespaces/kube-system/pods`
+* that is `(Some("kube-system"), "pods", None)`
+*/
+fn resource_path(path: &str) -> Option<(&str, Option<&str>, &str, Option<&str>)> {
+    let rest = path
+        .strip_prefix("/api/v1/")
+        .map(|rest| ("", rest))
+        .or_else(|| {
+            let rest = path.strip_prefix("/apis/")?;
+            let mut parts = rest.splitn(3, '/');
+            let group = parts.next()?;
+            let _version = parts.next()?;
+            Some((group, parts.next()?))
+        })?;
+    let (group, rest) = rest;
+    let parts: Vec<&str> = rest.split('/').collect();
+    match parts.as_slice() {
+        ["namespaces", namespace, plural] => Some((group, Some(*namespace), plural, None)),
+        ["namespaces", namespace, plural, name] => {
+            Some((group, Some(*namespace), plural, Some(*name)))
+        }
+        [plural] => Some((group, None, plural, None)),
+        [plural, name] => Some((group, None, plural, Some(*name))),
+        _ => None,
+    }
+}
+
+/**
+* a read only cluster made of `objects` - discovery, lists and gets by namespace and label
+* selector, watches that stay open and report nothing, and access reviews that allow everything.
+* namespaces are those of the objects, so a fixture needs no namespace objects of its own
+*/
+fn fake_route(objects: &[serde_json::Value], request: &MockRequest) -> (u16, String) {
+    let (path, query) = request
+        .path
+        .split_once('?')
+        .unwrap_or((request.path.as_str(), ""));
+    let param = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| percent_decode(value))
+    };
+    let not_found = || {
+        (
+            404,
+            status_body(404, "NotFound", &format!("{path} not found")),
+        )
+    };
+
+    if request.method == "POST" && path.ends_with("/selfsubjectaccessreviews") {
+        let mut review: serde_json::Value = serde_json::from_str(&request.body).unwrap_or_default();
+        review["status"] = serde_json::json!({"allowed": true});
+        return (201, review.to_string());
+    }
+    if request.method != "GET" {
+        return (
+            405,
+            status_body(405, "MethodNotAllowed", "the fake is read only"),
+        );
+    }
+    if param("watch").as_deref() == Some("true") {
+        return (200, String::new());
+    }
+    match path {
+        "/api" => {
+            return (
+                200,
+                serde_json::json!({"kind": "APIVersions", "versions": ["v1"]}).to_string(),
+            );
+        }
+        "/apis" => return (200, api_groups().to_string()),
+        "/api/v1" => {
+            return api_resources("", "v1").map_or_else(not_found, |r| (200, r.to_string()));
+        }
+        _ => {}
+    }
+    if let Some(group_version) = path.strip_prefix("/apis/")
+        && let Some((group, version)) = group_version.split_once('/')
+        && !version.contains('/')
+    {
+        return api_resources(group, version).map_or_else(not_found, |r| (200, r.to_string()));
+    }
+
+    let Some((group, namespace, plural, name)) = resource_path(path) else {
+        return not_found();
+    };
+    let Some((kind, ..)) = FAKE_KINDS
+        .iter()
+        .find(|(_, g, _, p, _)| *g == group && *p == plural)
+    else {
+        return not_found();
+    };
+    let items: Vec<serde_json::Value> = if *kind == "Namespace" {
+        let mut names: Vec<&str> = objects
+            .iter()
+            .filter_map(|object| object["metadata"]["namespace"].as_str())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+            .into_iter()
+            .map(|name| {
+                serde_json::json!({
+                    "apiVersion": "v1",
+                    "kind": "Namespace",
+                    "metadata": {"name": name}
+                })
+            })
+            .collect()
+    } else {
+        objects
+            .iter()
+            .filter(|object| object["kind"] == *kind)
+            .filter(|object| {
+                namespace.is_none_or(|namespace| object["metadata"]["namespace"] == namespace)
+            })
+            .filter(|object| {
+                param("labelSelector").is_none_or(|selector| selects(&selector, object))
+            })
+            .cloned()
+            .collect()
+    };
+    match name {
+        Some(name) => items
+            .into_iter()
+            .find(|object| object["metadata"]["name"] == name)
+            .map_or_else(not_found, |object| (200, object.to_string())),
+        None => (
+            200,
+            serde_json::json!({
+                "kind": format!("{kind}List"),
+                "apiVersion": "v1",
+                "metadata": {"resourceVersion": "1"},
+                "items": items
+            })
+            .to_string(),
+        ),
+    }
+}
+
+impl MockApiServer {
+    /// a fake api server holding `objects`, ie: the items of `load_manifests`
+    pub async fn with_objects(objects: Vec<serde_json::Value>) -> Self {
+        Self::start(move |request| fake_route(&objects, request)).await
+    }
+
+    /// as `with_objects`, but `answer` has the first say - ie: to refuse a kind or fail the reviews
+    pub async fn with_objects_and(
+        objects: Vec<serde_json::Value>,
+        answer: impl Fn(&MockRequest) -> Option<(u16, String)> + Send + Sync + 'static,
+    ) -> Self {
+        Self::start(move |request| answer(request).unwrap_or_else(|| fake_route(&objects, request)))
+            .await
+    }
+}
+
+/**
+* access reviews that fail with a 500, and `plurals` that cannot be listed or watched - what a
+* cluster looks like to a user whose role the reviews cannot vouch for
+*/
+pub fn refusing(
+    plurals: &'static [&'static str],
+) -> impl Fn(&MockRequest) -> Option<(u16, String)> {
+    move |request| {
+        let path = request.path.split('?').next().unwrap_or_default();
+        if path.ends_with("/selfsubjectaccessreviews") {
+            Some((
+                500,
+                status_body(500, "InternalError", "the authorizer is down"),
+            ))
+        } else if plurals
+            .iter()
+            .any(|plural| path.ends_with(&format!("/{plural}")))
+        {
+            Some((
+                403,
+                status_body(403, "Forbidden", "the role does not allow it"),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// the objects of fixture files and of inline yaml, as the fake serves them
+pub fn fixture_objects(paths: &[&str], yaml: &str) -> Vec<serde_json::Value> {
+    let patterns: Vec<String> = paths.iter().map(ToString::to_string).collect();
+    let mut objects = if patterns.is_empty() {
+        Vec::new()
+    } else {
+        load_manifests(&patterns).unwrap()["items"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+    };
+    objects.extend(parse_manifest(yaml, false).unwrap());
+    objects
+}
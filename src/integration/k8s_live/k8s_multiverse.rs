@@ -0,0 +1,223 @@
+use crate::floorplan::{FloorPlan, FloorPlanError, FloorPlanResult, Room, RoomKind};
+use crate::integration::integration_component::{ConnectionStatus, RoomEvents};
+use crate::integration::k8s_file::k8s_integration_systems::connect_rooms_with_doors;
+use std::collections::HashMap;
+
+pub const MULTIVERSE_ROOM_ID: &str = "multiverse";
+
+/// the same error, saying which cluster it came from
+fn in_context(error: &FloorPlanError, context: &str) -> FloorPlanError {
+    let prefixed = |msg: &str| format!("{context}: {msg}");
+    match error {
+        FloorPlanError::RoomNotFound(msg) => FloorPlanError::RoomNotFound(prefixed(msg)),
+        FloorPlanError::DoorNotFound(msg) => FloorPlanError::DoorNotFound(prefixed(msg)),
+        FloorPlanError::PathNotFound(msg) => FloorPlanError::PathNotFound(prefixed(msg)),
+        FloorPlanError::ServiceError(msg) => FloorPlanError::ServiceError(prefixed(msg)),
+        FloorPlanError::SnapshotError(msg) => FloorPlanError::SnapshotError(prefixed(msg)),
+        FloorPlanError::ConnectionError(msg) => FloorPlanError::ConnectionError(prefixed(msg)),
+        FloorPlanError::AuthError(msg) => FloorPlanError::AuthError(prefixed(msg)),
+        FloorPlanError::ManifestError(msg) => FloorPlanError::ManifestError(prefixed(msg)),
+        FloorPlanError::EventChannelMissing => FloorPlanError::EventChannelMissing,
+    }
+}
+
+/**
+* the latest of everything each cluster's watch reported, merged into one world - a Multiverse room
+* with a door to every cluster lobby, room ids prefixed with their context. a cluster with nothing
+* to show yet gets a locked lobby saying why
+*/
+#[derive(Debug, Clone, Default)]
+pub struct Multiverse {
+    contexts: Vec<String>,
+    floorplans: HashMap<String, FloorPlan>,
+    events: HashMap<String, RoomEvents>,
+    statuses: HashMap<String, ConnectionStatus>,
+}
+
+impl Multiverse {
+    pub fn new(contexts: Vec<String>) -> Self {
+        Self {
+            contexts,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_floorplan(&mut self, context: &str, floorplan: FloorPlan) {
+        self.floorplans.insert(context.to_string(), floorplan);
+    }
+
+    pub fn set_events(&mut self, context: &str, events: RoomEvents) {
+        self.events.insert(context.to_string(), events);
+    }
+
+    /// true when it changes the world too, ie: a cluster that never connected has a new reason
+    pub fn set_status(&mut self, context: &str, status: ConnectionStatus) -> bool {
+        self.statuses.insert(context.to_string(), status);
+        !self.floorplans.contains_key(context)
+    }
+
+    fn placeholder_lobby(&self, context: &str) -> Room {
+        let reason = self
+            .statuses
+            .get(context)
+            .and_then(|status| status.error.as_ref())
+            .map_or_else(|| "connecting...".to_string(), ToString::to_string);
+        Room {
+            id: format!("{context}/cluster"),
+            name: format!("{context} Cluster Lobby"),
+            kind: RoomKind::Cluster,
+            locked: Some(reason),
+            context: Some(context.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn floorplan(&self) -> FloorPlanResult<FloorPlan> {
+        let mut multiverse = FloorPlan::new();
+        let multiverse_room = Room {
+            id: MULTIVERSE_ROOM_ID.to_string(),
+            name: "Multiverse".to_string(),
+            kind: RoomKind::Multiverse,
+            ..Default::default()
+        };
+        multiverse.add_room(multiverse_room.clone());
+        let mut door_id = 0;
+        for context in &self.contexts {
+            let lobby = match self.floorplans.get(context) {
+                Some(cluster) => {
+                    let lobby_id = multiverse.add_cluster(cluster, context)?;
+                    let lobby = multiverse.get_room_by_id_mut(&lobby_id)?;
+                    lobby.name = format!("{context} {}", lobby.name);
+                    lobby.clone()
+                }
+                None => {
+                    let lobby = self.placeholder_lobby(context);
+                    multiverse.add_room(lobby.clone());
+                    lobby
+                }
+            };
+            connect_rooms_with_doors(&mut multiverse, &multiverse_room, &lobby, &mut door_id)?;
+        }
+        Ok(multiverse)
+    }
+
+    pub fn room_events(&self) -> RoomEvents {
+        RoomEvents(
+            self.events
+                .iter()
+                .flat_map(|(context, events)| {
+                    events.0.iter().map(move |(room_id, events)| {
+                        (format!("{context}/{room_id}"), events.clone())
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// the first failing cluster, in the order the contexts were given
+    pub fn status(&self) -> ConnectionStatus {
+        self.contexts
+            .iter()
+            .find_map(|context| {
+                let status = self.statuses.get(context)?;
+                let error = status.error.as_ref()?;
+                Some(ConnectionStatus::failed(
+                    in_context(error, context),
+                    status.retry,
+                ))
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integration::integration_component::{ResourceEvent, Retry};
+
+    fn cluster(namespace: &str) -> FloorPlan {
+        let mut plan = FloorPlan::new();
+        let lobby = Room {
+            id: "cluster".to_string(),
+            name: "Cluster Lobby".to_string(),
+            kind: RoomKind::Cluster,
+            ..Default::default()
+        };
+        let hallway = Room {
+            id: namespace.to_string(),
+            name: format!("{namespace} NS Hallway"),
+            kind: RoomKind::Namespace,
+            ..Default::default()
+        };
+        plan.add_room(lobby.clone());
+        plan.add_room(hallway.clone());
+        connect_rooms_with_doors(&mut plan, &lobby, &hallway, &mut 0).unwrap();
+        plan
+    }
+
+    #[test]
+    fn test_multiverse_floorplan() {
+        let mut multiverse = Multiverse::new(vec!["staging".to_string(), "prod-eu".to_string()]);
+        multiverse.set_floorplan("staging", cluster("shop"));
+        assert!(multiverse.set_status(
+            "prod-eu",
+            ConnectionStatus::failed(
+                FloorPlanError::AuthError("token expired".to_string()),
+                Retry::Automatic,
+            ),
+        ));
+        assert!(!multiverse.set_status("staging", ConnectionStatus::default()));
+
+        let plan = multiverse.floorplan().unwrap();
+        assert_eq!(plan.get_start_room_id(), Some(MULTIVERSE_ROOM_ID));
+        let mut lobbies: Vec<String> = plan
+            .get_doors_and_connected_rooms(MULTIVERSE_ROOM_ID)
+            .unwrap()
+            .into_iter()
+            .map(|(_, room)| room.name.clone())
+            .collect();
+        lobbies.sort();
+        assert_eq!(
+            lobbies,
+            vec!["prod-eu Cluster Lobby", "staging Cluster Lobby"]
+        );
+        let shop = plan.get_room_by_id("staging/shop").unwrap();
+        assert_eq!(shop.context.as_deref(), Some("staging"));
+        assert!(
+            plan.shortest_path(MULTIVERSE_ROOM_ID, "staging/shop")
+                .is_ok()
+        );
+        let waiting = plan.get_room_by_id("prod-eu/cluster").unwrap();
+        assert_eq!(
+            waiting.locked.as_deref(),
+            Some("not authorized: token expired")
+        );
+
+        assert_eq!(
+            multiverse.status().message(),
+            Some(
+                "not authorized: prod-eu: token expired\nreconnecting - showing the last good floorplan"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_multiverse_room_events() {
+        let mut multiverse = Multiverse::new(vec!["staging".to_string()]);
+        let event = ResourceEvent {
+            kind: "Pod".to_string(),
+            name: "web-1".to_string(),
+            reason: "BackOff".to_string(),
+            ..Default::default()
+        };
+        multiverse.set_events(
+            "staging",
+            RoomEvents::new([("shop-Pod-web-1".to_string(), event.clone())]),
+        );
+        assert_eq!(
+            multiverse.room_events().0.get("staging/shop-Pod-web-1"),
+            Some(&vec![event])
+        );
+    }
+}
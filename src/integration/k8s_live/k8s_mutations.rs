@@ -0,0 +1,288 @@
+use super::k8s_api::classify_error;
+use super::k8s_integration_systems::LiveSettings;
+use crate::floorplan::FloorPlanResult;
+use crate::mutation::mutation_component::{Mutation, MutationOutcome, MutationRequest};
+use bevy::prelude::*;
+use bevy_tokio_tasks::TokioTasksRuntime;
+use k8s_openapi::{
+    api::{apps::v1::Deployment, core::v1::Node, core::v1::Pod},
+    jiff::Timestamp,
+};
+use kube::{
+    Client,
+    api::{Api, DeleteParams, Patch, PatchParams},
+};
+
+/// the field manager cluster actions patch as, so `managedFields` show who made the change
+const FIELD_MANAGER: &str = "doors-isometric";
+
+fn patch_params(dry_run: bool) -> PatchParams {
+    PatchParams {
+        dry_run,
+        field_manager: Some(FIELD_MANAGER.to_string()),
+        ..Default::default()
+    }
+}
+
+async fn patch_deployment(
+    client: Client,
+    namespace: &str,
+    name: &str,
+    patch: serde_json::Value,
+    dry_run: bool,
+) -> FloorPlanResult<Deployment> {
+    let api: Api<Deployment> = Api::namespaced(client, namespace);
+    api.patch(name, &patch_params(dry_run), &Patch::Merge(patch))
+        .await
+        .map_err(|e| classify_error(&e))
+}
+
+/**
+* make one change to the cluster, or with `dry_run` have the server check it and say what it would
+* do without persisting anything - either way the answer is a one line summary of the change
+*/
+pub async fn mutate(client: Client, mutation: &Mutation, dry_run: bool) -> FloorPlanResult<String> {
+    let would = |done: &str| {
+        if dry_run {
+            format!("would be {done} (dry run)")
+        } else {
+            done.to_string()
+        }
+    };
+    match mutation {
+        Mutation::DeletePod { namespace, name } => {
+            let api: Api<Pod> = Api::namespaced(client, namespace);
+            let params = DeleteParams {
+                dry_run,
+                ..Default::default()
+            };
+            let pod = api
+                .delete(name, &params)
+                .await
+                .map_err(|e| classify_error(&e))?
+                .left();
+            let status = pod.as_ref().and_then(|pod| pod.status.as_ref());
+            let phase = status
+                .and_then(|status| status.phase.clone())
+                .unwrap_or_else(|| "Unknown".to_string());
+            let node = pod
+                .as_ref()
+                .and_then(|pod| pod.spec.as_ref())
+                .and_then(|spec| spec.node_name.clone())
+                .unwrap_or_else(|| "no node".to_string());
+            Ok(format!(
+                "pod {namespace}/{name} ({phase} on {node}) {}",
+                would("deleted")
+            ))
+        }
+        Mutation::RestartDeployment { namespace, name } => {
+            let patch = serde_json::json!({
+                "spec": {"template": {"metadata": {"annotations": {
+                    "kubectl.kubernetes.io/restartedAt": Timestamp::now().to_string()
+                }}}}
+            });
+            let deployment = patch_deployment(client, namespace, name, patch, dry_run).await?;
+            let replicas = deployment.spec.and_then(|spec| spec.replicas).unwrap_or(1);
+            Ok(format!(
+                "deployment {namespace}/{name} {}, its {replicas} pods are replaced a few at a time",
+                would("restarted")
+            ))
+        }
+        Mutation::ScaleDeployment {
+            namespace,
+            name,
+            replicas,
+        } => {
+            let patch = serde_json::json!({"spec": {"replicas": replicas}});
+            let deployment = patch_deployment(client, namespace, name, patch, dry_run).await?;
+            // the status is untouched by the patch, so it still tells how many there are now
+            let current = deployment
+                .status
+                .and_then(|status| status.replicas)
+                .unwrap_or(0);
+            Ok(format!(
+                "deployment {namespace}/{name} {} from {current} to {replicas} replicas",
+                would("scaled")
+            ))
+        }
+        Mutation::CordonNode { name } => {
+            let api: Api<Node> = Api::all(client);
+            let patch = serde_json::json!({"spec": {"unschedulable": true}});
+            api.patch(name, &patch_params(dry_run), &Patch::Merge(patch))
+                .await
+                .map_err(|e| classify_error(&e))?;
+            Ok(format!(
+                "node {name} {}, pods already there keep running",
+                would("cordoned")
+            ))
+        }
+    }
+}
+
+/// each request runs on the tokio runtime and its outcome comes back as an event
+pub fn apply_requested_mutations(
+    runtime: ResMut<TokioTasksRuntime>,
+    settings: Res<LiveSettings>,
+    mut requests: EventReader<MutationRequest>,
+) {
+    for request in requests.read() {
+        let request = request.clone();
+        let settings = settings.clone();
+        runtime.spawn_background_task(move |mut ctx| async move {
+            let result = match settings.client(request.context.as_deref()).await {
+                Ok(client) => mutate(client, &request.mutation, request.dry_run).await,
+                Err(e) => Err(e),
+            }
+            .map_err(|e| e.to_string());
+            ctx.run_on_main_thread(move |ctx| {
+                ctx.world.send_event(MutationOutcome { request, result });
+            })
+            .await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::floorplan::FloorPlanError;
+    use crate::integration::k8s_live::k8s_mock_server::{MockApiServer, status_body};
+
+    fn deployment_body() -> String {
+        serde_json::json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": {"name": "web", "namespace": "shop"},
+            "spec": {
+                "replicas": 5,
+                "selector": {"matchLabels": {"app": "web"}},
+                "template": {"metadata": {"labels": {"app": "web"}}}
+            },
+            "status": {"replicas": 3}
+        })
+        .to_string()
+    }
+
+    fn scale() -> Mutation {
+        Mutation::ScaleDeployment {
+            namespace: "shop".to_string(),
+            name: "web".to_string(),
+            replicas: 5,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_scale() {
+        let server = MockApiServer::start(|_| (200, deployment_body())).await;
+        let summary = mutate(server.client(), &scale(), true).await.unwrap();
+        assert_eq!(
+            summary,
+            "deployment shop/web would be scaled (dry run) from 3 to 5 replicas"
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "PATCH");
+        assert!(
+            requests[0]
+                .path
+                .starts_with("/apis/apps/v1/namespaces/shop/deployments/web?")
+        );
+        assert!(requests[0].path.contains("dryRun=All"));
+        assert!(requests[0].path.contains("fieldManager=doors-isometric"));
+        let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(body, serde_json::json!({"spec": {"replicas": 5}}));
+    }
+
+    #[tokio::test]
+    async fn test_restart_and_cordon() {
+        let server = MockApiServer::start(|request| {
+            if request.path.starts_with("/api/v1/nodes/worker-1") {
+                (
+                    200,
+                    serde_json::json!({
+                        "apiVersion": "v1",
+                        "kind": "Node",
+                        "metadata": {"name": "worker-1"},
+                        "spec": {"unschedulable": true}
+                    })
+                    .to_string(),
+                )
+            } else {
+                (200, deployment_body())
+            }
+        })
+        .await;
+        let restart = Mutation::RestartDeployment {
+            namespace: "shop".to_string(),
+            name: "web".to_string(),
+        };
+        let summary = mutate(server.client(), &restart, false).await.unwrap();
+        assert_eq!(
+            summary,
+            "deployment shop/web restarted, its 5 pods are replaced a few at a time"
+        );
+        let cordon = Mutation::CordonNode {
+            name: "worker-1".to_string(),
+        };
+        mutate(server.client(), &cordon, false).await.unwrap();
+
+        let requests = server.requests();
+        assert!(!requests[0].path.contains("dryRun"));
+        let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert!(
+            body["spec"]["template"]["metadata"]["annotations"]
+                ["kubectl.kubernetes.io/restartedAt"]
+                .is_string()
+        );
+        assert_eq!(requests[1].method, "PATCH");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&requests[1].body).unwrap(),
+            serde_json::json!({"spec": {"unschedulable": true}})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_pod() {
+        let server = MockApiServer::start(|_| {
+            (
+                200,
+                serde_json::json!({
+                    "apiVersion": "v1",
+                    "kind": "Pod",
+                    "metadata": {"name": "web-1", "namespace": "shop"},
+                    "spec": {"containers": [], "nodeName": "worker-1"},
+                    "status": {"phase": "Running"}
+                })
+                .to_string(),
+            )
+        })
+        .await;
+        let delete = Mutation::DeletePod {
+            namespace: "shop".to_string(),
+            name: "web-1".to_string(),
+        };
+        let summary = mutate(server.client(), &delete, true).await.unwrap();
+        assert_eq!(
+            summary,
+            "pod shop/web-1 (Running on worker-1) would be deleted (dry run)"
+        );
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "DELETE");
+        assert_eq!(requests[0].path, "/api/v1/namespaces/shop/pods/web-1?");
+        assert!(requests[0].body.contains("All"));
+    }
+
+    #[tokio::test]
+    async fn test_forbidden_mutation() {
+        let server = MockApiServer::start(|_| {
+            (
+                403,
+                status_body(403, "Forbidden", "deployments.apps \"web\" is forbidden"),
+            )
+        })
+        .await;
+        let result = mutate(server.client(), &scale(), false).await;
+        assert!(matches!(result, Err(FloorPlanError::AuthError(_))));
+    }
+}
@@ -0,0 +1,501 @@
+use super::k8s_access::AccessDenial;
+use super::k8s_api::{
+    ClusterObjects, ClusterScope, SelectedKind, api_resource, classify_watch_error, forbidden_watch,
+};
+use crate::floorplan::FloorPlanError;
+use crate::integration::integration_component::ResourceEvent;
+use crate::integration::integration_utils::get_resource_event;
+use bevy::log::{debug, warn};
+use futures::StreamExt;
+use k8s_openapi::api::core::v1::Namespace;
+use kube::{
+    Client, Resource,
+    api::Api,
+    core::{ApiResource, DynamicObject},
+    runtime::{
+        WatchStreamExt,
+        reflector::{self, Lookup, Store, store::Writer},
+        watcher,
+    },
+};
+use serde::de::DeserializeOwned;
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    hash::Hash,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    sync::mpsc::{UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
+
+/// what the reflectors report back to the floorplan publisher
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchSignal {
+    /// a store was updated
+    Changed,
+    /// a watch failed and is backing off before it reconnects on its own
+    Failed(FloorPlanError),
+}
+
+/**
+* local caches of the namespaces and of every watched kind, kept current by one reflector each -
+* building a floorplan from here costs no api calls. namespaces are only watched when the scope
+* does not name them, and then a kind has one store per named namespace
+*/
+pub struct ClusterStore {
+    scope: ClusterScope,
+    namespaces: Option<Store<Namespace>>,
+    kinds: Vec<SelectedKind>,
+    stores: Vec<(String, Store<DynamicObject>)>,
+    /// kept apart from the kinds so that events never get a hallway of their own
+    events: Vec<Store<DynamicObject>>,
+    /// what was not watched because the user may not, and what the server refused since
+    denials: Arc<Mutex<Vec<AccessDenial>>>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+/// the watches belong to the store, so starting over with a new client stops the old ones
+impl Drop for ClusterStore {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+impl ClusterStore {
+    pub fn objects(&self) -> ClusterObjects {
+        let namespaces = self.namespaces.as_ref().map_or_else(
+            || self.scope.listed_namespaces().unwrap_or_default(),
+            |store| {
+                store
+                    .state()
+                    .iter()
+                    .filter_map(|ns| ns.metadata.name.clone())
+                    .filter(|namespace| self.scope.includes(namespace))
+                    .collect()
+            },
+        );
+        let mut objects: BTreeMap<String, Vec<DynamicObject>> = BTreeMap::new();
+        for (kind, store) in &self.stores {
+            objects
+                .entry(kind.clone())
+                .or_default()
+                .extend(store.state().iter().map(|o| (**o).clone()));
+        }
+        ClusterObjects::new(namespaces, self.kinds.clone(), objects)
+            .with_denials(self.denials.lock().unwrap().clone())
+    }
+
+    pub fn events(&self) -> Vec<ResourceEvent> {
+        self.events
+            .iter()
+            .flat_map(Store::state)
+            .filter_map(|event| serde_json::to_value(&*event).ok())
+            .filter_map(|event| get_resource_event(&event))
+            .collect()
+    }
+
+    /**
+     * wait for every store to hold its initial list, giving up after `timeout` so a kind that can
+     * never be listed does not hold back the rest of the cluster - false if it gave up. events are
+     * not waited for, they come with the change their own initial list signals
+     */
+    pub async fn wait_until_ready(&self, timeout: Duration) -> bool {
+        let ready = async {
+            if let Some(namespaces) = &self.namespaces {
+                let _ = namespaces.wait_until_ready().await;
+            }
+            for (_, store) in &self.stores {
+                let _ = store.wait_until_ready().await;
+            }
+        };
+        let synced = tokio::time::timeout(timeout, ready).await.is_ok();
+        if !synced {
+            warn!("Not all watches synced within {timeout:?}");
+        }
+        synced
+    }
+}
+
+/// the initial list is announced once, when it is complete, not object by object
+const fn is_change<K>(event: &watcher::Event<K>) -> bool {
+    !matches!(event, watcher::Event::Init | watcher::Event::InitApply(_))
+}
+
+/// what a reflector does when the server refuses it
+enum Refusal {
+    /// report it like any other failure, the banner stays up until it recovers
+    Report,
+    /// an optional feed such as events - nothing is reported, and a refused one just stays empty
+    Quiet,
+    /// lock the kind's hallway and stop watching it
+    Lock {
+        resource: ApiResource,
+        namespace: Option<String>,
+        denials: Arc<Mutex<Vec<AccessDenial>>>,
+    },
+}
+
+fn spawn_reflector<K>(
+    writer: Writer<K>,
+    api: Api<K>,
+    config: watcher::Config,
+    changes: UnboundedSender<WatchSignal>,
+    refusal: Refusal,
+) -> JoinHandle<()>
+where
+    K: Resource + Lookup + Clone + DeserializeOwned + Debug + Send + Sync + 'static,
+    <K as Lookup>::DynamicType: Eq + Hash + Clone + Send + Sync,
+{
+    let stream = reflector::reflector(writer, watcher(api, config)).default_backoff();
+    tokio::spawn(async move {
+        let mut stream = std::pin::pin!(stream);
+        while let Some(event) = stream.next().await {
+            let signal = match event {
+                Ok(event) if is_change(&event) => WatchSignal::Changed,
+                Ok(_) => continue,
+                Err(e) if matches!(refusal, Refusal::Quiet) => {
+                    if forbidden_watch(&e).is_some() {
+                        debug!("Watch refused, leaving it empty: {e}");
+                        break;
+                    }
+                    debug!("Watch error, retrying: {e}");
+                    continue;
+                }
+                Err(e) => {
+                    if let Refusal::Lock {
+                        resource,
+                        namespace,
+                        denials,
+                    } = &refusal
+                        && let Some(reason) = forbidden_watch(&e)
+                    {
+                        warn!("Watching {} was refused: {e}", resource.plural);
+                        denials.lock().unwrap().push(AccessDenial::refused(
+                            resource,
+                            namespace.as_deref(),
+                            reason,
+                        ));
+                        // dropping the store's writer tells `wait_until_ready` not to wait for it,
+                        // and the next floorplan has the hallway locked
+                        let _ = changes.send(WatchSignal::Changed);
+                        break;
+                    }
+                    warn!("Watch error: {e}");
+                    WatchSignal::Failed(classify_watch_error(&e))
+                }
+            };
+            if changes.send(signal).is_err() {
+                debug!("Floorplan publisher is gone, stopping watch");
+                break;
+            }
+        }
+    })
+}
+
+/**
+* start a watch on the namespaces, on events unless `denials` covers them, and on each kind within
+* the scope - every add, update
+* or delete is signalled on `changes` after the local store has been updated. cluster scoped kinds
+* are left out when the scope names its namespaces, and so is whatever `denials` covers - a kind
+* the server refuses to list all the same is added to them and its watch stopped
+*/
+pub fn watch_cluster(
+    client: &Client,
+    kinds: &[SelectedKind],
+    scope: &ClusterScope,
+    denials: &[AccessDenial],
+    changes: &UnboundedSender<WatchSignal>,
+) -> ClusterStore {
+    let mut tasks = Vec::new();
+    let listed_namespaces = scope.listed_namespaces();
+    let namespaces = listed_namespaces.is_none().then(|| {
+        let writer = Writer::default();
+        let store = writer.as_reader();
+        let api = Api::<Namespace>::all(client.clone());
+        tasks.push(spawn_reflector(
+            writer,
+            api,
+            watcher::Config::default(),
+            changes.clone(),
+            Refusal::Report,
+        ));
+        store
+    });
+    let denials = Arc::new(Mutex::new(denials.to_vec()));
+    let events_denied = |namespace: Option<&str>| {
+        denials.lock().unwrap().iter().any(|denial| {
+            denial.kind == "Event"
+                && denial
+                    .namespace
+                    .as_deref()
+                    .is_none_or(|n| Some(n) == namespace)
+        })
+    };
+
+    let events_resource = api_resource("Event");
+    let events_apis: Vec<_> = listed_namespaces.as_ref().map_or_else(
+        || {
+            (!events_denied(None))
+                .then(|| Api::<DynamicObject>::all_with(client.clone(), &events_resource))
+                .into_iter()
+                .collect()
+        },
+        |namespaces| {
+            namespaces
+                .iter()
+                .filter(|namespace| !events_denied(Some(namespace)))
+                .map(|namespace| {
+                    Api::<DynamicObject>::namespaced_with(
+                        client.clone(),
+                        namespace,
+                        &events_resource,
+                    )
+                })
+                .collect()
+        },
+    );
+    let mut events = Vec::new();
+    for api in events_apis {
+        let writer = Writer::new(events_resource.clone());
+        events.push(writer.as_reader());
+        // events carry no labels, so the label selector is not applied to them
+        tasks.push(spawn_reflector(
+            writer,
+            api,
+            watcher::Config::default(),
+            changes.clone(),
+            Refusal::Quiet,
+        ));
+    }
+
+    let mut stores = Vec::new();
+    for SelectedKind {
+        resource,
+        namespaced,
+        ..
+    } in kinds
+    {
+        if !namespaced && listed_namespaces.is_some() {
+            continue;
+        }
+        let denied = |namespace: Option<&str>| {
+            denials.lock().unwrap().iter().any(|denial| {
+                denial.kind == resource.kind && denial.namespace.as_deref() == namespace
+            })
+        };
+        let apis = listed_namespaces.as_ref().map_or_else(
+            || {
+                (!denied(None))
+                    .then(|| {
+                        (
+                            None,
+                            Api::<DynamicObject>::all_with(client.clone(), resource),
+                        )
+                    })
+                    .into_iter()
+                    .collect()
+            },
+            |namespaces| {
+                namespaces
+                    .iter()
+                    .filter(|namespace| !denied(Some(namespace)))
+                    .map(|namespace| {
+                        (
+                            Some(namespace.clone()),
+                            Api::<DynamicObject>::namespaced_with(
+                                client.clone(),
+                                namespace,
+                                resource,
+                            ),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            },
+        );
+        for (namespace, api) in apis {
+            let writer = Writer::new(resource.clone());
+            stores.push((resource.kind.clone(), writer.as_reader()));
+            tasks.push(spawn_reflector(
+                writer,
+                api,
+                scope.watcher_config(),
+                changes.clone(),
+                Refusal::Lock {
+                    resource: resource.clone(),
+                    namespace,
+                    denials: denials.clone(),
+                },
+            ));
+        }
+    }
+
+    ClusterStore {
+        scope: scope.clone(),
+        namespaces,
+        kinds: kinds.to_vec(),
+        stores,
+        events,
+        denials,
+        tasks,
+    }
+}
+
+/**
+* after a change, keep absorbing further signals until none arrive for `debounce` - but never for
+* longer than `max_delay` so a cluster that is constantly churning still gets refreshed. returns the
+* last failure seen, if the watches have not recovered from it since
+*/
+pub async fn settle(
+    changes: &mut UnboundedReceiver<WatchSignal>,
+    debounce: Duration,
+    max_delay: Duration,
+) -> Option<FloorPlanError> {
+    let deadline = tokio::time::Instant::now() + max_delay;
+    let mut failure = None;
+    loop {
+        let quiet_at = (tokio::time::Instant::now() + debounce).min(deadline);
+        match tokio::time::timeout_at(quiet_at, changes.recv()).await {
+            Ok(Some(WatchSignal::Changed)) => failure = None,
+            Ok(Some(WatchSignal::Failed(e))) => failure = Some(e),
+            Ok(None) | Err(_) => break,
+        }
+    }
+    failure
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integration::k8s_live::k8s_api::{KindFilter, discover_kinds};
+    use crate::integration::k8s_live::k8s_mock_server::{MockApiServer, fixture_objects, refusing};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[tokio::test]
+    async fn test_forbidden_events_leave_the_feed_empty() {
+        // the reviews fail, so the events watch is tried and refused
+        let server = MockApiServer::with_objects_and(
+            fixture_objects(&["assets/k8s.yaml"], ""),
+            refusing(&["events"]),
+        )
+        .await;
+        let client = server.client();
+        let kinds = discover_kinds(&client, &KindFilter::default())
+            .await
+            .unwrap();
+        let (tx, mut rx) = unbounded_channel();
+        let store = watch_cluster(&client, &kinds, &ClusterScope::default(), &[], &tx);
+        let started = tokio::time::Instant::now();
+        assert!(store.wait_until_ready(Duration::from_secs(10)).await);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        while !server
+            .requests()
+            .iter()
+            .any(|request| request.path.starts_with("/api/v1/events"))
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(store.events().is_empty());
+        while let Ok(signal) = rx.try_recv() {
+            assert_eq!(signal, WatchSignal::Changed, "no banner for the events");
+        }
+
+        // a review that denies events keeps the watch from starting at all
+        let denial = AccessDenial::refused(&api_resource("Event"), None, "");
+        let before = server.requests().len();
+        let store = watch_cluster(&client, &kinds, &ClusterScope::default(), &[denial], &tx);
+        assert!(store.wait_until_ready(Duration::from_secs(10)).await);
+        assert!(
+            !server.requests()[before..]
+                .iter()
+                .any(|request| request.path.starts_with("/api/v1/events"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refused_watch_locks_the_hallway() {
+        let server = MockApiServer::with_objects_and(
+            fixture_objects(&["assets/k8s.yaml"], ""),
+            refusing(&["pods"]),
+        )
+        .await;
+        let client = server.client();
+        let kinds = discover_kinds(&client, &KindFilter::default())
+            .await
+            .unwrap();
+        let (tx, mut rx) = unbounded_channel();
+        let store = watch_cluster(&client, &kinds, &ClusterScope::default(), &[], &tx);
+
+        assert!(store.wait_until_ready(Duration::from_secs(10)).await);
+        let objects = store.objects();
+        assert_eq!(
+            objects
+                .denial("Pod", "kube-system")
+                .map(|denial| denial.verb.as_str()),
+            Some("list")
+        );
+        assert!(objects.denial("Deployment", "kube-system").is_none());
+        assert!(!objects.resources("Deployment", "kube-system").is_empty());
+        while let Ok(signal) = rx.try_recv() {
+            assert_eq!(signal, WatchSignal::Changed);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_settle_waits_for_quiet() {
+        let (tx, mut rx) = unbounded_channel();
+        let burst = tx.clone();
+        tokio::spawn(async move {
+            for _ in 0..5 {
+                burst.send(WatchSignal::Changed).unwrap();
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        });
+
+        let start = tokio::time::Instant::now();
+        settle(&mut rx, Duration::from_millis(250), Duration::from_secs(60)).await;
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(650), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(1), "{elapsed:?}");
+        drop(tx);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_settle_gives_up_at_max_delay() {
+        let (tx, mut rx) = unbounded_channel();
+        tokio::spawn(async move {
+            loop {
+                if tx.send(WatchSignal::Changed).is_err() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        });
+
+        let start = tokio::time::Instant::now();
+        settle(&mut rx, Duration::from_millis(250), Duration::from_secs(2)).await;
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_secs(2), "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(2100), "{elapsed:?}");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_settle_reports_unrecovered_failure() {
+        let (tx, mut rx) = unbounded_channel();
+        let auth_error = FloorPlanError::AuthError("token expired".to_string());
+        tx.send(WatchSignal::Changed).unwrap();
+        tx.send(WatchSignal::Failed(auth_error.clone())).unwrap();
+        let failure = settle(&mut rx, Duration::from_millis(250), Duration::from_secs(2)).await;
+        assert_eq!(failure, Some(auth_error.clone()));
+
+        tx.send(WatchSignal::Failed(auth_error)).unwrap();
+        tx.send(WatchSignal::Changed).unwrap();
+        let failure = settle(&mut rx, Duration::from_millis(250), Duration::from_secs(2)).await;
+        assert_eq!(failure, None);
+    }
+}
@@ -1,6 +1,13 @@
+pub mod k8s_access;
 pub mod k8s_api;
 pub mod k8s_integration_plugin;
 pub mod k8s_integration_systems;
+pub mod k8s_logs;
+#[cfg(test)]
+pub mod k8s_mock_server;
+pub mod k8s_multiverse;
+pub mod k8s_mutations;
+pub mod k8s_watch;
 // pub mod k8s_utils;
 //
 pub use k8s_integration_plugin::K8sIntegrationPlugin;
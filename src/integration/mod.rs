@@ -1,6 +1,9 @@
+pub mod integration_component;
 pub mod integration_plugin;
+pub mod integration_systems;
 pub mod integration_utils;
 
+pub mod snapshot;
 pub mod test_mode;
 
 #[cfg(feature = "k8s")]
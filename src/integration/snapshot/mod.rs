@@ -0,0 +1,5 @@
+pub mod snapshot_format;
+pub mod snapshot_plugin;
+pub mod snapshot_systems;
+
+pub use snapshot_plugin::{SnapshotIntegrationPlugin, SnapshotRecorderPlugin};
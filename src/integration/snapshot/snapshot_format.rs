@@ -0,0 +1,247 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::floorplan::{Door, FloorPlan, FloorPlanError, FloorPlanResult, Room};
+
+/**
+* bump this whenever `Room`, `Door` or the snapshot layout changes - older files are rejected
+* rather than silently misread
+*/
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 11;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    Json,
+    Binary,
+}
+
+impl SnapshotFormat {
+    /// `.json` files are human readable, anything else is the compact binary encoding
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Binary,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DoorSnapshot {
+    pub from: String,
+    pub to: String,
+    pub door: Door,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FloorPlanSnapshot {
+    pub version: u32,
+    pub start_room_id: Option<String>,
+    pub rooms: Vec<Room>,
+    pub doors: Vec<DoorSnapshot>,
+}
+
+impl FloorPlanSnapshot {
+    pub fn from_floorplan(floorplan: &FloorPlan) -> FloorPlanResult<Self> {
+        let rooms = floorplan.graph.node_weights().cloned().collect();
+        let doors = floorplan
+            .graph
+            .raw_edges()
+            .iter()
+            .map(|edge| {
+                Ok(DoorSnapshot {
+                    from: floorplan.get_room(edge.source())?.id.clone(),
+                    to: floorplan.get_room(edge.target())?.id.clone(),
+                    door: edge.weight.clone(),
+                })
+            })
+            .collect::<FloorPlanResult<Vec<_>>>()?;
+
+        Ok(Self {
+            version: SNAPSHOT_FORMAT_VERSION,
+            start_room_id: floorplan.get_start_room_id().map(String::from),
+            rooms,
+            doors,
+        })
+    }
+
+    pub fn into_floorplan(self) -> FloorPlanResult<FloorPlan> {
+        if self.version != SNAPSHOT_FORMAT_VERSION {
+            return Err(FloorPlanError::SnapshotError(format!(
+                "unsupported snapshot version {} (expected {SNAPSHOT_FORMAT_VERSION})",
+                self.version
+            )));
+        }
+
+        let mut floorplan = FloorPlan::new();
+        for room in self.rooms {
+            floorplan.add_room(room);
+        }
+        for door in self.doors {
+            floorplan.add_door(
+                floorplan.get_room_idx_by_id(&door.from)?,
+                floorplan.get_room_idx_by_id(&door.to)?,
+                door.door,
+            );
+        }
+        if let Some(start_room_id) = &self.start_room_id {
+            floorplan.set_start_room(start_room_id)?;
+        }
+        Ok(floorplan)
+    }
+}
+
+pub fn to_json(floorplan: &FloorPlan) -> FloorPlanResult<String> {
+    let snapshot = FloorPlanSnapshot::from_floorplan(floorplan)?;
+    serde_json::to_string_pretty(&snapshot)
+        .map_err(|e| FloorPlanError::SnapshotError(e.to_string()))
+}
+
+pub fn from_json(json: &str) -> FloorPlanResult<FloorPlan> {
+    serde_json::from_str::<FloorPlanSnapshot>(json)
+        .map_err(|e| FloorPlanError::SnapshotError(e.to_string()))?
+        .into_floorplan()
+}
+
+pub fn to_bytes(floorplan: &FloorPlan) -> FloorPlanResult<Vec<u8>> {
+    let snapshot = FloorPlanSnapshot::from_floorplan(floorplan)?;
+    bincode::serialize(&snapshot).map_err(|e| FloorPlanError::SnapshotError(e.to_string()))
+}
+
+pub fn from_bytes(bytes: &[u8]) -> FloorPlanResult<FloorPlan> {
+    bincode::deserialize::<FloorPlanSnapshot>(bytes)
+        .map_err(|e| FloorPlanError::SnapshotError(e.to_string()))?
+        .into_floorplan()
+}
+
+pub fn save(floorplan: &FloorPlan, path: &Path) -> FloorPlanResult<()> {
+    let bytes = match SnapshotFormat::from_path(path) {
+        SnapshotFormat::Json => to_json(floorplan)?.into_bytes(),
+        SnapshotFormat::Binary => to_bytes(floorplan)?,
+    };
+    std::fs::write(path, bytes).map_err(|e| FloorPlanError::SnapshotError(e.to_string()))
+}
+
+pub fn load(path: &Path) -> FloorPlanResult<FloorPlan> {
+    let bytes = std::fs::read(path).map_err(|e| FloorPlanError::SnapshotError(e.to_string()))?;
+    match SnapshotFormat::from_path(path) {
+        SnapshotFormat::Json => from_json(
+            std::str::from_utf8(&bytes)
+                .map_err(|e| FloorPlanError::SnapshotError(e.to_string()))?,
+        ),
+        SnapshotFormat::Binary => from_bytes(&bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::floorplan::RoomKind;
+
+    fn create_floorplan() -> FloorPlan {
+        let mut floorplan = FloorPlan::new();
+        let lobby = floorplan.add_room(Room {
+            id: "cluster".to_string(),
+            name: "Cluster Lobby".to_string(),
+            kind: RoomKind::Cluster,
+            ..Default::default()
+        });
+        let hallway = floorplan.add_room(Room {
+            id: "default".to_string(),
+            name: "default NS Hallway".to_string(),
+            kind: RoomKind::Namespace,
+            namespace: Some("default".to_string()),
+            labels: [("team".to_string(), "payments".to_string())].into(),
+            attributes: [("created".to_string(), "2025-01-18T14:30:26Z".to_string())].into(),
+            ..Default::default()
+        });
+        floorplan.add_door(
+            lobby,
+            hallway,
+            Door {
+                id: "0".to_string(),
+                name: "Door to default NS Hallway".to_string(),
+                is_exit: false,
+                ..Default::default()
+            },
+        );
+        floorplan.add_door(
+            hallway,
+            lobby,
+            Door {
+                id: "1".to_string(),
+                name: "Door to Cluster Lobby".to_string(),
+                is_exit: true,
+                ..Default::default()
+            },
+        );
+        floorplan.set_start_room("default").unwrap();
+        floorplan
+    }
+
+    fn assert_same_floorplan(a: &FloorPlan, b: &FloorPlan) {
+        assert_eq!(a, b);
+        assert_eq!(
+            FloorPlanSnapshot::from_floorplan(a).unwrap(),
+            FloorPlanSnapshot::from_floorplan(b).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let floorplan = create_floorplan();
+        let json = to_json(&floorplan).unwrap();
+        assert!(json.contains(&format!("\"version\": {SNAPSHOT_FORMAT_VERSION}")));
+        let loaded = from_json(&json).unwrap();
+        assert_same_floorplan(&floorplan, &loaded);
+        let start_room = loaded.get_start_room().unwrap();
+        assert_eq!(start_room.name, "default NS Hallway");
+        assert_eq!(start_room.kind, RoomKind::Namespace);
+        assert_eq!(
+            start_room.labels.get("team").map(String::as_str),
+            Some("payments")
+        );
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let floorplan = create_floorplan();
+        let bytes = to_bytes(&floorplan).unwrap();
+        let loaded = from_bytes(&bytes).unwrap();
+        assert_same_floorplan(&floorplan, &loaded);
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let floorplan = create_floorplan();
+        let dir = std::env::temp_dir();
+        for name in ["doors-snapshot-test.json", "doors-snapshot-test.bin"] {
+            let path = dir.join(name);
+            save(&floorplan, &path).unwrap();
+            let loaded = load(&path).unwrap();
+            assert_same_floorplan(&floorplan, &loaded);
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+
+    #[test]
+    fn test_rejects_unknown_version() {
+        let mut snapshot = FloorPlanSnapshot::from_floorplan(&create_floorplan()).unwrap();
+        snapshot.version = SNAPSHOT_FORMAT_VERSION + 1;
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert!(matches!(
+            from_json(&json),
+            Err(FloorPlanError::SnapshotError(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_door_to_unknown_room() {
+        let mut snapshot = FloorPlanSnapshot::from_floorplan(&create_floorplan()).unwrap();
+        snapshot.doors[0].to = "nowhere".to_string();
+        assert!(matches!(
+            snapshot.into_floorplan(),
+            Err(FloorPlanError::RoomNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,20 @@
+use bevy::prelude::*;
+
+use super::snapshot_systems::{fire_snapshot_floorplan_event, record_floorplan_snapshot};
+
+pub struct SnapshotIntegrationPlugin;
+
+impl Plugin for SnapshotIntegrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, fire_snapshot_floorplan_event);
+    }
+}
+
+/// writes every floorplan received from any generator to `--save-snapshot`
+pub struct SnapshotRecorderPlugin;
+
+impl Plugin for SnapshotRecorderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, record_floorplan_snapshot);
+    }
+}
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use clap::Parser;
+
+use crate::cli::Cli;
+use crate::floorplan::{FloorPlan, FloorPlanError, FloorPlanEvent, FloorPlanResult};
+use crate::integration::integration_component::{ConnectionStatus, Retry};
+
+use super::snapshot_format::{load, save};
+
+fn generate_floorplan_from_snapshot() -> FloorPlanResult<FloorPlan> {
+    let path = Cli::parse()
+        .snapshot_path
+        .ok_or_else(|| FloorPlanError::SnapshotError("--snapshot-path is required".to_string()))?;
+    info!("loading FloorPlan snapshot from {}", path.display());
+    load(&path)
+}
+
+pub fn fire_snapshot_floorplan_event(
+    mut events: EventWriter<FloorPlanEvent>,
+    mut status: ResMut<ConnectionStatus>,
+) {
+    match generate_floorplan_from_snapshot() {
+        Ok(floorplan) => {
+            events.send(FloorPlanEvent { floorplan });
+        }
+        Err(e) => {
+            error!("No snapshot FloorPlanEvent: {e}");
+            *status = ConnectionStatus::failed(e, Retry::Never);
+        }
+    }
+}
+
+pub fn record_floorplan_snapshot(
+    mut events: EventReader<FloorPlanEvent>,
+    mut path: Local<Option<PathBuf>>,
+) {
+    if path.is_none() {
+        *path = Cli::parse().save_snapshot;
+    }
+    if let Some(path) = path.as_ref() {
+        for event in events.read() {
+            match save(&event.floorplan, path) {
+                Ok(()) => debug!("saved FloorPlan snapshot to {}", path.display()),
+                Err(e) => warn!("Failed to save FloorPlan snapshot: {e:?}"),
+            }
+        }
+    }
+}
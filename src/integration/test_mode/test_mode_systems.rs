@@ -20,6 +20,7 @@ fn create_rooms(lim: u8) -> Vec<Room> {
         .map(|i| Room {
             id: i.to_string(),
             name: format!("Room {i}"),
+            ..Default::default()
         })
         .collect()
 }
@@ -30,6 +31,7 @@ fn create_doors(lim: u8) -> Vec<Door> {
             id: i.to_string(),
             name: format!("Door {i}"),
             is_exit: false,
+            ..Default::default()
         })
         .collect()
 }
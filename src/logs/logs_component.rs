@@ -0,0 +1,233 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::floorplan::{Room, RoomKind};
+
+/// how many of the kept lines the panel shows, the newest at the bottom
+pub const LOG_PANEL_LINES: usize = 20;
+
+pub struct LogsPlugin;
+
+/// the container whose logs are wanted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogTarget {
+    pub namespace: String,
+    pub pod: String,
+    pub container: String,
+    /// the kubeconfig context of its cluster, when the world spans several
+    pub context: Option<String>,
+}
+
+impl LogTarget {
+    /// only container rooms have logs, and they know their pod and namespace
+    pub fn from_room(room: &Room) -> Option<Self> {
+        if room.kind != RoomKind::Container {
+            return None;
+        }
+        Some(Self {
+            namespace: room.namespace.clone()?,
+            pod: room.attributes.get("pod")?.clone(),
+            container: room.attributes.get("container")?.clone(),
+            context: room.context.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogOptions {
+    /// keep the stream open and add lines as the container writes them
+    pub follow: bool,
+    /// the logs of the container's last terminated run, ie: the one that crashed
+    pub previous: bool,
+    /// how many lines to ask for, and the most the panel keeps while following
+    pub lines: usize,
+}
+
+impl Default for LogOptions {
+    fn default() -> Self {
+        Self {
+            follow: true,
+            previous: false,
+            lines: 200,
+        }
+    }
+}
+
+/**
+* asks the log source for a container's logs - `generation` tells the answers to a request apart
+* from those to the one it replaced
+*/
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct LogRequest {
+    pub generation: u64,
+    pub target: LogTarget,
+    pub options: LogOptions,
+}
+
+/// there is something to answer `LogRequest`s, without it the panel says why it stays empty
+#[derive(Resource, Default)]
+pub struct LogSource;
+
+/**
+* the log panel's state - open while it has a target. lines from a source that is answering an
+* older request are dropped, which is also how a stream learns it should stop
+*/
+#[derive(Resource, Debug, Default)]
+pub struct LogPanel {
+    pub room_id: String,
+    pub target: Option<LogTarget>,
+    pub options: LogOptions,
+    pub generation: u64,
+    pub lines: VecDeque<String>,
+    /// why there are no lines, ie: still loading or the request failed
+    pub status: Option<String>,
+}
+
+impl LogPanel {
+    /// (re)start streaming, the previous stream stops at its next line
+    pub fn open(&mut self, room_id: &str, target: LogTarget) -> LogRequest {
+        self.room_id = room_id.to_string();
+        self.target = Some(target.clone());
+        self.generation += 1;
+        self.lines.clear();
+        self.status = Some("loading...".to_string());
+        LogRequest {
+            generation: self.generation,
+            target,
+            options: self.options,
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.target = None;
+        self.generation += 1;
+        self.lines.clear();
+        self.status = None;
+    }
+
+    pub const fn is_open(&self) -> bool {
+        self.target.is_some()
+    }
+
+    /// whether a stream started for `generation` should keep going
+    pub const fn wants(&self, generation: u64) -> bool {
+        generation == self.generation
+    }
+
+    /// false once the lines are no longer wanted, so the source can stop streaming
+    pub fn push_lines(&mut self, generation: u64, lines: Vec<String>) -> bool {
+        if !self.wants(generation) {
+            return false;
+        }
+        if !lines.is_empty() {
+            self.status = None;
+        }
+        self.lines.extend(lines);
+        let excess = self.lines.len().saturating_sub(self.options.lines);
+        self.lines.drain(..excess);
+        true
+    }
+
+    /// how a stream ended, if it has nothing else to show
+    pub fn finish(&mut self, generation: u64, status: Option<String>) {
+        if generation == self.generation && (status.is_some() || self.lines.is_empty()) {
+            self.status = Some(status.unwrap_or_else(|| "no logs".to_string()));
+        }
+    }
+
+    pub fn text(&self) -> Option<String> {
+        let target = self.target.as_ref()?;
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        let mut text = format!(
+            "logs of {} in {}/{} - follow {} (f), previous {} (p), close (l)",
+            target.container,
+            target.namespace,
+            target.pod,
+            on_off(self.options.follow),
+            on_off(self.options.previous)
+        );
+        if let Some(status) = &self.status {
+            text.push('\n');
+            text.push_str(status);
+        }
+        let skip = self.lines.len().saturating_sub(LOG_PANEL_LINES);
+        for line in self.lines.iter().skip(skip) {
+            text.push('\n');
+            text.push_str(line);
+        }
+        Some(text)
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct LogPanelText;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn container_room() -> Room {
+        Room {
+            id: "shop-Pod-web-1-container-api".to_string(),
+            name: "container api".to_string(),
+            kind: RoomKind::Container,
+            namespace: Some("shop".to_string()),
+            attributes: BTreeMap::from([
+                ("pod".to_string(), "web-1".to_string()),
+                ("container".to_string(), "api".to_string()),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_log_target_from_room() {
+        let room = container_room();
+        assert_eq!(
+            LogTarget::from_room(&room),
+            Some(LogTarget {
+                namespace: "shop".to_string(),
+                pod: "web-1".to_string(),
+                container: "api".to_string(),
+                context: None,
+            })
+        );
+        let pod_room = Room {
+            kind: RoomKind::Pod,
+            ..room
+        };
+        assert_eq!(LogTarget::from_room(&pod_room), None);
+    }
+
+    #[test]
+    fn test_log_panel_keeps_the_newest_lines() {
+        let room = container_room();
+        let mut panel = LogPanel {
+            options: LogOptions {
+                lines: 3,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let first = panel.open(&room.id, LogTarget::from_room(&room).unwrap());
+        let second = panel.open(&room.id, LogTarget::from_room(&room).unwrap());
+        assert!(!panel.push_lines(first.generation, vec!["stale".to_string()]));
+        assert!(!panel.wants(first.generation));
+        assert!(panel.wants(second.generation));
+        assert!(panel.push_lines(
+            second.generation,
+            (1..=5).map(|n| format!("line {n}")).collect()
+        ));
+        assert_eq!(panel.lines, ["line 3", "line 4", "line 5"]);
+        assert!(panel.text().unwrap().ends_with("line 3\nline 4\nline 5"));
+
+        panel.finish(second.generation, None);
+        assert_eq!(panel.status, None);
+        panel.close();
+        assert!(!panel.wants(second.generation));
+        assert!(!panel.push_lines(second.generation, vec!["late".to_string()]));
+        assert_eq!(panel.text(), None);
+    }
+}
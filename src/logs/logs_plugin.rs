@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+
+use super::{
+    logs_component::{LogPanel, LogRequest, LogSource, LogsPlugin},
+    logs_systems::{
+        answer_without_log_source, close_log_panel_on_leave, control_log_panel, setup_log_panel,
+        update_log_panel,
+    },
+};
+use crate::state::GameState;
+
+impl Plugin for LogsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LogPanel::default())
+            .add_event::<LogRequest>()
+            .add_systems(Startup, setup_log_panel)
+            .add_systems(
+                Update,
+                (
+                    control_log_panel.run_if(in_state(GameState::InGame)),
+                    close_log_panel_on_leave,
+                    answer_without_log_source.run_if(not(resource_exists::<LogSource>)),
+                    update_log_panel,
+                )
+                    .chain(),
+            );
+    }
+}
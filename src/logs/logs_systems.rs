@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use clap::Parser;
+use leafwing_input_manager::prelude::ActionState;
+
+use super::logs_component::{LogPanel, LogPanelText, LogRequest, LogTarget};
+use crate::{
+    cli::Cli,
+    player::{Player, player_component::Action},
+    world::world_component::CurrentFloorPlan,
+};
+
+pub fn setup_log_panel(mut panel: ResMut<LogPanel>) {
+    if let Some(lines) = Cli::parse().log_lines {
+        panel.options.lines = lines;
+    }
+}
+
+/**
+* in a container room the logs key opens the panel and closes it again, while it is open the
+* follow and previous keys flip those options and restart the stream
+*/
+pub fn control_log_panel(
+    player_query: Query<&ActionState<Action>, With<Player>>,
+    current_floorplan: Res<CurrentFloorPlan>,
+    mut panel: ResMut<LogPanel>,
+    mut requests: EventWriter<LogRequest>,
+) {
+    let Ok(action_state) = player_query.get_single() else {
+        return;
+    };
+    let here = current_floorplan.you_are_here.as_ref();
+
+    if panel.is_open() {
+        if action_state.just_pressed(&Action::Logs) {
+            panel.close();
+            return;
+        }
+        let follow = action_state.just_pressed(&Action::LogsFollow);
+        let previous = action_state.just_pressed(&Action::LogsPrevious);
+        if follow || previous {
+            if follow {
+                panel.options.follow = !panel.options.follow;
+            }
+            if previous {
+                panel.options.previous = !panel.options.previous;
+            }
+            if let Some(target) = panel.target.clone() {
+                let room_id = panel.room_id.clone();
+                requests.send(panel.open(&room_id, target));
+            }
+        }
+    } else if action_state.just_pressed(&Action::Logs)
+        && let Some(room) = here
+        && let Some(target) = LogTarget::from_room(room)
+    {
+        requests.send(panel.open(&room.id, target));
+    }
+}
+
+/// leaving the container room stops its logs
+pub fn close_log_panel_on_leave(
+    current_floorplan: Res<CurrentFloorPlan>,
+    mut panel: ResMut<LogPanel>,
+) {
+    let here = current_floorplan
+        .you_are_here
+        .as_ref()
+        .map(|room| room.id.as_str());
+    if panel.is_open() && here != Some(panel.room_id.as_str()) {
+        panel.close();
+    }
+}
+
+/// with no source to ask, every request is answered with why
+pub fn answer_without_log_source(
+    mut requests: EventReader<LogRequest>,
+    mut panel: ResMut<LogPanel>,
+) {
+    for request in requests.read() {
+        panel.finish(
+            request.generation,
+            Some("logs need a live cluster, run with --room-generator=k8s-live".to_string()),
+        );
+    }
+}
+
+/// show, update or remove the panel whenever its state changes
+pub fn update_log_panel(
+    mut commands: Commands,
+    panel: Res<LogPanel>,
+    mut text_query: Query<(Entity, &mut Text), With<LogPanelText>>,
+) {
+    if !panel.is_changed() {
+        return;
+    }
+
+    match (panel.text(), text_query.get_single_mut()) {
+        (Some(logs), Ok((_, mut text))) => text.0 = logs,
+        (Some(logs), Err(_)) => {
+            commands.spawn((
+                Text::new(logs),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                BackgroundColor(Color::BLACK.with_alpha(0.75)),
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(5.0),
+                    left: Val::Px(5.0),
+                    width: Val::Percent(55.0),
+                    padding: UiRect::all(Val::Px(6.0)),
+                    ..default()
+                },
+                LogPanelText,
+            ));
+        }
+        (None, Ok((entity, _))) => commands.entity(entity).despawn_recursive(),
+        (None, Err(_)) => {}
+    }
+}
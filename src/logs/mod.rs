@@ -0,0 +1,5 @@
+pub mod logs_component;
+pub mod logs_plugin;
+pub mod logs_systems;
+
+pub use logs_component::LogsPlugin;
@@ -2,7 +2,11 @@ use avian3d::PhysicsPlugins;
 use bevy_embedded_assets::{EmbeddedAssetPlugin, PluginMode};
 use bevy_tokio_tasks::TokioTasksPlugin;
 use floorplan::FloorPlanEvent;
+use history::HistoryPlugin;
+use inspector::InspectorPlugin;
 use integration::integration_plugin::IntegrationPlugin;
+use logs::LogsPlugin;
+use mutation::MutationPlugin;
 mod integration;
 use bevy::prelude::*;
 use camera::IsometricCameraPlugin;
@@ -10,13 +14,19 @@ use clap::Parser;
 #[cfg(feature = "perfmon")]
 use perf::PerfPlugin;
 use player::PlayerPlugin;
+use search::SearchPlugin;
 use state::StatePlugin;
 use world::WorldPlugin;
 mod camera;
 mod cli;
 mod floorplan;
+mod history;
+mod inspector;
+mod logs;
+mod mutation;
 mod perf;
 mod player;
+mod search;
 mod state;
 mod world;
 
@@ -39,6 +49,11 @@ fn main() {
             PerfPlugin,
             StatePlugin,
             PlayerPlugin,
+            SearchPlugin,
+            LogsPlugin,
+            InspectorPlugin,
+            MutationPlugin,
+            HistoryPlugin,
         ))
         .run();
 }
@@ -0,0 +1,5 @@
+pub mod mutation_component;
+pub mod mutation_plugin;
+pub mod mutation_systems;
+
+pub use mutation_component::MutationPlugin;
@@ -0,0 +1,504 @@
+use std::{
+    fmt::{Display, Formatter},
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use bevy::prelude::*;
+use k8s_openapi::jiff::Timestamp;
+
+use crate::floorplan::{Room, RoomKind};
+
+pub struct MutationPlugin;
+
+/// a change to the cluster the player can make from inside a room
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mutation {
+    DeletePod {
+        namespace: String,
+        name: String,
+    },
+    RestartDeployment {
+        namespace: String,
+        name: String,
+    },
+    ScaleDeployment {
+        namespace: String,
+        name: String,
+        replicas: u32,
+    },
+    CordonNode {
+        name: String,
+    },
+}
+
+impl Display for Mutation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DeletePod { namespace, name } => write!(f, "delete pod {namespace}/{name}"),
+            Self::RestartDeployment { namespace, name } => {
+                write!(f, "rollout-restart deployment {namespace}/{name}")
+            }
+            Self::ScaleDeployment {
+                namespace,
+                name,
+                replicas,
+            } => write!(
+                f,
+                "scale deployment {namespace}/{name} to {replicas} replicas"
+            ),
+            Self::CordonNode { name } => write!(f, "cordon node {name}"),
+        }
+    }
+}
+
+/// rooms are named "{kind} {name}", the resource name is what follows the kind
+fn resource_name(room: &Room, kind: &str) -> Option<String> {
+    room.name
+        .strip_prefix(kind)
+        .and_then(|name| name.strip_prefix(' '))
+        .map(String::from)
+}
+
+impl Mutation {
+    /// what can be done from a room - scaling starts from the replicas the deployment wants now
+    pub fn for_room(room: &Room) -> Vec<Self> {
+        let namespace = room.namespace.clone();
+        match (&room.kind, namespace) {
+            (RoomKind::Pod, Some(namespace)) => resource_name(room, "Pod")
+                .map(|name| vec![Self::DeletePod { namespace, name }])
+                .unwrap_or_default(),
+            (RoomKind::Deployment, Some(namespace)) => resource_name(room, "Deployment")
+                .map(|name| {
+                    vec![
+                        Self::RestartDeployment {
+                            namespace: namespace.clone(),
+                            name: name.clone(),
+                        },
+                        Self::ScaleDeployment {
+                            namespace,
+                            name,
+                            replicas: room.status.as_ref().map_or(1, |status| status.total),
+                        },
+                    ]
+                })
+                .unwrap_or_default(),
+            (RoomKind::Node, _) => resource_name(room, "Node")
+                .map(|name| vec![Self::CordonNode { name }])
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/**
+* asks the mutation source to make a change - as a server side dry run first, then for real once
+* the player has seen what the dry run said. `generation` tells the answers apart
+*/
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct MutationRequest {
+    pub generation: u64,
+    pub mutation: Mutation,
+    pub dry_run: bool,
+    /// the kubeconfig context of the cluster to change, `None` for the only one
+    pub context: Option<String>,
+}
+
+/// what the source made of a request - a short summary of the change, or why there was none
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct MutationOutcome {
+    pub request: MutationRequest,
+    pub result: Result<String, String>,
+}
+
+/// there is something to answer `MutationRequest`s, without it every request is refused
+#[derive(Resource, Default)]
+pub struct MutationSource;
+
+/// where the dialog is, from picking an action to reading how it went
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MutationStage {
+    #[default]
+    Choosing,
+    DryRun,
+    Confirming,
+    Applying,
+    Done,
+}
+
+/**
+* the cluster actions dialog - the actions of the room it was opened in, the one picked, and what
+* the server said about it. nothing is sent while mutations are not allowed
+*/
+#[derive(Resource, Debug, Default)]
+pub struct MutationDialog {
+    pub allowed: bool,
+    /// why mutations are not allowed although they were asked for
+    pub refusal: Option<String>,
+    pub room_name: String,
+    pub context: Option<String>,
+    pub choices: Vec<Mutation>,
+    pub selected: usize,
+    pub stage: MutationStage,
+    pub generation: u64,
+    /// the dry run's preview, the outcome, or why nothing happened
+    pub status: Option<String>,
+}
+
+impl MutationDialog {
+    pub fn open(&mut self, room: &Room) {
+        self.room_name.clone_from(&room.name);
+        self.context.clone_from(&room.context);
+        self.choices = Mutation::for_room(room);
+        self.selected = 0;
+        self.generation += 1;
+        if self.allowed {
+            self.stage = MutationStage::Choosing;
+            self.status = None;
+        } else {
+            self.stage = MutationStage::Done;
+            self.status = Some(self.refusal.clone().unwrap_or_else(|| {
+                "read-only - run with --allow-mutations to act on the cluster".to_string()
+            }));
+        }
+    }
+
+    pub fn select_by(&mut self, offset: isize) {
+        if self.stage == MutationStage::Choosing && !self.choices.is_empty() {
+            self.selected = self
+                .selected
+                .saturating_add_signed(offset)
+                .min(self.choices.len() - 1);
+        }
+    }
+
+    /// more or fewer replicas, when the picked action is a scale
+    pub fn scale_by(&mut self, offset: i32) {
+        if self.stage == MutationStage::Choosing
+            && let Some(Mutation::ScaleDeployment { replicas, .. }) =
+                self.choices.get_mut(self.selected)
+        {
+            *replicas = replicas.saturating_add_signed(offset);
+        }
+    }
+
+    fn request(&mut self, dry_run: bool) -> Option<MutationRequest> {
+        self.generation += 1;
+        Some(MutationRequest {
+            generation: self.generation,
+            mutation: self.choices.get(self.selected)?.clone(),
+            dry_run,
+            context: self.context.clone(),
+        })
+    }
+
+    /// the picked action goes to the server as a dry run, nothing changes yet
+    pub fn preview(&mut self) -> Option<MutationRequest> {
+        if !self.allowed || self.stage != MutationStage::Choosing {
+            return None;
+        }
+        let request = self.request(true)?;
+        self.stage = MutationStage::DryRun;
+        self.status = Some(format!("dry run of {}...", request.mutation));
+        Some(request)
+    }
+
+    /// only a dry run the server accepted can be confirmed
+    pub fn confirm(&mut self) -> Option<MutationRequest> {
+        if !self.allowed || self.stage != MutationStage::Confirming {
+            return None;
+        }
+        let request = self.request(false)?;
+        self.stage = MutationStage::Applying;
+        self.status = Some(format!("{}...", request.mutation));
+        Some(request)
+    }
+
+    /// answers to requests the dialog has moved on from are dropped
+    pub fn answer(&mut self, outcome: &MutationOutcome) {
+        if outcome.request.generation != self.generation {
+            return;
+        }
+        self.stage = match (self.stage, &outcome.result) {
+            (MutationStage::DryRun, Ok(_)) => MutationStage::Confirming,
+            _ => MutationStage::Done,
+        };
+        self.status = Some(match &outcome.result {
+            Ok(summary) => summary.clone(),
+            Err(e) => format!("{} failed: {e}", outcome.request.mutation),
+        });
+    }
+
+    pub fn text(&self) -> String {
+        let mut lines = vec![format!("cluster actions for {}", self.room_name)];
+        match self.stage {
+            MutationStage::Choosing => {
+                lines.extend(self.choices.iter().enumerate().map(|(i, mutation)| {
+                    let marker = if i == self.selected { ">" } else { " " };
+                    format!("{marker} {mutation}")
+                }));
+                lines.push(
+                    "up/down pick, left/right replicas, enter dry run, esc closes".to_string(),
+                );
+            }
+            MutationStage::Confirming => {
+                lines.extend(self.status.clone());
+                lines.push("y applies it, n or esc cancels".to_string());
+            }
+            MutationStage::DryRun | MutationStage::Applying => lines.extend(self.status.clone()),
+            MutationStage::Done => {
+                lines.extend(self.status.clone());
+                lines.push("enter or esc closes".to_string());
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/**
+* every change is appended to this file before it is sent, and every answer, dry run or not, once it
+* is back - one json object per line
+*/
+#[derive(Resource, Debug, Default)]
+pub struct AuditLog {
+    pub file: Option<File>,
+    pub context: Option<String>,
+}
+
+impl AuditLog {
+    /// opened up front, so a log that cannot be written stops mutations before any are made
+    pub fn open(path: &Path, context: Option<String>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Some(file),
+            context,
+        })
+    }
+
+    pub fn append(&mut self, entry: &str) -> io::Result<()> {
+        match &mut self.file {
+            Some(file) => writeln!(file, "{entry}"),
+            None => Err(io::Error::other("no audit log is open")),
+        }
+    }
+
+    /// a change about to be sent, before the cluster has had a say
+    pub fn request_entry(&self, now: Timestamp, request: &MutationRequest) -> String {
+        serde_json::json!({
+            "time": now.to_string(),
+            "context": request.context.as_ref().or(self.context.as_ref()),
+            "action": request.mutation.to_string(),
+            "dryRun": request.dry_run,
+            "requested": true,
+        })
+        .to_string()
+    }
+
+    pub fn entry(&self, now: Timestamp, outcome: &MutationOutcome) -> String {
+        let (result, error) = match &outcome.result {
+            Ok(summary) => (Some(summary), None),
+            Err(e) => (None, Some(e)),
+        };
+        serde_json::json!({
+            "time": now.to_string(),
+            "context": outcome.request.context.as_ref().or(self.context.as_ref()),
+            "action": outcome.request.mutation.to_string(),
+            "dryRun": outcome.request.dry_run,
+            "result": result,
+            "error": error,
+        })
+        .to_string()
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct MutationDialogText;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::floorplan::ResourceStatus;
+
+    fn deployment_room() -> Room {
+        Room {
+            id: "shop-Deployment-web".to_string(),
+            name: "Deployment web".to_string(),
+            kind: RoomKind::Deployment,
+            namespace: Some("shop".to_string()),
+            status: Some(ResourceStatus {
+                ready: 2,
+                total: 3,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_mutations_for_room() {
+        assert_eq!(
+            Mutation::for_room(&deployment_room()),
+            vec![
+                Mutation::RestartDeployment {
+                    namespace: "shop".to_string(),
+                    name: "web".to_string(),
+                },
+                Mutation::ScaleDeployment {
+                    namespace: "shop".to_string(),
+                    name: "web".to_string(),
+                    replicas: 3,
+                },
+            ]
+        );
+        let node = Room {
+            id: "Node-worker-1".to_string(),
+            name: "Node worker-1".to_string(),
+            kind: RoomKind::Node,
+            ..Default::default()
+        };
+        assert_eq!(
+            Mutation::for_room(&node),
+            vec![Mutation::CordonNode {
+                name: "worker-1".to_string()
+            }]
+        );
+        let service = Room {
+            name: "Service web".to_string(),
+            kind: RoomKind::Service,
+            ..deployment_room()
+        };
+        assert!(Mutation::for_room(&service).is_empty());
+    }
+
+    #[test]
+    fn test_read_only_dialog_sends_nothing() {
+        let mut dialog = MutationDialog::default();
+        dialog.open(&deployment_room());
+        assert_eq!(dialog.stage, MutationStage::Done);
+        assert_eq!(dialog.preview(), None);
+        assert!(dialog.text().contains("--allow-mutations"));
+    }
+
+    #[test]
+    fn test_dialog_dry_runs_before_applying() {
+        let mut dialog = MutationDialog {
+            allowed: true,
+            ..Default::default()
+        };
+        dialog.open(&deployment_room());
+        assert_eq!(dialog.confirm(), None);
+        dialog.select_by(1);
+        dialog.scale_by(2);
+        let dry_run = dialog.preview().unwrap();
+        assert!(dry_run.dry_run);
+        assert_eq!(
+            dry_run.mutation.to_string(),
+            "scale deployment shop/web to 5 replicas"
+        );
+
+        dialog.answer(&MutationOutcome {
+            request: dry_run.clone(),
+            result: Ok("deployment shop/web would go from 3 to 5 replicas".to_string()),
+        });
+        assert_eq!(dialog.stage, MutationStage::Confirming);
+        assert!(dialog.text().contains("would go from 3 to 5"));
+
+        let apply = dialog.confirm().unwrap();
+        assert!(!apply.dry_run);
+        assert_eq!(apply.mutation, dry_run.mutation);
+        // a late answer to the dry run changes nothing
+        dialog.answer(&MutationOutcome {
+            request: dry_run,
+            result: Err("too late".to_string()),
+        });
+        assert_eq!(dialog.stage, MutationStage::Applying);
+        dialog.answer(&MutationOutcome {
+            request: apply,
+            result: Err("forbidden".to_string()),
+        });
+        assert_eq!(dialog.stage, MutationStage::Done);
+        assert_eq!(
+            dialog.status.as_deref(),
+            Some("scale deployment shop/web to 5 replicas failed: forbidden")
+        );
+    }
+
+    #[test]
+    fn test_audit_entry() {
+        let audit = AuditLog {
+            context: Some("kind-dev".to_string()),
+            ..Default::default()
+        };
+        let outcome = MutationOutcome {
+            request: MutationRequest {
+                generation: 1,
+                mutation: Mutation::CordonNode {
+                    name: "worker-1".to_string(),
+                },
+                dry_run: false,
+                context: None,
+            },
+            result: Ok("node worker-1 cordoned".to_string()),
+        };
+        let entry: serde_json::Value =
+            serde_json::from_str(&audit.entry("2026-01-02T03:04:05Z".parse().unwrap(), &outcome))
+                .unwrap();
+        assert_eq!(
+            entry,
+            serde_json::json!({
+                "time": "2026-01-02T03:04:05Z",
+                "context": "kind-dev",
+                "action": "cordon node worker-1",
+                "dryRun": false,
+                "result": "node worker-1 cordoned",
+                "error": null,
+            })
+        );
+    }
+
+    #[test]
+    fn test_audit_log_is_written_before_applying() {
+        assert!(AuditLog::open(Path::new("/no-such-dir/doors-audit.log"), None).is_err());
+
+        let path = std::env::temp_dir().join("doors-audit-test.log");
+        let _ = std::fs::remove_file(&path);
+        let mut audit = AuditLog::open(&path, Some("kind-dev".to_string())).unwrap();
+        let request = MutationRequest {
+            generation: 2,
+            mutation: Mutation::DeletePod {
+                namespace: "shop".to_string(),
+                name: "web-1".to_string(),
+            },
+            dry_run: false,
+            context: None,
+        };
+        let entry = audit.request_entry("2026-01-02T03:04:05Z".parse().unwrap(), &request);
+        audit.append(&entry).unwrap();
+
+        let written: serde_json::Value =
+            serde_json::from_str(std::fs::read_to_string(&path).unwrap().trim()).unwrap();
+        assert_eq!(
+            written,
+            serde_json::json!({
+                "time": "2026-01-02T03:04:05Z",
+                "context": "kind-dev",
+                "action": "delete pod shop/web-1",
+                "dryRun": false,
+                "requested": true,
+            })
+        );
+        assert!(AuditLog::default().append(&entry).is_err());
+    }
+
+    #[test]
+    fn test_refused_dialog_says_why() {
+        let mut dialog = MutationDialog {
+            refusal: Some("no audit log".to_string()),
+            ..Default::default()
+        };
+        dialog.open(&deployment_room());
+        assert_eq!(dialog.preview(), None);
+        assert_eq!(dialog.status.as_deref(), Some("no audit log"));
+    }
+}
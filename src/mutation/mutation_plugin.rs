@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+
+use super::{
+    mutation_component::{
+        AuditLog, MutationDialog, MutationOutcome, MutationPlugin, MutationRequest, MutationSource,
+    },
+    mutation_systems::{
+        answer_without_mutation_source, despawn_mutation_dialog, mutation_dialog_input,
+        open_mutation_dialog, record_mutation_outcomes, setup_mutations, update_mutation_dialog,
+    },
+};
+use crate::state::GameState;
+
+impl Plugin for MutationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MutationDialog::default())
+            .insert_resource(AuditLog::default())
+            .add_event::<MutationRequest>()
+            .add_event::<MutationOutcome>()
+            .add_systems(Startup, setup_mutations)
+            .add_systems(
+                Update,
+                open_mutation_dialog.run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (
+                    mutation_dialog_input.run_if(in_state(GameState::Mutating)),
+                    answer_without_mutation_source.run_if(not(resource_exists::<MutationSource>)),
+                    record_mutation_outcomes,
+                    update_mutation_dialog.run_if(in_state(GameState::Mutating)),
+                )
+                    .chain(),
+            )
+            .add_systems(OnExit(GameState::Mutating), despawn_mutation_dialog);
+    }
+}
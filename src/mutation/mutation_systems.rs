@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use clap::Parser;
+use k8s_openapi::jiff::Timestamp;
+use leafwing_input_manager::prelude::ActionState;
+
+use super::mutation_component::{
+    AuditLog, MutationDialog, MutationDialogText, MutationOutcome, MutationRequest, MutationStage,
+};
+use crate::{
+    cli::Cli,
+    player::{Player, player_component::Action},
+    state::GameState,
+    world::world_component::CurrentFloorPlan,
+};
+
+/// mutations are only allowed once the audit log is open, so none can go unrecorded
+pub fn setup_mutations(mut dialog: ResMut<MutationDialog>, mut audit: ResMut<AuditLog>) {
+    let cli = Cli::parse();
+    if !cli.allow_mutations {
+        return;
+    }
+    let Some(path) = cli.audit_log else {
+        dialog.refusal = Some("read-only - --allow-mutations needs an --audit-log".to_string());
+        return;
+    };
+    match AuditLog::open(&path, cli.contexts.first().cloned()) {
+        Ok(opened) => {
+            *audit = opened;
+            dialog.allowed = true;
+        }
+        Err(e) => {
+            error!("Unable to open the audit log {}: {e}", path.display());
+            dialog.refusal = Some(format!(
+                "read-only - unable to open the audit log {}: {e}",
+                path.display()
+            ));
+        }
+    }
+}
+
+/// the mutate key opens the dialog in any room there is something to do in
+pub fn open_mutation_dialog(
+    player_query: Query<&ActionState<Action>, With<Player>>,
+    current_floorplan: Res<CurrentFloorPlan>,
+    mut dialog: ResMut<MutationDialog>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let Ok(action_state) = player_query.get_single()
+        && action_state.just_pressed(&Action::Mutate)
+        && let Some(room) = &current_floorplan.you_are_here
+    {
+        dialog.open(room);
+        if !dialog.choices.is_empty() {
+            next_state.set(GameState::Mutating);
+        }
+    }
+}
+
+pub fn mutation_dialog_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut dialog: ResMut<MutationDialog>,
+    mut audit: ResMut<AuditLog>,
+    mut requests: EventWriter<MutationRequest>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let closing = match dialog.stage {
+        MutationStage::Confirming => keys.any_just_pressed([KeyCode::Escape, KeyCode::KeyN]),
+        MutationStage::Done => keys.any_just_pressed([KeyCode::Escape, KeyCode::Enter]),
+        _ => keys.just_pressed(KeyCode::Escape),
+    };
+    if closing {
+        // an answer still on its way is dropped, the audit log has it all the same
+        dialog.generation += 1;
+        next_state.set(GameState::InGame);
+        return;
+    }
+    for (key, offset) in [(KeyCode::ArrowDown, 1), (KeyCode::ArrowUp, -1)] {
+        if keys.just_pressed(key) {
+            dialog.select_by(offset);
+        }
+    }
+    for (key, offset) in [(KeyCode::ArrowRight, 1), (KeyCode::ArrowLeft, -1)] {
+        if keys.just_pressed(key) {
+            dialog.scale_by(offset);
+        }
+    }
+    if keys.just_pressed(KeyCode::Enter)
+        && let Some(request) = dialog.preview()
+    {
+        requests.send(request);
+    }
+    if keys.just_pressed(KeyCode::KeyY)
+        && let Some(request) = dialog.confirm()
+    {
+        // the change is on record before the cluster sees it, or it is not sent at all
+        let entry = audit.request_entry(Timestamp::now(), &request);
+        match audit.append(&entry) {
+            Ok(()) => {
+                requests.send(request);
+            }
+            Err(e) => dialog.answer(&MutationOutcome {
+                request,
+                result: Err(format!("not sent, the audit log could not be written: {e}")),
+            }),
+        }
+    }
+}
+
+/// with no source to ask, every request is refused with why
+pub fn answer_without_mutation_source(
+    mut requests: EventReader<MutationRequest>,
+    mut outcomes: EventWriter<MutationOutcome>,
+) {
+    for request in requests.read() {
+        outcomes.send(MutationOutcome {
+            request: request.clone(),
+            result: Err(
+                "cluster actions need a live cluster, run with --room-generator=k8s-live"
+                    .to_string(),
+            ),
+        });
+    }
+}
+
+/// every outcome, dry run or not, goes to the audit log before the dialog shows it
+pub fn record_mutation_outcomes(
+    mut outcomes: EventReader<MutationOutcome>,
+    mut audit: ResMut<AuditLog>,
+    mut dialog: ResMut<MutationDialog>,
+) {
+    for outcome in outcomes.read() {
+        let entry = audit.entry(Timestamp::now(), outcome);
+        if let Err(e) = audit.append(&entry) {
+            error!("Unable to write the audit log: {e}");
+        }
+        info!(
+            "{}{}: {:?}",
+            outcome.request.mutation,
+            if outcome.request.dry_run {
+                " (dry run)"
+            } else {
+                ""
+            },
+            outcome.result
+        );
+        dialog.answer(outcome);
+    }
+}
+
+pub fn update_mutation_dialog(
+    mut commands: Commands,
+    dialog: Res<MutationDialog>,
+    mut text_query: Query<&mut Text, With<MutationDialogText>>,
+) {
+    if !dialog.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = text_query.get_single_mut() {
+        text.0 = dialog.text();
+    } else {
+        commands.spawn((
+            Text::new(dialog.text()),
+            TextFont {
+                font_size: 15.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            BackgroundColor(Color::BLACK.with_alpha(0.85)),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(30.0),
+                left: Val::Percent(25.0),
+                width: Val::Percent(50.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            MutationDialogText,
+        ));
+    }
+}
+
+pub fn despawn_mutation_dialog(
+    mut commands: Commands,
+    query: Query<Entity, With<MutationDialogText>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,3 +1,4 @@
+use crate::floorplan::FloorPlanDiff;
 use crate::player::player_component::GroundedState;
 use crate::state::GameState;
 use crate::world::world_component::{CurrentFloorPlan, NextFloorPlan};
@@ -407,6 +408,57 @@ impl PerfUiEntry for RoomName {
     }
 }
 
+#[derive(Component)]
+#[require(PerfUiRoot)]
+pub struct RoomKindText {
+    pub sort_key: i32,
+}
+
+impl Default for RoomKindText {
+    fn default() -> Self {
+        Self {
+            sort_key: iyes_perf_ui::utils::next_sort_key(),
+        }
+    }
+}
+
+impl PerfUiEntry for RoomKindText {
+    type Value = String;
+    type SystemParam = SRes<CurrentFloorPlan>;
+
+    fn label(&self) -> &'static str {
+        "Room Kind"
+    }
+
+    fn sort_key(&self) -> i32 {
+        self.sort_key
+    }
+
+    fn update_value(
+        &self,
+        plan: &mut <Self::SystemParam as SystemParam>::Item<'_, '_>,
+    ) -> Option<Self::Value> {
+        plan.you_are_here
+            .as_ref()
+            .map(|room| match &room.namespace {
+                Some(namespace) => format!("{} ({namespace})", room.kind),
+                None => room.kind.to_string(),
+            })
+    }
+
+    fn format_value(&self, value: &Self::Value) -> String {
+        value.to_string()
+    }
+
+    fn value_color(&self, _value: &Self::Value) -> Option<Color> {
+        None
+    }
+
+    fn value_highlight(&self, _value: &Self::Value) -> bool {
+        false
+    }
+}
+
 #[derive(Component)]
 #[require(PerfUiRoot)]
 pub struct PlayerIsGrounded {
@@ -486,6 +538,10 @@ impl PerfUiEntry for GameStateText {
         match state.get() {
             GameState::InGame => Some("In Game".to_string()),
             GameState::Paused => Some("Paused".to_string()),
+            GameState::Searching => Some("Searching".to_string()),
+            GameState::Inspecting => Some("Inspecting".to_string()),
+            GameState::Mutating => Some("Mutating".to_string()),
+            GameState::TimeTravelling => Some("Time Travelling".to_string()),
             GameState::TransitioningOutSetup => Some("Transitioning Out Setup".to_string()),
             GameState::TransitioningOut => Some("Transitioning Out".to_string()),
             GameState::TransitioningInSetup => Some("Transitioning In Setup".to_string()),
@@ -507,3 +563,49 @@ impl PerfUiEntry for GameStateText {
         false
     }
 }
+
+#[derive(Component)]
+#[require(PerfUiRoot)]
+pub struct NextFloorplanChanges {
+    pub sort_key: i32,
+}
+
+impl Default for NextFloorplanChanges {
+    fn default() -> Self {
+        Self {
+            sort_key: iyes_perf_ui::utils::next_sort_key(),
+        }
+    }
+}
+
+impl PerfUiEntry for NextFloorplanChanges {
+    type Value = String;
+    type SystemParam = SRes<NextFloorPlan>;
+
+    fn label(&self) -> &'static str {
+        "Next Floorplan Changes"
+    }
+
+    fn sort_key(&self) -> i32 {
+        self.sort_key
+    }
+
+    fn update_value(
+        &self,
+        plan: &mut <Self::SystemParam as SystemParam>::Item<'_, '_>,
+    ) -> Option<Self::Value> {
+        plan.diff.as_ref().map(FloorPlanDiff::summary)
+    }
+
+    fn format_value(&self, value: &Self::Value) -> String {
+        value.to_string()
+    }
+
+    fn value_color(&self, _value: &Self::Value) -> Option<Color> {
+        None
+    }
+
+    fn value_highlight(&self, _value: &Self::Value) -> bool {
+        true
+    }
+}
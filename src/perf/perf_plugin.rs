@@ -1,6 +1,6 @@
 use super::perf_component::{
-    GameStateText, PlayerIsGrounded, RoomName, TimeInRoom, TimeSinceLastFloorplanRefresh,
-    TimeSinceNextFloorplanCreated,
+    GameStateText, NextFloorplanChanges, PlayerIsGrounded, RoomKindText, RoomName, TimeInRoom,
+    TimeSinceLastFloorplanRefresh, TimeSinceNextFloorplanCreated,
 };
 use super::perf_system::{toggle_builtins, toggle_customs};
 use super::{WorldEdgeCount, WorldNodeCount};
@@ -29,8 +29,10 @@ impl Plugin for PerfPlugin {
             .add_perf_ui_simple_entry::<WorldEdgeCount>()
             .add_perf_ui_simple_entry::<TimeSinceLastFloorplanRefresh>()
             .add_perf_ui_simple_entry::<TimeSinceNextFloorplanCreated>()
+            .add_perf_ui_simple_entry::<NextFloorplanChanges>()
             .add_perf_ui_simple_entry::<TimeInRoom>()
             .add_perf_ui_simple_entry::<RoomName>()
+            .add_perf_ui_simple_entry::<RoomKindText>()
             .add_perf_ui_simple_entry::<PlayerIsGrounded>()
             .add_perf_ui_simple_entry::<GameStateText>()
             .add_systems(
@@ -1,5 +1,7 @@
 use super::perf_component::GameStateText;
+use super::perf_component::NextFloorplanChanges;
 use super::perf_component::PlayerIsGrounded;
+use super::perf_component::RoomKindText;
 use super::perf_component::RoomName;
 use super::perf_component::SystemMonitor;
 use super::perf_component::TimeInRoom;
@@ -46,8 +48,10 @@ pub fn toggle_customs(
                 WorldEdgeCount::default(),
                 TimeSinceLastFloorplanRefresh::default(),
                 TimeSinceNextFloorplanCreated::default(),
+                NextFloorplanChanges::default(),
                 TimeInRoom::default(),
                 RoomName::default(),
+                RoomKindText::default(),
                 PlayerIsGrounded::default(),
                 GameStateText::default(),
             ));
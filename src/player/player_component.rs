@@ -14,6 +14,15 @@ pub enum Action {
     MoveLeft,
     MoveRight,
     Jump,
+    Target,
+    Search,
+    Logs,
+    LogsFollow,
+    LogsPrevious,
+    Inspect,
+    Mutate,
+    Timeline,
+    Regroup,
 }
 
 #[derive(Resource, Default)]
@@ -62,6 +71,15 @@ impl PlayerBundle {
             (Action::MoveRight, KeyCode::ArrowRight),
             (Action::Jump, KeyCode::Space),
             (Action::Open, KeyCode::ShiftLeft),
+            (Action::Target, KeyCode::KeyT),
+            (Action::Search, KeyCode::Slash),
+            (Action::Logs, KeyCode::KeyL),
+            (Action::LogsFollow, KeyCode::KeyF),
+            (Action::LogsPrevious, KeyCode::KeyP),
+            (Action::Inspect, KeyCode::KeyI),
+            (Action::Mutate, KeyCode::KeyM),
+            (Action::Timeline, KeyCode::KeyH),
+            (Action::Regroup, KeyCode::KeyG),
         ]);
 
         Self {
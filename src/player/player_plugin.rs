@@ -3,7 +3,8 @@ use crate::state::GameState;
 use super::{
     player_component::{Action, GroundedState, PlayerConfig, PlayerStartPosition},
     player_systems::{
-        detect_enter_door, detect_grounded, detect_wall_collision, player_movement, spawn_player,
+        detect_enter_door, detect_grounded, detect_wall_collision, player_movement,
+        select_navigation_target, spawn_player,
     },
 };
 use bevy::prelude::*;
@@ -51,8 +52,15 @@ impl Plugin for PlayerPlugin {
                     player_movement,
                     detect_enter_door,
                     detect_wall_collision,
+                    select_navigation_target,
                 )
-                    .chain(),
+                    .chain()
+                    .run_if(
+                        not(in_state(GameState::Searching))
+                            .and(not(in_state(GameState::Inspecting)))
+                            .and(not(in_state(GameState::Mutating)))
+                            .and(not(in_state(GameState::TimeTravelling))),
+                    ),
             );
     }
 }
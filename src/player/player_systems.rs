@@ -3,10 +3,11 @@ use super::player_component::{
     PlayerStartPosition, PlayerState,
 };
 use crate::{
-    floorplan::{Door, Room},
+    floorplan::{Door, DoorKind, Room},
     state::{GameState, state_component::GameOverReason},
     world::world_component::{
-        CurrentFloorPlan, DisplayRoomInfoEvent, Floor, PlatformMarker, Wall, WallState,
+        CurrentFloorPlan, DisplayRoomInfoEvent, Floor, NavigationTarget, PlatformMarker, Wall,
+        WallState, WorldConfig,
     },
 };
 use avian3d::prelude::*;
@@ -181,7 +182,7 @@ pub fn detect_grounded(
 fn find_door_collision(
     collision: &Collision,
     door_query: &Query<(Entity, &Transform, &Parent, &Door)>,
-) -> Option<Entity> {
+) -> Option<(Entity, DoorKind)> {
     let contacts = &collision.0;
     let involved_entities = [contacts.entity1, contacts.entity2];
     if contacts.is_sensor {
@@ -189,8 +190,8 @@ fn find_door_collision(
     }
 
     for entity in &involved_entities {
-        if let Ok((_entity, _transform, parent, _door)) = door_query.get(*entity) {
-            return Some(parent.get());
+        if let Ok((_entity, _transform, parent, door)) = door_query.get(*entity) {
+            return Some((parent.get(), door.kind));
         }
     }
 
@@ -212,17 +213,13 @@ pub fn detect_enter_door(
 ) {
     if let Ok((player, transform, action_state)) = player_query.get_single_mut() {
         for collision in collision_events.read() {
-            if let Some(room_entity) = find_door_collision(collision, &door_query)
+            if let Some((room_entity, door)) = find_door_collision(collision, &door_query)
                 && let Ok(room) = room_query.get(room_entity)
             {
-                if action_state.just_pressed(&Action::Open) {
+                if action_state.just_pressed(&Action::Open) && room.locked.is_none() {
                     debug!("Entering room: {:?}", room);
-                    *current_floorplan = CurrentFloorPlan {
-                        floorplan: current_floorplan.floorplan.clone(),
-                        you_are_here: Some(room.clone()),
-                        previous_room: current_floorplan.you_are_here.clone(),
-                        ..Default::default()
-                    };
+                    *current_floorplan = current_floorplan
+                        .enter(room.clone(), current_floorplan.you_are_here.clone());
 
                     start_position.position = Some(transform.translation);
 
@@ -233,6 +230,7 @@ pub fn detect_enter_door(
                     events.send(DisplayRoomInfoEvent {
                         room: room.clone(),
                         you_are_here: current_floorplan.you_are_here.clone(),
+                        door,
                     });
                 }
             }
@@ -259,3 +257,44 @@ pub fn detect_wall_collision(
         }
     }
 }
+
+/// stand on any room tile and press the target key to be guided there, press it again to cancel
+pub fn select_navigation_target(
+    world_config: Res<WorldConfig>,
+    player_query: Query<(&Transform, &ActionState<Action>), With<Player>>,
+    room_query: Query<(&GlobalTransform, &Room)>,
+    mut navigation_target: ResMut<NavigationTarget>,
+    mut events: EventWriter<DisplayRoomInfoEvent>,
+) {
+    if let Ok((transform, action_state)) = player_query.get_single()
+        && action_state.just_pressed(&Action::Target)
+    {
+        let player_xz = transform.translation.xz();
+        let nearest = room_query
+            .iter()
+            .map(|(room_transform, room)| {
+                (room_transform.translation().xz().distance(player_xz), room)
+            })
+            .filter(|(distance, _)| *distance <= world_config.spacing / 2.0)
+            .min_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        if let Some((_, room)) = nearest {
+            if navigation_target
+                .room
+                .as_ref()
+                .is_some_and(|target| target.id == room.id)
+            {
+                debug!("Clearing navigation target: {:?}", room);
+                navigation_target.room = None;
+            } else {
+                debug!("Navigation target: {:?}", room);
+                navigation_target.room = Some(room.clone());
+                events.send(DisplayRoomInfoEvent {
+                    room: room.clone(),
+                    you_are_here: None,
+                    door: DoorKind::Passage,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+/**
+* subsequence matching in the spirit of fzf - every query character must appear in order, runs of
+* consecutive matches and matches at word starts score higher, and `None` means no match at all
+*/
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    // greedy matching from the first hit misses better alignments later on, so try every start
+    let best = (0..candidate.len())
+        .filter(|start| candidate[*start] == query[0])
+        .filter_map(|start| score_from(&query, &candidate, start))
+        .max()?;
+
+    // prefer shorter candidates when everything else is equal
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    Some(best * 100 - candidate.len() as i32)
+}
+
+fn score_from(query: &[char], candidate: &[char], start: usize) -> Option<i32> {
+    let mut score = 0;
+    let mut query_pos = 0;
+    let mut previous_match: Option<usize> = None;
+    for (pos, c) in candidate.iter().enumerate().skip(start) {
+        if query_pos < query.len() && *c == query[query_pos] {
+            score += 1;
+            if previous_match.is_some_and(|previous| previous + 1 == pos) {
+                score += 5;
+            }
+            if pos == 0 || !candidate[pos - 1].is_alphanumeric() {
+                score += 3;
+            }
+            previous_match = Some(pos);
+            query_pos += 1;
+        }
+    }
+    (query_pos == query.len()).then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subsequence_match() {
+        assert!(fuzzy_score("pay7f9", "Pod payments-api-7f9").is_some());
+        assert!(fuzzy_score("PAYMENTS", "Pod payments-api-7f9").is_some());
+        assert!(fuzzy_score("", "anything").is_some());
+        assert!(fuzzy_score("9f7", "Pod payments-api-7f9").is_none());
+        assert!(fuzzy_score("payments-api-7f9x", "Pod payments-api-7f9").is_none());
+    }
+
+    #[test]
+    fn test_ranking() {
+        let consecutive = fuzzy_score("api", "Pod payments-api-7f9").unwrap();
+        let scattered = fuzzy_score("api", "Pod a-pi-i").unwrap();
+        assert!(consecutive > scattered);
+
+        let word_start = fuzzy_score("cm", "ConfigMap coredns-map").unwrap();
+        let mid_word = fuzzy_score("cm", "Deployment acme").unwrap();
+        assert!(word_start > mid_word);
+
+        let short = fuzzy_score("pod", "Pod a").unwrap();
+        let long = fuzzy_score("pod", "Pod aaaaaaaa").unwrap();
+        assert!(short > long);
+    }
+}
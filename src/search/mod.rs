@@ -0,0 +1,6 @@
+pub mod fuzzy;
+pub mod search_component;
+pub mod search_plugin;
+pub mod search_systems;
+
+pub use search_component::SearchPlugin;
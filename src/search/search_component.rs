@@ -0,0 +1,20 @@
+use bevy::prelude::*;
+
+use crate::floorplan::Room;
+
+pub const MAX_SEARCH_RESULTS: usize = 10;
+
+pub struct SearchPlugin;
+
+#[derive(Resource, Default, Debug)]
+pub struct RoomSearch {
+    pub query: String,
+    pub results: Vec<Room>,
+    pub selected: usize,
+}
+
+#[derive(Debug, Component)]
+pub struct SearchOverlay;
+
+#[derive(Debug, Component)]
+pub struct SearchOverlayText;
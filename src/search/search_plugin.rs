@@ -0,0 +1,27 @@
+use bevy::prelude::*;
+
+use super::{
+    search_component::{RoomSearch, SearchPlugin},
+    search_systems::{
+        despawn_search_overlay, open_search, search_input, spawn_search_overlay,
+        update_search_overlay,
+    },
+};
+use crate::state::GameState;
+
+impl Plugin for SearchPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RoomSearch::default())
+            .add_systems(Update, open_search.run_if(in_state(GameState::InGame)))
+            .add_systems(OnEnter(GameState::Searching), spawn_search_overlay)
+            .add_systems(OnExit(GameState::Searching), despawn_search_overlay)
+            .add_systems(
+                Update,
+                (
+                    search_input,
+                    update_search_overlay.run_if(in_state(GameState::Searching)),
+                )
+                    .chain(),
+            );
+    }
+}
@@ -0,0 +1,272 @@
+use bevy::{
+    input::{
+        ButtonState,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+};
+use leafwing_input_manager::prelude::ActionState;
+
+use super::{
+    fuzzy::fuzzy_score,
+    search_component::{MAX_SEARCH_RESULTS, RoomSearch, SearchOverlay, SearchOverlayText},
+};
+use crate::{
+    floorplan::{FloorPlan, Room},
+    player::{
+        Player,
+        player_component::{Action, PlayerStartPosition},
+    },
+    state::GameState,
+    world::world_component::CurrentFloorPlan,
+};
+
+fn find_rooms(floorplan: &FloorPlan, query: &str) -> Vec<Room> {
+    let mut matches: Vec<(i32, &Room)> = floorplan
+        .get_all_room_ids()
+        .iter()
+        .filter_map(|id| floorplan.get_room_by_id(id).ok())
+        .filter_map(|room| {
+            let score = fuzzy_score(query, &room.name).max(fuzzy_score(query, &room.id))?;
+            Some((score, room))
+        })
+        .collect();
+    matches.sort_by(|(a_score, a), (b_score, b)| b_score.cmp(a_score).then(a.name.cmp(&b.name)));
+    matches
+        .into_iter()
+        .take(MAX_SEARCH_RESULTS)
+        .map(|(_, room)| room.clone())
+        .collect()
+}
+
+/**
+* a teleported player still needs a way back out, so pretend they arrived through the room their
+* exit door leads to
+*/
+fn choose_previous_room(floorplan: &FloorPlan, room: &Room) -> Option<Room> {
+    let doors_and_rooms = floorplan.get_doors_and_connected_rooms(&room.id).ok()?;
+    doors_and_rooms
+        .iter()
+        .find(|(door, _)| door.is_exit)
+        .or_else(|| doors_and_rooms.first())
+        .map(|(_, room)| (*room).clone())
+}
+
+pub fn open_search(
+    player_query: Query<&ActionState<Action>, With<Player>>,
+    mut search: ResMut<RoomSearch>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let Ok(action_state) = player_query.get_single()
+        && action_state.just_pressed(&Action::Search)
+    {
+        *search = RoomSearch::default();
+        next_state.set(GameState::Searching);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn search_input(
+    mut commands: Commands,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut search: ResMut<RoomSearch>,
+    mut current_floorplan: ResMut<CurrentFloorPlan>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    mut start_position: ResMut<PlayerStartPosition>,
+) {
+    // always drain the reader so the key that opened the search is not typed into it
+    let searching = *state.get() == GameState::Searching;
+    let mut query_changed = false;
+    for event in keyboard_events.read() {
+        if !searching || event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Escape => next_state.set(GameState::InGame),
+            Key::Enter => {
+                if let Some(room) = search.results.get(search.selected).cloned() {
+                    debug!("Teleporting to room: {:?}", room);
+                    let previous_room = current_floorplan
+                        .floorplan
+                        .as_ref()
+                        .and_then(|floorplan| choose_previous_room(floorplan, &room));
+                    *current_floorplan = current_floorplan.enter(room, previous_room);
+                    if let Ok((player, transform)) = player_query.get_single() {
+                        start_position.position = Some(transform.translation);
+                        commands.entity(player).despawn();
+                    }
+                    next_state.set(GameState::TransitioningOutSetup);
+                }
+            }
+            Key::ArrowDown => {
+                search.selected = (search.selected + 1).min(search.results.len().saturating_sub(1));
+            }
+            Key::ArrowUp => search.selected = search.selected.saturating_sub(1),
+            Key::Backspace => {
+                search.query.pop();
+                query_changed = true;
+            }
+            Key::Space => {
+                search.query.push(' ');
+                query_changed = true;
+            }
+            Key::Character(text) => {
+                search
+                    .query
+                    .extend(text.chars().filter(|c| !c.is_control()));
+                query_changed = true;
+            }
+            _ => {}
+        }
+    }
+
+    if query_changed {
+        search.results = current_floorplan
+            .floorplan
+            .as_ref()
+            .map(|floorplan| find_rooms(floorplan, &search.query))
+            .unwrap_or_default();
+        search.selected = 0;
+    }
+}
+
+pub fn spawn_search_overlay(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut search: ResMut<RoomSearch>,
+    current_floorplan: Res<CurrentFloorPlan>,
+) {
+    search.results = current_floorplan
+        .floorplan
+        .as_ref()
+        .map(|floorplan| find_rooms(floorplan, &search.query))
+        .unwrap_or_default();
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(5.0),
+                left: Val::Percent(25.0),
+                width: Val::Percent(50.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+            SearchOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 24.0,
+                    ..default()
+                },
+                SearchOverlayText,
+            ));
+        });
+}
+
+pub fn despawn_search_overlay(mut commands: Commands, query: Query<Entity, With<SearchOverlay>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn update_search_overlay(
+    search: Res<RoomSearch>,
+    mut query: Query<&mut Text, With<SearchOverlayText>>,
+) {
+    if !search.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = query.get_single_mut() {
+        let results = search
+            .results
+            .iter()
+            .enumerate()
+            .map(|(i, room)| {
+                let marker = if i == search.selected { ">" } else { " " };
+                format!("{marker} {}", room.name)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        text.0 = format!("find room: {}_\n\n{results}", search.query);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::floorplan::Door;
+
+    fn create_floorplan() -> FloorPlan {
+        let mut floorplan = FloorPlan::new();
+        let lobby = floorplan.add_room(Room {
+            id: "cluster".to_string(),
+            name: "Cluster Lobby".to_string(),
+            ..Default::default()
+        });
+        for (i, pod) in ["payments-api-7f9", "payments-db-0", "checkout-5d1"]
+            .iter()
+            .enumerate()
+        {
+            let room = floorplan.add_room(Room {
+                id: format!("default-Pod-{pod}"),
+                name: format!("Pod {pod}"),
+                ..Default::default()
+            });
+            floorplan.add_door(
+                lobby,
+                room,
+                Door {
+                    id: (i * 2).to_string(),
+                    name: format!("Door to Pod {pod}"),
+                    is_exit: false,
+                    ..Default::default()
+                },
+            );
+            floorplan.add_door(
+                room,
+                lobby,
+                Door {
+                    id: (i * 2 + 1).to_string(),
+                    name: "Door to Cluster Lobby".to_string(),
+                    is_exit: true,
+                    ..Default::default()
+                },
+            );
+        }
+        floorplan
+    }
+
+    #[test]
+    fn test_find_rooms() {
+        let floorplan = create_floorplan();
+        let names: Vec<String> = find_rooms(&floorplan, "payapi")
+            .into_iter()
+            .map(|room| room.name)
+            .collect();
+        assert_eq!(names, vec!["Pod payments-api-7f9"]);
+
+        let results = find_rooms(&floorplan, "payments");
+        assert_eq!(results.len(), 2);
+
+        assert_eq!(find_rooms(&floorplan, "").len(), 4);
+        assert!(find_rooms(&floorplan, "zzz").is_empty());
+    }
+
+    #[test]
+    fn test_choose_previous_room() {
+        let floorplan = create_floorplan();
+        let pod = floorplan
+            .get_room_by_id("default-Pod-checkout-5d1")
+            .unwrap()
+            .clone();
+        let previous = choose_previous_room(&floorplan, &pod).unwrap();
+        assert_eq!(previous.id, "cluster");
+    }
+}
@@ -12,6 +12,13 @@ pub enum GameState {
     #[default]
     InGame,
     Paused,
+    Searching,
+    /// the resource inspector is open, the player waits
+    Inspecting,
+    /// the cluster actions dialog is open, the player waits
+    Mutating,
+    /// the timeline is open, the player waits
+    TimeTravelling,
     TransitioningOutSetup,
     TransitioningOut,
     TransitioningInSetup,
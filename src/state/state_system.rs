@@ -27,6 +27,16 @@ pub fn handle_pause_events(
             _ => (),
         }
     } else if keyboard_input.just_pressed(KeyCode::KeyQ) {
+        if matches!(
+            state.get(),
+            GameState::Searching
+                | GameState::Inspecting
+                | GameState::Mutating
+                | GameState::TimeTravelling
+        ) {
+            // "q" is just another letter while typing a search, and open panels close first
+            return;
+        }
         if *state != GameState::InGame {
             // Quit the game immediately - they already are in a paused state
             std::process::exit(0);
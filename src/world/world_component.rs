@@ -4,12 +4,14 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
-use crate::floorplan::{FloorPlan, Room};
+use crate::floorplan::{DoorKind, FloorPlan, FloorPlanDiff, Room};
 
 #[derive(Event)]
 pub struct DisplayRoomInfoEvent {
     pub room: Room,
     pub you_are_here: Option<Room>,
+    /// the door between the two, which says how the rooms relate
+    pub door: DoorKind,
 }
 
 #[derive(Resource)]
@@ -46,6 +48,7 @@ pub struct Floor {}
 pub struct NextFloorPlan {
     pub floorplan: Option<FloorPlan>,
     pub created: Option<Duration>, // update every time we modify due to changes in the external world
+    pub diff: Option<FloorPlanDiff>, // what the on-deck floorplan changes relative to the current one
 }
 
 #[derive(Default, Resource, Debug)]
@@ -55,6 +58,42 @@ pub struct CurrentFloorPlan {
     pub time_in_room: Duration,
     pub you_are_here: Option<Room>,
     pub previous_room: Option<Room>,
+    /// showing a floorplan from the timeline - live ones are not put on deck until it is back to now
+    pub rewound: bool,
+}
+
+impl CurrentFloorPlan {
+    /// the same world with the player in another room, the time in it starting over
+    pub fn enter(&self, room: Room, previous_room: Option<Room>) -> Self {
+        Self {
+            floorplan: self.floorplan.clone(),
+            refreshed: self.refreshed,
+            you_are_here: Some(room),
+            previous_room,
+            rewound: self.rewound,
+            ..Default::default()
+        }
+    }
+}
+
+/// the room the player asked to be guided to - the next door on the way is highlighted
+#[derive(Default, Resource, Debug)]
+pub struct NavigationTarget {
+    pub room: Option<Room>,
+}
+
+/// marks the room and door of a failing workload so their glow can be animated
+#[derive(Component, Default)]
+pub struct Pulsing;
+
+/// a door into a room the user may not look inside, it stays dark and shut
+#[derive(Component, Default)]
+pub struct LockedDoor;
+
+/// screen-space label that follows the room of a pod or container that has restarted
+#[derive(Component)]
+pub struct RestartBadge {
+    pub room: Entity,
 }
 
 #[derive(Component, Default)]
@@ -89,3 +128,35 @@ impl Default for WorldConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entering_a_room_keeps_the_world_rewound() {
+        let lobby = Room {
+            id: "cluster".to_string(),
+            ..Default::default()
+        };
+        let pod = Room {
+            id: "default-Pod-web".to_string(),
+            ..Default::default()
+        };
+        let current = CurrentFloorPlan {
+            floorplan: Some(FloorPlan::new()),
+            refreshed: Duration::from_secs(3),
+            time_in_room: Duration::from_secs(2),
+            you_are_here: Some(lobby.clone()),
+            rewound: true,
+            ..Default::default()
+        };
+
+        let entered = current.enter(pod.clone(), Some(lobby.clone()));
+        assert!(entered.rewound);
+        assert_eq!(entered.refreshed, Duration::from_secs(3));
+        assert_eq!(entered.time_in_room, Duration::ZERO);
+        assert_eq!(entered.you_are_here, Some(pod));
+        assert_eq!(entered.previous_room, Some(lobby));
+    }
+}
@@ -1,10 +1,12 @@
 use super::{
     world_component::{
-        CurrentFloorPlan, DisplayRoomInfoEvent, NextFloorPlan, WorldConfig, WorldPlugin,
+        CurrentFloorPlan, DisplayRoomInfoEvent, NavigationTarget, NextFloorPlan, WorldConfig,
+        WorldPlugin,
     },
     world_systems::{
-        display_room_info_text, handle_floor_plan_event, platform_transition_in,
-        platform_transition_in_setup, platform_transition_out, platform_transition_out_setup,
+        display_room_info_text, handle_floor_plan_event, highlight_next_door,
+        platform_transition_in, platform_transition_in_setup, platform_transition_out,
+        platform_transition_out_setup, position_restart_badges, pulse_failing_rooms,
         remove_room_info_text, setup_quit_displaying_room_info_text_timer, update_wall_state,
     },
 };
@@ -41,6 +43,7 @@ impl Plugin for WorldPlugin {
         app.insert_resource(CurrentFloorPlan::default())
             .add_event::<DisplayRoomInfoEvent>()
             .insert_resource(NextFloorPlan::default())
+            .insert_resource(NavigationTarget::default())
             .insert_resource(load_world_config_from_lua())
             .add_systems(
                 Update,
@@ -49,6 +52,7 @@ impl Plugin for WorldPlugin {
                     display_room_info_text,
                     remove_room_info_text,
                     setup_quit_displaying_room_info_text_timer,
+                    highlight_next_door,
                 )
                     .run_if(in_state(GameState::InGame)),
             )
@@ -61,6 +65,8 @@ impl Plugin for WorldPlugin {
                     platform_transition_in_setup.run_if(in_state(GameState::TransitioningInSetup)),
                     platform_transition_in.run_if(in_state(GameState::TransitioningIn)),
                     update_wall_state,
+                    pulse_failing_rooms,
+                    position_restart_badges,
                 ),
             );
     }
@@ -1,33 +1,56 @@
 use super::world_component::{
-    CurrentFloorPlan, DisplayRoomInfoEvent, Floor, NextFloorPlan, PlatformMarker,
-    PlatformTransition, RoomInfoDisplayTimer, RoomInfoText, Wall, WallState, WorldConfig,
+    CurrentFloorPlan, DisplayRoomInfoEvent, Floor, LockedDoor, NavigationTarget, NextFloorPlan,
+    PlatformMarker, PlatformTransition, Pulsing, RestartBadge, RoomInfoDisplayTimer, RoomInfoText,
+    Wall, WallState, WorldConfig,
 };
 use crate::{
-    floorplan::{Door, FloorPlan, FloorPlanEvent, Room},
+    camera::camera_component::MainCamera,
+    floorplan::{Door, FloorPlan, FloorPlanEvent, Health, Room, RoomKind},
     state::GameState,
 };
 use avian3d::prelude::*;
 use bevy::{
     color::palettes::tailwind::{
-        BLUE_600, GRAY_500, GRAY_600, GREEN_600, ORANGE_600, PURPLE_600, RED_600, YELLOW_600,
+        AMBER_700, BLUE_600, CYAN_600, EMERALD_600, FUCHSIA_600, GRAY_500, GRAY_600, GREEN_600,
+        INDIGO_600, LIME_600, ORANGE_600, PURPLE_600, RED_500, RED_600, TEAL_600, YELLOW_300,
+        YELLOW_600, ZINC_800,
     },
     prelude::*,
 };
 use petgraph::prelude::*;
 use std::collections::HashMap;
 
-fn calculate_room_color(name: &str) -> Srgba {
-    match name {
-        n if n.contains("Deployment") => PURPLE_600,
-        n if n.contains("ReplicaSet") => ORANGE_600,
-        n if n.contains("Pod") => RED_600,
-        n if n.contains("Service") => BLUE_600,
-        n if n.contains("CofnigMap") => YELLOW_600,
-        n if n.contains("Hallway") => GREEN_600,
+const fn calculate_room_color(kind: &RoomKind) -> Srgba {
+    match kind {
+        RoomKind::Deployment | RoomKind::StatefulSet => PURPLE_600,
+        RoomKind::ReplicaSet => ORANGE_600,
+        RoomKind::CronJob | RoomKind::Job => LIME_600,
+        RoomKind::Pod => RED_600,
+        RoomKind::Service => BLUE_600,
+        RoomKind::ConfigMap => YELLOW_600,
+        RoomKind::Ingress | RoomKind::IngressHost | RoomKind::IngressPath => CYAN_600,
+        RoomKind::Secret => AMBER_700,
+        RoomKind::PersistentVolumeClaim | RoomKind::PersistentVolume | RoomKind::StorageClass => {
+            TEAL_600
+        }
+        RoomKind::Node => INDIGO_600,
+        RoomKind::Hallway | RoomKind::Namespace => GREEN_600,
+        RoomKind::Suite => EMERALD_600,
+        RoomKind::Multiverse => FUCHSIA_600,
         _ => GRAY_600,
     }
 }
 
+/// failing workloads are red regardless of kind, pending ones keep their kind color but dimmed
+fn calculate_room_health_color(room: &Room) -> Color {
+    let color = Color::from(calculate_room_color(&room.kind));
+    match room.health() {
+        Health::Healthy => color,
+        Health::Pending => color.darker(0.2),
+        Health::Failing => Color::from(RED_500),
+    }
+}
+
 pub fn handle_floor_plan_event(
     mut events: EventReader<FloorPlanEvent>,
     mut current_floorplan: ResMut<CurrentFloorPlan>,
@@ -65,14 +88,24 @@ fn process_floorplan_event(
         current_floorplan.previous_room = None;
         return true;
     }
-    // if current floor plan has changed then we need to update the on-deck floor plan
-    if let Some(plan) = &current_floorplan.floorplan
-        && plan != floorplan
-    {
-        next_floorplan.floorplan = Some(floorplan.clone());
-        next_floorplan.created = Some(time.elapsed());
+    if current_floorplan.rewound {
         return false;
     }
+    // if current floor plan has changed then we need to update the on-deck floor plan
+    if let Some(plan) = &current_floorplan.floorplan {
+        let diff = plan.diff(floorplan);
+        if diff.is_empty() {
+            // the external world changed back before we got around to using the on-deck plan
+            next_floorplan.floorplan = None;
+            next_floorplan.created = None;
+            next_floorplan.diff = None;
+        } else if next_floorplan.diff.as_ref() != Some(&diff) {
+            debug!("Floorplan changed: {}", diff.summary());
+            next_floorplan.floorplan = Some(floorplan.clone());
+            next_floorplan.created = Some(time.elapsed());
+            next_floorplan.diff = Some(diff);
+        }
+    }
 
     false
 }
@@ -101,6 +134,7 @@ pub fn platform_transition_in_setup(
         current_floorplan.refreshed = time.elapsed();
         next_floorplan.floorplan = None;
         next_floorplan.created = None;
+        next_floorplan.diff = None;
     }
     current_floorplan.time_in_room = time.elapsed();
 
@@ -176,6 +210,8 @@ pub fn platform_transition_in_setup(
     next_state.set(GameState::TransitioningIn);
 }
 
+const PENDING_ROOM_SCALE: f32 = 0.6;
+
 #[allow(clippy::too_many_arguments)]
 fn spawn_connected_room(
     world_config: &WorldConfig,
@@ -189,8 +225,11 @@ fn spawn_connected_room(
     is_exit: bool, // Whether this room is the previous room
     initial_y_offset: f32,
 ) -> Entity {
+    let health = room.health();
     let room_height = if is_exit {
         world_config.exit_room_y
+    } else if health == Health::Pending {
+        world_config.room_y * PENDING_ROOM_SCALE
     } else {
         world_config.room_y
     };
@@ -200,13 +239,22 @@ fn spawn_connected_room(
         room_height,
         world_config.room_z,
     ));
-    let mat = materials.add(Color::from(calculate_room_color(&room.name)));
-    let position = calculate_room_position(world_config, node_index, initial_y_offset, num_rooms);
+    let mat = materials.add(calculate_room_health_color(room));
+    // keep shortened rooms sitting on the floor
+    let y_offset = if is_exit {
+        initial_y_offset
+    } else {
+        initial_y_offset - (world_config.room_y - room_height) / 2.0
+    };
+    let position = calculate_room_position(world_config, node_index, y_offset, num_rooms);
     let collider = Collider::cuboid(world_config.room_x, room_height, world_config.room_z);
 
     let door = spawn_connected_room_door(world_config, commands, meshes, materials, door);
+    if room.locked.is_some() {
+        commands.entity(door).insert(LockedDoor);
+    }
 
-    commands
+    let room_entity = commands
         .spawn((
             Mesh3d(shape),
             MeshMaterial3d(mat),
@@ -217,7 +265,35 @@ fn spawn_connected_room(
             PlatformMarker::default(),
         ))
         .add_child(door)
-        .id()
+        .id();
+
+    if health == Health::Failing {
+        commands.entity(room_entity).insert(Pulsing);
+        commands.entity(door).insert(Pulsing);
+    }
+
+    if let Some(status) = &room.status
+        && status.restarts > 0
+    {
+        commands.spawn((
+            Text::new(format!("{} restarts", status.restarts)),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            BackgroundColor(Color::from(RED_600)),
+            Node {
+                position_type: PositionType::Absolute,
+                padding: UiRect::axes(Val::Px(4.0), Val::Px(2.0)),
+                ..default()
+            },
+            Visibility::Hidden,
+            RestartBadge { room: room_entity },
+        ));
+    }
+
+    room_entity
 }
 
 fn spawn_connected_room_door(
@@ -357,6 +433,102 @@ fn spawn_floor(
     floor_entity
 }
 
+fn next_door_towards_target(
+    current_floorplan: &CurrentFloorPlan,
+    navigation_target: &NavigationTarget,
+) -> Option<String> {
+    let floorplan = current_floorplan.floorplan.as_ref()?;
+    let you_are_here = current_floorplan.you_are_here.as_ref()?;
+    let target = navigation_target.room.as_ref()?;
+    match floorplan.shortest_path(&you_are_here.id, &target.id) {
+        Ok(path) => path.first().map(|door| door.id.clone()),
+        Err(e) => {
+            debug!("No route to {}: {e:?}", target.name);
+            None
+        }
+    }
+}
+
+/// recolor the doors on the current platform so the next one on the route to the target stands out
+pub fn highlight_next_door(
+    mut navigation_target: ResMut<NavigationTarget>,
+    current_floorplan: Res<CurrentFloorPlan>,
+    door_query: Query<(
+        Ref<Door>,
+        &MeshMaterial3d<StandardMaterial>,
+        Has<LockedDoor>,
+    )>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let doors_added = door_query.iter().any(|(door, _, _)| door.is_added());
+    if !doors_added && !navigation_target.is_changed() {
+        return;
+    }
+
+    if let (Some(target), Some(you_are_here)) =
+        (&navigation_target.room, &current_floorplan.you_are_here)
+        && target.id == you_are_here.id
+    {
+        debug!("Arrived at {}", target.name);
+        navigation_target.room = None;
+    }
+
+    let next_door_id = next_door_towards_target(&current_floorplan, &navigation_target);
+    for (door, material, locked) in &door_query {
+        if let Some(material) = materials.get_mut(&material.0) {
+            let color = if next_door_id.as_deref() == Some(door.id.as_str()) {
+                YELLOW_300
+            } else if locked {
+                ZINC_800
+            } else {
+                RED_600
+            };
+            material.base_color = Color::from(color);
+        }
+    }
+}
+
+/// failing rooms and their doors glow on and off
+pub fn pulse_failing_rooms(
+    time: Res<Time>,
+    query: Query<&MeshMaterial3d<StandardMaterial>, With<Pulsing>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let intensity = (time.elapsed_secs() * 4.0).sin().mul_add(0.5, 0.5);
+    for material in &query {
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.emissive = LinearRgba::from(RED_500) * intensity * 4.0;
+        }
+    }
+}
+
+/// keep each restart badge above its room, dropping badges whose room has been despawned
+pub fn position_restart_badges(
+    mut commands: Commands,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    room_query: Query<&GlobalTransform, With<Room>>,
+    mut badge_query: Query<(Entity, &RestartBadge, &mut Node, &mut Visibility)>,
+    world_config: Res<WorldConfig>,
+) {
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    for (entity, badge, mut node, mut visibility) in &mut badge_query {
+        let Ok(room_transform) = room_query.get(badge.room) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        let anchor = room_transform.translation() + Vec3::Y * world_config.room_y;
+        if let Ok(position) = camera.world_to_viewport(camera_transform, anchor) {
+            node.left = Val::Px(position.x);
+            node.top = Val::Px(position.y);
+            *visibility = Visibility::Inherited;
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
 pub fn update_wall_state(
     mut query: Query<(&mut WallState, &mut Visibility), With<Wall>>,
     time: Res<Time>,
@@ -465,15 +637,36 @@ pub fn platform_transition_out(
     }
 }
 
+fn describe_room(room: &Room) -> String {
+    let on = room
+        .context
+        .as_ref()
+        .map_or_else(String::new, |context| format!(" on {context}"));
+    let description = match (&room.kind, &room.namespace) {
+        (RoomKind::Unknown, _) => room.name.clone(),
+        (kind, Some(namespace)) => format!("{}\n({kind} in {namespace}{on})", room.name),
+        (kind, None) => format!("{}\n({kind}{on})", room.name),
+    };
+    let description = match &room.status {
+        Some(status) => format!("{description}\n{status}"),
+        None => description,
+    };
+    match &room.locked {
+        Some(reason) => format!("{description}\n{reason}"),
+        None => description,
+    }
+}
+
 pub fn display_room_info_text(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut events: EventReader<DisplayRoomInfoEvent>,
 ) {
     for event in events.read() {
+        let destination = describe_room(&event.room);
         let text = event.you_are_here.as_ref().map_or_else(
-            || event.room.name.to_string(),
-            |room| format!("{}\ndoor to\n{}", room.name, event.room.name),
+            || destination.clone(),
+            |room| format!("{}\n{}\n{destination}", room.name, event.door.describe()),
         );
 
         commands.spawn((